@@ -0,0 +1,92 @@
+//! Owner/Admin authentication for wallet-level management instructions.
+//!
+//! Policy templates, wallet config, proposals, ownership transfer, deferred
+//! cancellation and freeze all gate on the same check: a wallet-bound Owner
+//! or Admin Authority PDA authenticated over the instruction's signed payload.
+
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
+    error::AuthError,
+    state::{authority::AuthorityAccountHeader, wallet_config::WalletLimits, AccountDiscriminator},
+};
+
+/// Verifies the wallet and the Owner/Admin authority, then authenticates it
+/// against `data_payload` for instruction `discriminator` (a Secp256r1
+/// authority within the signature age of `limits`).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn authenticate_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet_pda: &AccountInfo,
+    admin_auth_pda: &AccountInfo,
+    limits: &WalletLimits<'_>,
+    authority_payload: &[u8],
+    data_payload: &[u8],
+    discriminator: u8,
+) -> ProgramResult {
+    if wallet_pda.owner() != program_id || admin_auth_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Validate Wallet discriminator
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Authority PDA must be writable (counter increment for Secp256r1)
+    if !admin_auth_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let admin_data = unsafe { admin_auth_pda.borrow_mut_data_unchecked() };
+    if admin_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let admin_header =
+        unsafe { std::ptr::read_unaligned(admin_data.as_ptr() as *const AuthorityAccountHeader) };
+
+    if admin_header.discriminator != AccountDiscriminator::Authority as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if admin_header.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only Owner (0) or Admin (1) can manage wallet-level configuration
+    if admin_header.role > 1 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    match admin_header.authority_type {
+        0 => {
+            Ed25519Authenticator.authenticate(
+                accounts,
+                admin_data,
+                &[],
+                data_payload,
+                &[discriminator],
+                program_id,
+            )?;
+        },
+        1 => {
+            Secp256r1Authenticator::new(limits)?.authenticate(
+                accounts,
+                admin_data,
+                authority_payload,
+                data_payload,
+                &[discriminator],
+                program_id,
+            )?;
+        },
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::auth::traits::Authenticator;
+use crate::auth::{check_authority_active, traits::Authenticator};
 use crate::state::authority::AuthorityAccountHeader;
 use assertions::sol_assert_bytes_eq;
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
@@ -18,6 +18,7 @@ impl Authenticator for Ed25519Authenticator {
         if authority_data.len() < std::mem::size_of::<AuthorityAccountHeader>() + 32 {
             return Err(ProgramError::InvalidAccountData);
         }
+        check_authority_active(authority_data)?;
 
         // Header is at specific offset, but we just need variable data here for key
         let header_size = std::mem::size_of::<AuthorityAccountHeader>();
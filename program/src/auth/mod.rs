@@ -1,3 +1,23 @@
+use pinocchio::{
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+};
+
+use crate::{error::AuthError, state::authority::read_authority_expiry};
+
+pub mod admin;
 pub mod ed25519;
+pub mod possession;
 pub mod secp256r1;
 pub mod traits;
+
+/// Rejects an authority whose expiry (`AUTHORITY_FLAG_EXPIRES`) has passed.
+/// Every authenticator checks it before verifying the signature.
+pub(crate) fn check_authority_active(authority_data: &[u8]) -> Result<(), ProgramError> {
+    if let Some(expiry) = read_authority_expiry(authority_data)? {
+        if Clock::get()?.slot > expiry.expires_at {
+            return Err(AuthError::AuthorityExpired.into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Proof of possession for newly registered authorities.
+//!
+//! `CreateWallet` and `AddAuthority` can require the new key itself to
+//! authenticate (`REGISTER_FLAG_PROVE_POSSESSION`), so a mistyped or foreign
+//! key cannot be registered. The check runs against the freshly written
+//! authority data: an Ed25519 key must sign the transaction, and a Secp256r1
+//! passkey must sign a WebAuthn assertion over the registration challenge
+//! (the new Authority PDA, counter 1), verified through the precompile
+//! introspection like any other Secp256r1 authentication. The authority is
+//! then marked `AUTHORITY_FLAG_VERIFIED`.
+
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
+    error::AuthError,
+    state::{
+        authority::{AuthorityAccountHeader, AUTHORITY_FLAG_VERIFIED},
+        wallet_config::WalletLimits,
+    },
+};
+
+/// Registration args flag: the new authority must prove possession of its key.
+pub const REGISTER_FLAG_PROVE_POSSESSION: u8 = 1 << 0;
+
+/// Authenticates a new authority against its own `authority_data` (header
+/// and key data) and marks it verified. `signed_payload` is the registration
+/// challenge; `auth_payload` is the Secp256r1 assertion (ignored for Ed25519),
+/// within the signature age of `limits`.
+pub fn prove_possession(
+    accounts: &[AccountInfo],
+    limits: &WalletLimits<'_>,
+    authority_data: &mut [u8],
+    auth_payload: &[u8],
+    signed_payload: &[u8],
+    discriminator: &[u8],
+    program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+    if authority_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let authority_type = unsafe {
+        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+    }
+    .authority_type;
+    match authority_type {
+        0 => Ed25519Authenticator.authenticate(
+            accounts,
+            authority_data,
+            &[],
+            signed_payload,
+            discriminator,
+            program_id,
+        )?,
+        1 => Secp256r1Authenticator::new(limits)?.authenticate(
+            accounts,
+            authority_data,
+            auth_payload,
+            signed_payload,
+            discriminator,
+            program_id,
+        )?,
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+
+    // Re-read: a Secp256r1 assertion advanced the counter
+    let mut header = unsafe {
+        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+    };
+    header.flags |= AUTHORITY_FLAG_VERIFIED;
+    unsafe {
+        std::ptr::write_unaligned(
+            authority_data.as_mut_ptr() as *mut AuthorityAccountHeader,
+            header,
+        );
+    }
+    Ok(())
+}
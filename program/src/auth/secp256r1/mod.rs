@@ -1,4 +1,7 @@
-use crate::{error::AuthError, state::authority::AuthorityAccountHeader};
+use crate::{
+    error::AuthError,
+    state::{authority::AuthorityAccountHeader, wallet_config::WalletLimits},
+};
 use pinocchio::{
     account_info::AccountInfo,
     program_error::ProgramError,
@@ -17,14 +20,24 @@ pub mod webauthn;
 use self::introspection::verify_secp256r1_instruction_data;
 use self::webauthn::{base64url_encode_no_pad, extract_top_level_string_field, AuthDataParser};
 
-use crate::auth::traits::Authenticator;
+use crate::auth::{check_authority_active, traits::Authenticator};
 use crate::utils::get_stack_height;
 
-/// Maximum age (in slots) for a Secp256r1 signature to be considered valid (~60 seconds).
-const MAX_SLOT_AGE: u64 = 150;
-
 /// Authenticator implementation for Secp256r1 (WebAuthn).
-pub struct Secp256r1Authenticator;
+pub struct Secp256r1Authenticator {
+    /// Freshness window of the signature's slot (the wallet's
+    /// `max_signature_age_slots`).
+    pub max_signature_age_slots: u64,
+}
+
+impl Secp256r1Authenticator {
+    /// An authenticator bound to the signature age limit of the wallet.
+    pub fn new(limits: &WalletLimits<'_>) -> Result<Self, ProgramError> {
+        Ok(Self {
+            max_signature_age_slots: limits.get()?.max_signature_age_slots,
+        })
+    }
+}
 
 impl Authenticator for Secp256r1Authenticator {
     /// Authenticates a Secp256r1 signature (WebAuthn/Passkeys).
@@ -68,16 +81,6 @@ impl Authenticator for Secp256r1Authenticator {
             return Err(AuthError::PermissionDenied.into());
         }
 
-        // Validate slot freshness using Clock sysvar
-        let clock = Clock::get()?;
-        let current_slot = clock.slot;
-        if slot > current_slot {
-            return Err(AuthError::InvalidSignatureAge.into());
-        }
-        if current_slot - slot >= MAX_SLOT_AGE {
-            return Err(AuthError::InvalidSignatureAge.into());
-        }
-
         let header_size = std::mem::size_of::<AuthorityAccountHeader>();
         if auth_data.len() < header_size {
             return Err(AuthError::InvalidAuthorityPayload.into());
@@ -86,6 +89,19 @@ impl Authenticator for Secp256r1Authenticator {
         let mut header = unsafe {
             std::ptr::read_unaligned(auth_data.as_ptr() as *const AuthorityAccountHeader)
         };
+        check_authority_active(auth_data)?;
+
+        // Validate slot freshness using Clock sysvar, within the wallet's
+        // signature age limit (~60 seconds by default)
+        let max_slot_age = self.max_signature_age_slots;
+        let clock = Clock::get()?;
+        let current_slot = clock.slot;
+        if slot > current_slot {
+            return Err(AuthError::InvalidSignatureAge.into());
+        }
+        if current_slot - slot >= max_slot_age {
+            return Err(AuthError::InvalidSignatureAge.into());
+        }
 
         // --- Odometer validation ---
         let expected_counter = header.counter.wrapping_add(1);
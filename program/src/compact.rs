@@ -1,5 +1,8 @@
+use std::borrow::Cow;
+
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
+
 /// Container for a set of compact instructions.
 ///
 /// This struct holds multiple compact instructions and provides
@@ -32,11 +35,72 @@ pub struct CompactInstruction {
 /// # Fields
 /// * `program_id_index` - Index of the program ID in the account list
 /// * `accounts` - Slice of account indexes
-/// * `data` - Slice of instruction data
+/// * `data` - Instruction data, still in its wire encoding
 pub struct CompactInstructionRef<'a> {
     pub program_id_index: u8,
     pub accounts: &'a [u8],
-    pub data: &'a [u8],
+    pub data: CompactData<'a>,
+}
+
+/// Instruction data of a `CompactInstructionRef`, borrowed from the wire bytes.
+///
+/// Only v2 streams produce the deduplicated variants; they are expanded once,
+/// at `decompress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactData<'a> {
+    /// Stored verbatim.
+    Literal(&'a [u8]),
+    /// The first bytes of an earlier instruction's literal data, then `suffix`.
+    SharedPrefix { prefix: &'a [u8], suffix: &'a [u8] },
+    /// Run-length segments (validated at parse) expanding to `len` bytes.
+    Segments { encoded: &'a [u8], len: usize },
+}
+
+impl<'a> CompactData<'a> {
+    /// Length of the expanded data.
+    pub fn len(&self) -> usize {
+        match self {
+            CompactData::Literal(data) => data.len(),
+            CompactData::SharedPrefix { prefix, suffix } => prefix.len() + suffix.len(),
+            CompactData::Segments { len, .. } => *len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Expands the data. Literal data is borrowed, not copied.
+    pub fn expand(&self) -> Cow<'a, [u8]> {
+        match *self {
+            CompactData::Literal(data) => Cow::Borrowed(data),
+            CompactData::SharedPrefix { prefix, suffix } => {
+                let mut data = Vec::with_capacity(prefix.len() + suffix.len());
+                data.extend_from_slice(prefix);
+                data.extend_from_slice(suffix);
+                Cow::Owned(data)
+            },
+            CompactData::Segments { encoded, len } => {
+                let mut data = Vec::with_capacity(len);
+                let mut rest = encoded;
+                while !rest.is_empty() {
+                    // Walked once already by `parse_segments`; cannot fail here.
+                    let Ok((header, tail)) = read_varint(rest) else {
+                        break;
+                    };
+                    let count = (header >> 1) as usize;
+                    if header & 1 == 1 {
+                        data.resize(data.len() + count, tail[0]);
+                        rest = &tail[1..];
+                    } else {
+                        data.extend_from_slice(&tail[..count]);
+                        rest = &tail[count..];
+                    }
+                }
+                Cow::Owned(data)
+            },
+        }
+    }
 }
 
 impl<'a> CompactInstructionRef<'a> {
@@ -68,6 +132,67 @@ impl<'a> CompactInstructionRef<'a> {
         let data = &bytes[data_start..data_start + data_len];
         let rest = &bytes[data_start + data_len..];
 
+        Ok((
+            CompactInstructionRef {
+                program_id_index,
+                accounts,
+                data: CompactData::Literal(data),
+            },
+            rest,
+        ))
+    }
+
+    /// Deserialize one v2 instruction. `previous` holds the instructions
+    /// already parsed from the same stream (shared-prefix sources).
+    /// Format: [program_id_index: u8][num_accounts: varint][accounts...][data_kind: u8][data...]
+    fn from_bytes_v2(
+        bytes: &'a [u8],
+        previous: &[CompactInstructionRef<'a>],
+    ) -> Result<(Self, &'a [u8]), ProgramError> {
+        let (&program_id_index, rest) = bytes
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (num_accounts, rest) = read_varint(rest)?;
+        let (accounts, rest) = split_at_checked(rest, num_accounts as usize)?;
+        let (&data_kind, rest) = rest
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        let (data, rest) = match data_kind {
+            DATA_KIND_LITERAL => {
+                let (len, rest) = read_varint(rest)?;
+                let (data, rest) = split_at_checked(rest, len as usize)?;
+                (CompactData::Literal(data), rest)
+            },
+            DATA_KIND_SHARED_PREFIX => {
+                let (&source, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                let (prefix_len, rest) = read_varint(rest)?;
+                let (suffix_len, rest) = read_varint(rest)?;
+                let (suffix, rest) = split_at_checked(rest, suffix_len as usize)?;
+                // The source must be an earlier instruction with literal data,
+                // so the prefix stays a borrowed slice.
+                let prefix = match previous.get(source as usize).map(|ix| ix.data) {
+                    Some(CompactData::Literal(data)) if data.len() >= prefix_len as usize => {
+                        &data[..prefix_len as usize]
+                    },
+                    _ => return Err(ProgramError::InvalidInstructionData),
+                };
+                (CompactData::SharedPrefix { prefix, suffix }, rest)
+            },
+            DATA_KIND_SEGMENTS => {
+                let (encoded_len, rest) = read_varint(rest)?;
+                let (encoded, rest) = split_at_checked(rest, encoded_len as usize)?;
+                let len = segments_len(encoded)?;
+                (CompactData::Segments { encoded, len }, rest)
+            },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        if data.len() > MAX_EXPANDED_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
         Ok((
             CompactInstructionRef {
                 program_id_index,
@@ -78,44 +203,167 @@ impl<'a> CompactInstructionRef<'a> {
         ))
     }
 
-    /// Decompress into a full Instruction without cloning the instruction
-    /// data. `account_infos` lifetime 'b is tracked separately from the
+    /// Decompress into a full Instruction. Literal data is borrowed from the
+    /// instruction data; only deduplicated v2 data is expanded into a buffer.
+    /// `account_infos` lifetime 'b is tracked separately from the
     /// instruction-data lifetime 'a.
     pub fn decompress<'b>(
         &self,
-        account_infos: &'b [AccountInfo],
+        resolver: &AccountResolver<'b>,
     ) -> Result<DecompressedInstructionRef<'a, 'b>, ProgramError> {
-        if (self.program_id_index as usize) >= account_infos.len() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        let program_id = account_infos[self.program_id_index as usize].key();
+        let program_id = resolver.resolve(self.program_id_index)?.key();
 
         let mut accounts: Vec<&AccountInfo> = Vec::with_capacity(self.accounts.len());
         for &index in self.accounts {
-            if (index as usize) >= account_infos.len() {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            accounts.push(&account_infos[index as usize]);
+            accounts.push(resolver.resolve(index)?);
         }
 
         Ok(DecompressedInstructionRef {
             program_id,
             accounts,
-            data: self.data,
+            data: self.data.expand(),
         })
     }
 }
 
+/// Reserved index byte for the executing instruction's payer.
+pub const RESERVED_PAYER_INDEX: u8 = 0xFD;
+/// Reserved index byte for the wallet PDA.
+pub const RESERVED_WALLET_INDEX: u8 = 0xFE;
+/// Reserved index byte for the vault PDA.
+pub const RESERVED_VAULT_INDEX: u8 = 0xFF;
+
+/// Resolves compact-instruction index bytes to accounts.
+///
+/// In a v1 stream every index byte is a position in the instruction's
+/// accounts. v2 streams use the extended index space:
+/// * `0x00..=0xFC` — position in the instruction's accounts
+/// * `0xFD` / `0xFE` / `0xFF` — the payer / wallet / vault of the executing
+///   instruction, wherever its account layout puts them
+pub struct AccountResolver<'b> {
+    accounts: &'b [AccountInfo],
+    /// Positions of the payer, wallet and vault in `accounts`.
+    reserved: [usize; 3],
+    /// Whether the extended (v2) index space applies.
+    extended: bool,
+}
+
+impl<'b> AccountResolver<'b> {
+    /// Resolver for v2 streams. `reserved` holds the positions of payer,
+    /// wallet and vault.
+    pub fn new(accounts: &'b [AccountInfo], reserved: [usize; 3]) -> Self {
+        Self {
+            accounts,
+            reserved,
+            extended: true,
+        }
+    }
+
+    /// Resolver for v1 streams: every index byte is an account position.
+    pub fn positional(accounts: &'b [AccountInfo]) -> Self {
+        Self {
+            accounts,
+            reserved: [0; 3],
+            extended: false,
+        }
+    }
+
+    pub fn accounts(&self) -> &'b [AccountInfo] {
+        self.accounts
+    }
+
+    pub fn resolve(&self, index: u8) -> Result<&'b AccountInfo, ProgramError> {
+        let position = match index {
+            _ if !self.extended => index as usize,
+            RESERVED_PAYER_INDEX => self.reserved[0],
+            RESERVED_WALLET_INDEX => self.reserved[1],
+            RESERVED_VAULT_INDEX => self.reserved[2],
+            _ => index as usize,
+        };
+        self.accounts
+            .get(position)
+            .ok_or(ProgramError::InvalidInstructionData)
+    }
+}
+
 /// Zero-copy variant of DecompressedInstruction. `data` borrows from the
-/// original instruction_data — no clone.
+/// original instruction_data unless it had to be expanded.
 pub struct DecompressedInstructionRef<'a, 'b> {
     pub program_id: &'b Pubkey,
     pub accounts: Vec<&'b AccountInfo>,
-    pub data: &'a [u8],
+    pub data: Cow<'a, [u8]>,
+}
+
+/// First byte of a v2 stream. A v1 stream starts with its instruction count
+/// (at most `MAX_COMPACT_INSTRUCTIONS`), so a byte with bit 7 set is a version tag.
+pub const COMPACT_FORMAT_V2: u8 = 0x82;
+
+/// Whether `bytes` is a v2 stream (and so uses the extended index space).
+#[inline]
+pub fn is_compact_v2(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&COMPACT_FORMAT_V2)
+}
+
+/// Upper bound on a single instruction's expanded data (the runtime's CPI
+/// data limit). Keeps run-length segments from expanding without bound.
+pub const MAX_EXPANDED_DATA_LEN: usize = 10 * 1024;
+
+/// v2 data kinds.
+const DATA_KIND_LITERAL: u8 = 0;
+const DATA_KIND_SHARED_PREFIX: u8 = 1;
+const DATA_KIND_SEGMENTS: u8 = 2;
+
+/// Reads an LEB128 varint of at most 3 bytes whose value fits in a u16.
+fn read_varint(bytes: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+    let mut value: u32 = 0;
+    for (i, &byte) in bytes.iter().enumerate().take(3) {
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            let value = u16::try_from(value).map_err(|_| ProgramError::InvalidInstructionData)?;
+            return Ok((value, &bytes[i + 1..]));
+        }
+    }
+    Err(ProgramError::InvalidInstructionData)
+}
+
+/// Appends `value` as an LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u16) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn split_at_checked(bytes: &[u8], mid: usize) -> Result<(&[u8], &[u8]), ProgramError> {
+    if bytes.len() < mid {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(bytes.split_at(mid))
+}
+
+/// Walks run-length segments and returns their expanded length.
+///
+/// Each segment starts with a varint header `(n << 1) | kind`: kind 0 is a
+/// literal of `n` bytes, kind 1 repeats the following byte `n` times.
+fn segments_len(mut encoded: &[u8]) -> Result<usize, ProgramError> {
+    let mut len = 0usize;
+    while !encoded.is_empty() {
+        let (header, rest) = read_varint(encoded)?;
+        let count = (header >> 1) as usize;
+        let consumed = if header & 1 == 1 { 1 } else { count };
+        encoded = split_at_checked(rest, consumed)?.1;
+        len += count;
+        if len > MAX_EXPANDED_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    }
+    Ok(len)
 }
 
 /// Parse + return total bytes consumed (ref-based, no allocations for
-/// account index bytes or instruction data).
+/// account index bytes or instruction data). Accepts both encodings:
+/// v1 (`[num_instructions: u8]...`) and v2 (`[COMPACT_FORMAT_V2]...`).
 pub fn parse_compact_instructions_ref_with_len<'a>(
     bytes: &'a [u8],
 ) -> Result<(Vec<CompactInstructionRef<'a>>, usize), ProgramError> {
@@ -123,16 +371,24 @@ pub fn parse_compact_instructions_ref_with_len<'a>(
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    let num_instructions = bytes[0] as usize;
+    let v2 = is_compact_v2(bytes);
+    let (num_instructions, mut remaining) = if v2 {
+        let (count, rest) = read_varint(&bytes[1..])?;
+        (count as usize, rest)
+    } else {
+        (bytes[0] as usize, &bytes[1..])
+    };
     if num_instructions > MAX_COMPACT_INSTRUCTIONS {
         return Err(ProgramError::InvalidInstructionData);
     }
 
     let mut instructions = Vec::with_capacity(num_instructions);
-    let mut remaining = &bytes[1..];
-
     for _ in 0..num_instructions {
-        let (ix, rest) = CompactInstructionRef::from_bytes(remaining)?;
+        let (ix, rest) = if v2 {
+            CompactInstructionRef::from_bytes_v2(remaining, &instructions)?
+        } else {
+            CompactInstructionRef::from_bytes(remaining)?
+        };
         instructions.push(ix);
         remaining = rest;
     }
@@ -305,6 +561,103 @@ pub fn serialize_compact_instructions(instructions: &[CompactInstruction]) -> Ve
     compact_instructions.into_bytes()
 }
 
+/// Serialize multiple CompactInstructions in the v2 encoding.
+///
+/// Each instruction's data is written verbatim, as a shared prefix of an
+/// earlier verbatim instruction, or as run-length segments — whichever is
+/// shortest. Account indexes are written as given; use the reserved indexes
+/// (`RESERVED_PAYER_INDEX`, ...) to reference the payer, wallet and vault.
+pub fn serialize_compact_instructions_v2(instructions: &[CompactInstruction]) -> Vec<u8> {
+    assert!(
+        instructions.len() <= MAX_COMPACT_INSTRUCTIONS,
+        "instruction count exceeds MAX_COMPACT_INSTRUCTIONS"
+    );
+    let mut bytes = vec![COMPACT_FORMAT_V2];
+    write_varint(&mut bytes, instructions.len() as u16);
+
+    // Only verbatim data can be a shared-prefix source
+    let mut is_literal = [false; MAX_COMPACT_INSTRUCTIONS];
+    for (i, ix) in instructions.iter().enumerate() {
+        let accounts_len = u16::try_from(ix.accounts.len()).expect("account count exceeds u16 max");
+        let data_len = u16::try_from(ix.data.len()).expect("data length exceeds u16 max");
+        bytes.push(ix.program_id_index);
+        write_varint(&mut bytes, accounts_len);
+        bytes.extend_from_slice(&ix.accounts);
+
+        let mut best = vec![DATA_KIND_LITERAL];
+        write_varint(&mut best, data_len);
+        best.extend_from_slice(&ix.data);
+        is_literal[i] = true;
+
+        for (j, source) in instructions[..i].iter().enumerate() {
+            if !is_literal[j] {
+                continue;
+            }
+            let common = source
+                .data
+                .iter()
+                .zip(ix.data.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let mut candidate = vec![DATA_KIND_SHARED_PREFIX, j as u8];
+            write_varint(&mut candidate, common as u16);
+            write_varint(&mut candidate, data_len - common as u16);
+            candidate.extend_from_slice(&ix.data[common..]);
+            if candidate.len() < best.len() {
+                best = candidate;
+                is_literal[i] = false;
+            }
+        }
+
+        let segments = encode_segments(&ix.data);
+        if let Ok(encoded_len) = u16::try_from(segments.len()) {
+            let mut candidate = vec![DATA_KIND_SEGMENTS];
+            write_varint(&mut candidate, encoded_len);
+            candidate.extend_from_slice(&segments);
+            if candidate.len() < best.len() {
+                best = candidate;
+                is_literal[i] = false;
+            }
+        }
+
+        bytes.extend_from_slice(&best);
+    }
+    bytes
+}
+
+/// Run-length encodes `data`: runs of 4+ identical bytes become run segments,
+/// everything else literal segments (see `segments_len`).
+fn encode_segments(data: &[u8]) -> Vec<u8> {
+    const MAX_SEGMENT: usize = 0x7FFF;
+    let mut out = Vec::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+    while i < data.len() {
+        let run = data[i..]
+            .iter()
+            .take(MAX_SEGMENT)
+            .take_while(|&&b| b == data[i])
+            .count();
+        if run < 4 {
+            i += 1;
+            continue;
+        }
+        for chunk in data[literal_start..i].chunks(MAX_SEGMENT) {
+            write_varint(&mut out, (chunk.len() as u16) << 1);
+            out.extend_from_slice(chunk);
+        }
+        write_varint(&mut out, ((run as u16) << 1) | 1);
+        out.push(data[i]);
+        i += run;
+        literal_start = i;
+    }
+    for chunk in data[literal_start..].chunks(MAX_SEGMENT) {
+        write_varint(&mut out, (chunk.len() as u16) << 1);
+        out.extend_from_slice(chunk);
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -488,6 +841,179 @@ mod tests {
         assert_eq!(parsed[0].data, vec![0xAB, 0xCD]);
     }
 
+    #[test]
+    fn test_extended_index_space_is_v2_only() {
+        // v1: a reserved byte is a plain position (out of range here).
+        assert_eq!(
+            AccountResolver::positional(&[])
+                .resolve(RESERVED_VAULT_INDEX)
+                .err(),
+            Some(ProgramError::InvalidInstructionData)
+        );
+
+        assert!(is_compact_v2(&[COMPACT_FORMAT_V2, 0]));
+        assert!(!is_compact_v2(&[1, 0, 0, 0, 0]));
+        assert!(!is_compact_v2(&[]));
+    }
+
+    fn expanded(bytes: &[u8]) -> Vec<Vec<u8>> {
+        let (parsed, consumed) = parse_compact_instructions_ref_with_len(bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        parsed
+            .iter()
+            .map(|ix| ix.data.expand().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_varint_roundtrip() {
+        for value in [0u16, 1, 127, 128, 300, 16_383, 16_384, u16::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value);
+            assert!(bytes.len() <= 3);
+            let (decoded, rest) = read_varint(&bytes).unwrap();
+            assert_eq!(decoded, value);
+            assert!(rest.is_empty());
+        }
+        // Unterminated and > u16 values are rejected
+        assert!(read_varint(&[0x80, 0x80, 0x80]).is_err());
+        assert!(read_varint(&[0xFF, 0xFF, 0x7F]).is_err());
+    }
+
+    #[test]
+    fn test_ref_parser_accepts_v1() {
+        let instructions = vec![CompactInstruction {
+            program_id_index: 4,
+            accounts: vec![0, 3],
+            data: vec![1, 2, 3],
+        }];
+        let bytes = serialize_compact_instructions(&instructions);
+        let (parsed, consumed) = parse_compact_instructions_ref_with_len(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(parsed[0].program_id_index, 4);
+        assert_eq!(parsed[0].accounts, &[0, 3]);
+        assert_eq!(parsed[0].data, CompactData::Literal(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_v2_roundtrip_with_reserved_indexes() {
+        let instructions = vec![
+            CompactInstruction {
+                program_id_index: 4,
+                accounts: vec![RESERVED_VAULT_INDEX, 5],
+                data: vec![2, 0, 0, 0, 0x40, 0x42, 0x0F, 0, 0, 0, 0, 0],
+            },
+            CompactInstruction {
+                program_id_index: 6,
+                accounts: vec![RESERVED_PAYER_INDEX, RESERVED_WALLET_INDEX],
+                data: vec![0xAB, 0xCD],
+            },
+        ];
+        let bytes = serialize_compact_instructions_v2(&instructions);
+        assert_eq!(bytes[0], COMPACT_FORMAT_V2);
+
+        let (parsed, _) = parse_compact_instructions_ref_with_len(&bytes).unwrap();
+        assert_eq!(parsed[0].accounts, &[RESERVED_VAULT_INDEX, 5]);
+        assert_eq!(parsed[1].program_id_index, 6);
+        assert_eq!(
+            expanded(&bytes),
+            vec![instructions[0].data.clone(), instructions[1].data.clone()]
+        );
+    }
+
+    #[test]
+    fn test_v2_shares_prefix_with_earlier_instruction() {
+        // Two transfers that differ only in the amount's low byte
+        let mut first = vec![3u8];
+        first.extend(0u8..40);
+        let mut second = first.clone();
+        *second.last_mut().unwrap() = 0x22;
+        let instructions = vec![
+            CompactInstruction {
+                program_id_index: 0,
+                accounts: vec![1],
+                data: first.clone(),
+            },
+            CompactInstruction {
+                program_id_index: 0,
+                accounts: vec![2],
+                data: second.clone(),
+            },
+        ];
+
+        let bytes = serialize_compact_instructions_v2(&instructions);
+        assert!(bytes.len() < serialize_compact_instructions(&instructions).len());
+
+        let (parsed, _) = parse_compact_instructions_ref_with_len(&bytes).unwrap();
+        assert!(matches!(parsed[1].data, CompactData::SharedPrefix { .. }));
+        assert_eq!(expanded(&bytes), vec![first, second]);
+    }
+
+    #[test]
+    fn test_v2_run_length_segments() {
+        let mut data = vec![9u8, 8];
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(&[7, 6, 5]);
+        let instructions = vec![CompactInstruction {
+            program_id_index: 0,
+            accounts: vec![],
+            data: data.clone(),
+        }];
+
+        let bytes = serialize_compact_instructions_v2(&instructions);
+        let (parsed, _) = parse_compact_instructions_ref_with_len(&bytes).unwrap();
+        assert!(matches!(
+            parsed[0].data,
+            CompactData::Segments { len: 69, .. }
+        ));
+        assert_eq!(expanded(&bytes), vec![data]);
+    }
+
+    #[test]
+    fn test_v2_rejects_malformed_streams() {
+        // Unknown version tag
+        assert!(parse_compact_instructions_ref_with_len(&[0x83, 0]).is_err());
+
+        // Shared prefix referencing an instruction that does not precede it
+        let forward = [COMPACT_FORMAT_V2, 1, 0, 0, DATA_KIND_SHARED_PREFIX, 0, 0, 0];
+        assert!(parse_compact_instructions_ref_with_len(&forward).is_err());
+
+        // Prefix longer than the source's data
+        let too_long = [
+            COMPACT_FORMAT_V2,
+            2,
+            0,
+            0,
+            DATA_KIND_LITERAL,
+            1,
+            0xAA,
+            0,
+            0,
+            DATA_KIND_SHARED_PREFIX,
+            0,
+            2,
+            0,
+        ];
+        assert!(parse_compact_instructions_ref_with_len(&too_long).is_err());
+
+        // Run expanding past MAX_EXPANDED_DATA_LEN
+        let mut bomb = vec![COMPACT_FORMAT_V2, 1, 0, 0, DATA_KIND_SEGMENTS];
+        let mut segments = Vec::new();
+        for _ in 0..2 {
+            write_varint(&mut segments, (0x2000 << 1) | 1);
+            segments.push(0);
+        }
+        write_varint(&mut bomb, segments.len() as u16);
+        bomb.extend_from_slice(&segments);
+        assert!(parse_compact_instructions_ref_with_len(&bomb).is_err());
+
+        // Truncated literal
+        assert!(
+            parse_compact_instructions_ref_with_len(&[COMPACT_FORMAT_V2, 1, 0, 0, 0, 4, 1])
+                .is_err()
+        );
+    }
+
     /// Test demonstrating Issue #11 fix concept:
     /// Same indices with different account orderings should produce different extended payloads
     #[test]
@@ -4,8 +4,10 @@ use pinocchio::{
 };
 
 use crate::processor::{
-    authorize, create_session, create_wallet, execute, execute_deferred, manage_authority,
-    reclaim_deferred, revoke_session, transfer_ownership,
+    append_session_actions, authorize, cancel_deferred, create_session, create_wallet,
+    deferred_buffer, execute, execute_deferred, freeze, manage_authority, policy_template,
+    proposal, reclaim_deferred, revoke_session, rotate_session, timelock, transfer_ownership,
+    wallet_config,
 };
 
 entrypoint!(process_instruction);
@@ -32,6 +34,25 @@ pub fn process_instruction(
         7 => execute_deferred::process(program_id, accounts, data),
         8 => reclaim_deferred::process(program_id, accounts, data),
         9 => revoke_session::process(program_id, accounts, data),
+        10 => rotate_session::process(program_id, accounts, data),
+        11 => policy_template::process_create_policy_template(program_id, accounts, data),
+        12 => policy_template::process_update_policy_template(program_id, accounts, data),
+        13 => append_session_actions::process(program_id, accounts, data),
+        15 => deferred_buffer::process_write_deferred_buffer(program_id, accounts, data),
+        16 => cancel_deferred::process(program_id, accounts, data),
+        17 => authorize::process_recurring(program_id, accounts, data),
+        18 => authorize::process_conditional(program_id, accounts, data),
+        19 => authorize::process_until(program_id, accounts, data),
+        20 => wallet_config::process_set_wallet_config(program_id, accounts, data),
+        21 => proposal::process_create_proposal(program_id, accounts, data),
+        22 => proposal::process_approve_proposal(program_id, accounts, data),
+        23 => timelock::process_queue_execute(program_id, accounts, data),
+        24 => transfer_ownership::process_propose_ownership(program_id, accounts, data),
+        25 => transfer_ownership::process_accept_ownership(program_id, accounts, data),
+        26 => transfer_ownership::process_cancel_ownership_transfer(program_id, accounts, data),
+        27 => manage_authority::process_close_expired_authority(program_id, accounts, data),
+        28 => freeze::process_freeze(program_id, accounts, data),
+        29 => freeze::process_unfreeze(program_id, accounts, data),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -37,6 +37,24 @@ pub enum AuthError {
     SessionVaultOwnerChanged = 3030,
     SessionVaultDataLenChanged = 3031,
     SessionTokenAuthorityChanged = 3032,
+    SessionCreatorRevoked = 3033,
+    ActionPayerNotAllowed = 3034,
+    InvalidPolicyTemplate = 3035,
+    SessionNotFinalized = 3036,
+    ActionUnsupported = 3037,
+    InvalidDeferredBuffer = 3039,
+    DeferredIntervalNotElapsed = 3040,
+    DeferredConditionNotMet = 3041,
+    InvalidDeferredPredicate = 3042,
+    InvalidWalletConfig = 3043,
+    InvalidProposal = 3044,
+    ProposalAlreadyApproved = 3045,
+    ProposalThresholdNotMet = 3046,
+    TimelockRequired = 3047,
+    TimelockNotElapsed = 3048,
+    StepUpRequired = 3049,
+    AuthorityExpired = 3050,
+    WalletFrozen = 3051,
 }
 
 impl From<AuthError> for ProgramError {
@@ -18,14 +18,25 @@ pub enum ProgramIx {
     #[account(3, writable, name = "authority", desc = "Initial owner authority PDA")]
     #[account(4, name = "system_program", desc = "System Program")]
     #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        optional,
+        name = "auth_extra",
+        desc = "Proof of possession: owner keypair signer (Ed25519) or sysvar instructions (Secp256r1)"
+    )]
     CreateWallet {
         user_seed: Vec<u8>,
         auth_type: u8,
         auth_pubkey: [u8; 33],
         credential_hash: [u8; 32],
+        flags: u8,
     },
 
     /// Add a new authority to the wallet
+    ///
+    /// With `flags` bit 1 (expires) the authority data is followed by
+    /// `expires_at: u64`; the payer is recorded as funder for
+    /// CloseExpiredAuthority.
     #[account(0, signer, writable, name = "payer", desc = "Payer and rent contributor")]
     #[account(1, name = "wallet", desc = "Wallet PDA")]
     #[account(
@@ -54,6 +65,7 @@ pub enum ProgramIx {
         new_pubkey: [u8; 33],
         new_hash: [u8; 32],
         new_role: u8,
+        flags: u8,
     },
 
     /// Remove an authority from the wallet
@@ -170,8 +182,9 @@ pub enum ProgramIx {
 
     /// Authorize deferred execution (TX1 of 2-transaction flow)
     ///
-    /// Verifies Secp256r1 signature over instruction/account hashes, then creates
-    /// a DeferredExec PDA storing the authorization for later execution.
+    /// Authenticates an Owner/Admin Authority (Ed25519 or Secp256r1) or a
+    /// Session over instruction/account hashes, then creates a DeferredExec PDA
+    /// storing the authorization for later execution.
     #[account(
         0,
         signer,
@@ -184,7 +197,7 @@ pub enum ProgramIx {
         2,
         writable,
         name = "authority",
-        desc = "Authority PDA (counter incremented)"
+        desc = "Authority or Session PDA (nonce incremented)"
     )]
     #[account(
         3,
@@ -196,8 +209,8 @@ pub enum ProgramIx {
     #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
     #[account(
         6,
-        name = "sysvar_instructions",
-        desc = "Sysvar Instructions (for Secp256r1 precompile introspection)"
+        name = "auth_extra",
+        desc = "Ed25519 / Session: signer keypair | Secp256r1: Sysvar Instructions"
     )]
     Authorize {
         instructions_hash: [u8; 32],
@@ -208,7 +221,12 @@ pub enum ProgramIx {
     /// Execute a previously authorized deferred execution (TX2 of 2-transaction flow)
     ///
     /// Verifies compact instructions against stored hashes, executes via CPI
-    /// with vault PDA signing, then closes the DeferredExec account.
+    /// with vault PDA signing, then closes the DeferredExec account (multi-use:
+    /// records the run, closing on the last one). Empty
+    /// `instructions` executes from the Deferred Buffer and closes it too.
+    /// An Authority-authorized DeferredExec needs its authorizer's Authority
+    /// PDA among the remaining accounts; a session-authorized one its writable
+    /// Session PDA, and it runs under the session's actions.
     #[account(0, signer, writable, name = "payer", desc = "Transaction payer")]
     #[account(1, name = "wallet", desc = "Wallet PDA")]
     #[account(2, writable, name = "vault", desc = "Vault PDA (signer for CPI)")]
@@ -224,6 +242,13 @@ pub enum ProgramIx {
         name = "refund_destination",
         desc = "Account to receive rent refund from closed DeferredExec"
     )]
+    #[account(
+        5,
+        optional,
+        writable,
+        name = "deferred_buffer",
+        desc = "Deferred Buffer PDA (only when instructions is empty)"
+    )]
     ExecuteDeferred { instructions: Vec<u8> },
 
     /// Reclaim an expired DeferredExec account and refund rent
@@ -247,6 +272,13 @@ pub enum ProgramIx {
         name = "refund_destination",
         desc = "Account to receive rent refund"
     )]
+    #[account(
+        3,
+        optional,
+        writable,
+        name = "deferred_buffer",
+        desc = "Deferred Buffer PDA to close along with it"
+    )]
     ReclaimDeferred,
 
     /// Revoke a session key early (before expiry)
@@ -274,6 +306,673 @@ pub enum ProgramIx {
         desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
     )]
     RevokeSession,
+
+    /// Rotate a session to a new session key, keeping its state
+    ///
+    /// Only Owner or Admin can rotate. Copies the header and action buffer (spent
+    /// counters intact) into the new key's PDA and closes the old one.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer for the new session; receives the old session's rent"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "admin_authority",
+        desc = "Owner/Admin authority PDA (counter incremented for Secp256r1)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "old_session",
+        desc = "Session PDA to rotate (closed)"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "new_session",
+        desc = "Session PDA for the new key (created)"
+    )]
+    #[account(5, name = "system_program", desc = "System Program")]
+    #[account(
+        6,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    RotateSession { new_session_key: [u8; 32] },
+
+    /// Create a wallet-scoped policy template
+    ///
+    /// Only Owner or Admin can create. Followed by `[actions_len(2)][actions]`;
+    /// sessions created with the template flag enforce these actions.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer for the template account"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "admin_authority",
+        desc = "Owner/Admin authority PDA (counter incremented for Secp256r1)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "policy_template",
+        desc = "Policy Template PDA (created)"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    CreatePolicyTemplate { template_id: u32 },
+
+    /// Replace a policy template's actions
+    ///
+    /// Only Owner or Admin can update. Data is `[actions_len(2)][actions]`; the
+    /// stateful actions must keep their order and mints.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Pays for growth; receives refunded rent on shrink"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "admin_authority",
+        desc = "Owner/Admin authority PDA (counter incremented for Secp256r1)"
+    )]
+    #[account(3, writable, name = "policy_template", desc = "Policy Template PDA")]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    UpdatePolicyTemplate,
+
+    /// Append a chunk to a staged session's action buffer
+    ///
+    /// Only the session's creator authority can append. Followed by
+    /// `[chunk_len(2)][chunk]`; `finalize = 1` validates the whole buffer and
+    /// makes the session usable.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Pays for the account growth"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "admin_authority",
+        desc = "Creator authority PDA (counter incremented for Secp256r1)"
+    )]
+    #[account(3, writable, name = "session", desc = "Staged Session PDA")]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    AppendSessionActions { finalize: u8 },
+
+    /// Unassigned; keeps the discriminators of the instructions below stable.
+    /// The program rejects it with `InvalidInstructionData`.
+    Reserved14,
+
+    /// Append a chunk to a DeferredExec's payload buffer
+    ///
+    /// Only the DeferredExec payer can write, before expiry. Followed by the
+    /// chunk bytes; `offset` must equal the bytes written so far.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "DeferredExec payer (pays for the buffer)"
+    )]
+    #[account(1, name = "deferred_exec", desc = "DeferredExec PDA")]
+    #[account(
+        2,
+        writable,
+        name = "deferred_buffer",
+        desc = "Deferred Buffer PDA (created on the first write)"
+    )]
+    #[account(3, name = "system_program", desc = "System Program")]
+    WriteDeferredBuffer { offset: u32 },
+
+    /// Cancel a pending deferred authorization before it expires
+    ///
+    /// Any Owner/Admin, or the Session that authorized it, closes the
+    /// DeferredExec PDA. Rent is refunded to the stored payer. Followed by the
+    /// Secp256r1 auth payload, if any.
+    #[account(0, signer, name = "payer", desc = "Transaction payer")]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "canceller",
+        desc = "Owner/Admin Authority PDA or the authorizing Session PDA"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "deferred_exec",
+        desc = "DeferredExec PDA (closed)"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "refund_destination",
+        desc = "Stored DeferredExec payer (receives rent refund)"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519 / Session: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    CancelDeferred,
+
+    /// Authorize a multi-use deferred execution (e.g. recurring DCA)
+    ///
+    /// Same as `Authorize`, but `ExecuteDeferred` may run the authorization up
+    /// to `max_executions` times, at least `min_interval_slots` apart. The last
+    /// run closes the DeferredExec PDA.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "authority",
+        desc = "Authority or Session PDA (nonce incremented)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "deferred_exec",
+        desc = "DeferredExec PDA to be created"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        name = "auth_extra",
+        desc = "Ed25519 / Session: signer keypair | Secp256r1: Sysvar Instructions"
+    )]
+    AuthorizeRecurring {
+        instructions_hash: [u8; 32],
+        accounts_hash: [u8; 32],
+        expiry_offset: u16,
+        max_executions: u16,
+        min_interval_slots: u32,
+    },
+
+    /// Authorize a conditional deferred execution (limit order / stop-loss)
+    ///
+    /// Same as `AuthorizeRecurring`, plus a signed predicate list stored with
+    /// the DeferredExec. `ExecuteDeferred` runs only when every predicate holds
+    /// (each reads a value at an offset of a named account and compares it).
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "authority",
+        desc = "Authority or Session PDA (nonce incremented)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "deferred_exec",
+        desc = "DeferredExec PDA to be created"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        name = "auth_extra",
+        desc = "Ed25519 / Session: signer keypair | Secp256r1: Sysvar Instructions"
+    )]
+    AuthorizeConditional {
+        instructions_hash: [u8; 32],
+        accounts_hash: [u8; 32],
+        expiry_offset: u16,
+        max_executions: u16,
+        min_interval_slots: u32,
+        predicates: Vec<u8>,
+    },
+
+    /// Authorize a deferred execution with an absolute expiry slot
+    ///
+    /// Same as `Authorize`, but `expires_at` is an absolute slot (part of the
+    /// signed payload) and may lie up to the wallet's configured maximum
+    /// deferred lifetime ahead (~1 hour without a Wallet Config PDA).
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "authority",
+        desc = "Authority or Session PDA (nonce incremented)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "deferred_exec",
+        desc = "DeferredExec PDA to be created"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        name = "auth_extra",
+        desc = "Ed25519 / Session: signer keypair | Secp256r1: Sysvar Instructions"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "wallet_config",
+        desc = "Wallet Config PDA (required with WALLET_FLAG_LIMITS)"
+    )]
+    AuthorizeUntil {
+        instructions_hash: [u8; 32],
+        accounts_hash: [u8; 32],
+        expires_at: u64,
+    },
+
+    /// Set the wallet-level configuration (Owner only)
+    ///
+    /// Creates or overwrites the Wallet Config PDA. Each value must be within
+    /// its program-wide bound; an enabled timelock can only be tightened.
+    /// Each `timelock_mints` / `step_up_mints` entry is a mint followed by its
+    /// max outflow (u64 LE).
+    /// The security limits follow; `flags` bit 0 lets Admins add Spenders.
+    /// Then the freeze settings: a `guardian` key (all zeros = none) and the
+    /// `unfreeze_delay_slots`. While the wallet is frozen, only the guardian
+    /// may change.
+    /// Followed by the Secp256r1 auth payload, if any.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "wallet",
+        desc = "Wallet PDA (timelock, step-up and limits flags)"
+    )]
+    #[account(2, writable, name = "owner_authority", desc = "Owner Authority PDA")]
+    #[account(
+        3,
+        writable,
+        name = "wallet_config",
+        desc = "Wallet Config PDA (created on first use)"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    SetWalletConfig {
+        max_deferred_expiry_slots: u64,
+        proposal_threshold: u8,
+        timelock_slots: u64,
+        timelock_max_lamports: u64,
+        sensitive_programs: Vec<[u8; 32]>,
+        timelock_mints: Vec<[u8; 40]>,
+        step_up_authority: [u8; 32],
+        step_up_lamports: u64,
+        step_up_mints: Vec<[u8; 40]>,
+        max_signature_age_slots: u64,
+        max_session_slots: u64,
+        min_deferred_expiry_slots: u64,
+        flags: u8,
+        guardian: [u8; 32],
+        unfreeze_delay_slots: u64,
+    },
+
+    /// Create a multi-approver proposal (Owner/Admin)
+    ///
+    /// Creates a DeferredExec PDA of kind proposal storing the hashes, the
+    /// approver Authority PDAs and the wallet's `proposal_threshold`. Once the
+    /// threshold is met, anyone can run it once with `ExecuteDeferred`. The
+    /// proposer's signature counts as its approval if it is an approver.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "proposer",
+        desc = "Owner/Admin Authority PDA (counter incremented)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "proposal",
+        desc = "Proposal (DeferredExec) PDA to be created"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: Sysvar Instructions"
+    )]
+    #[account(
+        7,
+        optional,
+        name = "wallet_config",
+        desc = "Wallet Config PDA (required with WALLET_FLAG_LIMITS)"
+    )]
+    CreateProposal {
+        instructions_hash: [u8; 32],
+        accounts_hash: [u8; 32],
+        expires_at: u64,
+        approvers: Vec<[u8; 32]>,
+    },
+
+    /// Approve a proposal as one of its approvers
+    ///
+    /// Followed by the Secp256r1 auth payload, if any (signature bound to the
+    /// proposal PDA).
+    #[account(0, signer, name = "payer", desc = "Transaction payer")]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "approver",
+        desc = "Approver Owner/Admin Authority PDA"
+    )]
+    #[account(3, writable, name = "proposal", desc = "Proposal (DeferredExec) PDA")]
+    #[account(
+        4,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    ApproveProposal,
+
+    /// Queue an execution of a timelocked wallet (Owner/Admin)
+    ///
+    /// Creates a DeferredExec PDA that `ExecuteDeferred` can run once,
+    /// `timelock_slots` after queueing and until `expires_at`. Any Owner/Admin
+    /// can veto it meanwhile with `CancelDeferred`.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "proposer",
+        desc = "Owner/Admin Authority PDA (counter incremented)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "queued_exec",
+        desc = "Queued (DeferredExec) PDA to be created"
+    )]
+    #[account(4, name = "system_program", desc = "System Program")]
+    #[account(5, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        6,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: Sysvar Instructions"
+    )]
+    #[account(7, name = "wallet_config", desc = "Wallet Config PDA")]
+    QueueExecute {
+        instructions_hash: [u8; 32],
+        accounts_hash: [u8; 32],
+        expires_at: u64,
+    },
+
+    /// Propose a new Owner (two-step ownership transfer, step 1)
+    ///
+    /// Records the new Owner in the Pending Owner PDA; nothing changes until
+    /// it accepts. Same new-owner encoding as `TransferOwnership`, followed
+    /// by the Secp256r1 auth payload, if any.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "current_owner_authority",
+        desc = "Current owner authority PDA (proposer)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "pending_owner",
+        desc = "Pending Owner PDA to be created"
+    )]
+    #[account(
+        4,
+        name = "refund_destination",
+        desc = "Receives the proposer and Pending Owner rent on accept or cancel"
+    )]
+    #[account(5, name = "system_program", desc = "System Program")]
+    #[account(6, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        7,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    ProposeOwnership {
+        new_type: u8,
+        new_pubkey: [u8; 33],
+        new_hash: [u8; 32],
+    },
+
+    /// Accept a proposed ownership transfer (step 2)
+    ///
+    /// The new Owner proves possession of its key: an Ed25519 key signs the
+    /// transaction, a Secp256r1 passkey's auth payload (counter 1, bound to the
+    /// Pending Owner PDA) follows. Creates the new Owner and closes the
+    /// proposer and the Pending Owner PDA.
+    #[account(
+        0,
+        signer,
+        writable,
+        name = "payer",
+        desc = "Payer and rent contributor"
+    )]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        2,
+        writable,
+        name = "pending_owner",
+        desc = "Pending Owner PDA (closed)"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "current_owner_authority",
+        desc = "Proposer owner authority PDA (closed)"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "new_owner_authority",
+        desc = "New owner authority PDA to be created"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "refund_destination",
+        desc = "Refund destination recorded by ProposeOwnership"
+    )]
+    #[account(6, name = "system_program", desc = "System Program")]
+    #[account(7, name = "rent_sysvar", desc = "Rent Sysvar")]
+    #[account(
+        8,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: new owner keypair | Secp256r1: sysvar_instructions"
+    )]
+    AcceptOwnership,
+
+    /// Cancel a pending ownership transfer (any Owner)
+    ///
+    /// Followed by the Secp256r1 auth payload, if any (signature bound to the
+    /// Pending Owner PDA).
+    #[account(0, signer, name = "payer", desc = "Transaction payer")]
+    #[account(1, name = "wallet", desc = "Wallet PDA")]
+    #[account(2, writable, name = "owner_authority", desc = "Owner Authority PDA")]
+    #[account(
+        3,
+        writable,
+        name = "pending_owner",
+        desc = "Pending Owner PDA (closed)"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "refund_destination",
+        desc = "Refund destination recorded by ProposeOwnership"
+    )]
+    #[account(
+        5,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    CancelOwnershipTransfer,
+
+    /// Close an authority whose expiry slot has passed (permissionless)
+    #[account(0, name = "wallet", desc = "Wallet PDA")]
+    #[account(
+        1,
+        writable,
+        name = "expired_authority",
+        desc = "Expired Authority PDA (closed)"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "funder",
+        desc = "Funder recorded at registration (receives the rent)"
+    )]
+    CloseExpiredAuthority,
+
+    /// Freeze the wallet (any Owner, or the guardian)
+    ///
+    /// Blocks Execute, ExecuteDeferred and CreateSession until an Owner
+    /// unfreezes it. An Owner also cancels a pending unfreeze; the guardian
+    /// cannot freeze a frozen wallet. An Owner signs the wallet, the payer and
+    /// the Wallet Config PDA (if required); its Secp256r1 auth payload, if
+    /// any, is the instruction data.
+    #[account(0, signer, name = "payer", desc = "Transaction payer")]
+    #[account(1, writable, name = "wallet", desc = "Wallet PDA (frozen flag set)")]
+    #[account(
+        2,
+        writable,
+        name = "caller",
+        desc = "Owner Authority PDA, or the guardian (signer)"
+    )]
+    #[account(
+        3,
+        writable,
+        optional,
+        name = "wallet_config",
+        desc = "Wallet Config PDA (required with a guardian or unfreeze delay)"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    Freeze,
+
+    /// Unfreeze the wallet (Owner only)
+    ///
+    /// With an unfreeze delay, the first call requests the unfreeze and a
+    /// call once the delay has passed completes it. Signs the same payload as
+    /// Freeze; the Secp256r1 auth payload, if any, is the instruction data.
+    #[account(0, signer, name = "payer", desc = "Transaction payer")]
+    #[account(
+        1,
+        writable,
+        name = "wallet",
+        desc = "Wallet PDA (frozen flag cleared)"
+    )]
+    #[account(2, writable, name = "owner_authority", desc = "Owner Authority PDA")]
+    #[account(
+        3,
+        writable,
+        optional,
+        name = "wallet_config",
+        desc = "Wallet Config PDA (required with a guardian or unfreeze delay)"
+    )]
+    #[account(
+        4,
+        optional,
+        name = "auth_extra",
+        desc = "Ed25519: signer keypair | Secp256r1: sysvar_instructions"
+    )]
+    Unfreeze,
 }
 
 #[repr(C)]
@@ -1,4 +1,7 @@
 #![allow(unexpected_cfgs)]
+// `is_multiple_of` needs rustc 1.87; the platform-tools rustc used by
+// `cargo build-sbf` may be older, so the program keeps `% n != 0`.
+#![allow(clippy::manual_is_multiple_of)]
 
 #[cfg(not(feature = "no-entrypoint"))]
 use {default_env::default_env, solana_security_txt::security_txt};
@@ -0,0 +1,203 @@
+use assertions::sol_assert_bytes_eq;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
+    error::AuthError,
+    state::{
+        action::{validate_actions_buffer, MAX_ACTIONS_BUFFER_SIZE},
+        authority::AuthorityAccountHeader,
+        session::{read_session, write_session, SESSION_FLAG_STAGED, SESSION_HEADER_SIZE},
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+    utils::transfer_lamports,
+};
+
+/// Process the AppendSessionActions instruction.
+///
+/// Extends the action buffer of a session created with `SESSION_FLAG_STAGED`.
+/// A transaction holds roughly 900 bytes of actions, so large policies are
+/// written in chunks: `CreateSession` carries the first chunk, each
+/// `AppendSessionActions` grows the account and appends the next one, and the
+/// chunk marked `finalize` validates the complete buffer and clears the flag.
+/// Until then `Execute` rejects the session with `SessionNotFinalized`.
+///
+/// Only the Authority that created the session can append, so a policy is
+/// never assembled from two authorities' chunks.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer (covers the extra rent)
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Creator Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Session PDA (staged)
+/// 5. `[]` System Program
+/// 6. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [finalize(1)][chunk_len(2)][chunk(N)][auth_payload(variable)]
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 3 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let finalize = match instruction_data[0] {
+        0 => false,
+        1 => true,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    let chunk_len = u16::from_le_bytes([instruction_data[1], instruction_data[2]]) as usize;
+    let chunk_end = 3 + chunk_len;
+    if instruction_data.len() < chunk_end {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let chunk = &instruction_data[3..chunk_end];
+    let authority_payload = &instruction_data[chunk_end..];
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let admin_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let session_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify ownership of program accounts
+    if wallet_pda.owner() != program_id
+        || admin_auth_pda.owner() != program_id
+        || session_pda.owner() != program_id
+    {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Validate Wallet discriminator
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Authority PDA must be writable (counter increment for Secp256r1)
+    if !admin_auth_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Read authority header
+    let admin_data = unsafe { admin_auth_pda.borrow_mut_data_unchecked() };
+    if admin_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let admin_header =
+        unsafe { std::ptr::read_unaligned(admin_data.as_ptr() as *const AuthorityAccountHeader) };
+
+    if admin_header.discriminator != AccountDiscriminator::Authority as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if admin_header.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Validate the staged session
+    let session_data = unsafe { session_pda.borrow_data_unchecked() };
+    let mut session = read_session(session_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    if session.discriminator != AccountDiscriminator::Session as u8 {
+        return Err(AuthError::InvalidSessionAccount.into());
+    }
+    if session.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if session.flags & SESSION_FLAG_STAGED == 0 {
+        return Err(AuthError::InvalidSessionAccount.into());
+    }
+
+    // Only the creating Authority may continue building the policy
+    if session.creator != *admin_auth_pda.key() {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.slot > session.expires_at {
+        return Err(AuthError::SessionExpired.into());
+    }
+
+    // Bind the signature to the session + finalize flag + chunk
+    let mut data_payload = Vec::with_capacity(32 + chunk_end);
+    data_payload.extend_from_slice(session_pda.key().as_ref());
+    data_payload.extend_from_slice(&instruction_data[..chunk_end]);
+
+    // Authenticate
+    match admin_header.authority_type {
+        0 => {
+            Ed25519Authenticator.authenticate(
+                accounts,
+                admin_data,
+                &[],
+                &data_payload,
+                &[13],
+                program_id,
+            )?;
+        },
+        1 => {
+            let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+            Secp256r1Authenticator::new(&limits)?.authenticate(
+                accounts,
+                admin_data,
+                authority_payload,
+                &data_payload,
+                &[13],
+                program_id,
+            )?;
+        },
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+
+    // Grow the account and append the chunk
+    let old_len = session_data.len();
+    let new_len = old_len + chunk_len;
+    if new_len - SESSION_HEADER_SIZE > MAX_ACTIONS_BUFFER_SIZE {
+        return Err(AuthError::ActionBufferInvalid.into());
+    }
+
+    if chunk_len > 0 {
+        let rent = Rent::get()?;
+        let required = rent.minimum_balance(new_len);
+        let current = session_pda.lamports();
+        if required > current {
+            transfer_lamports(payer, session_pda, system_program, required - current)?;
+        }
+        session_pda.resize(new_len)?;
+    }
+
+    let data = unsafe { session_pda.borrow_mut_data_unchecked() };
+    data[old_len..new_len].copy_from_slice(chunk);
+
+    if finalize {
+        validate_actions_buffer(&data[SESSION_HEADER_SIZE..])?;
+        session.flags &= !SESSION_FLAG_STAGED;
+        write_session(data, &session);
+    }
+
+    Ok(())
+}
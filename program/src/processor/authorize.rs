@@ -1,12 +1,21 @@
 use crate::{
-    auth::{secp256r1::Secp256r1Authenticator, traits::Authenticator},
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
     error::AuthError,
+    processor::session_enforcement::check_session_usable,
     state::{
-        authority::AuthorityAccountHeader,
-        deferred::DeferredExecAccount,
+        authority::{authority_expired, AuthorityAccountHeader},
+        deferred::{
+            DeferredExecAccount, DEFERRED_AUTHORITY_KIND_AUTHORITY,
+            DEFERRED_AUTHORITY_KIND_SESSION, DEFERRED_EXEC_HEADER_SIZE, MAX_DEFERRED_EXECUTIONS,
+        },
+        predicate::parse_predicates,
+        session::{read_session, write_session},
+        wallet_config::{WalletConfigAccount, WalletLimits},
         AccountDiscriminator, CURRENT_ACCOUNT_VERSION,
     },
-    utils::initialize_pda_account,
+    utils::{get_stack_height, initialize_pda_account},
 };
 use pinocchio::{
     account_info::AccountInfo,
@@ -17,25 +26,27 @@ use pinocchio::{
     ProgramResult,
 };
 
-/// Minimum expiry window in slots (~4 seconds at 400ms/slot)
-const MIN_EXPIRY_SLOTS: u16 = 10;
-
-/// Maximum expiry window in slots (~1 hour at 400ms/slot)
-const MAX_EXPIRY_SLOTS: u16 = 9_000;
-
 /// Process the Authorize instruction (deferred execution tx1).
 ///
-/// Verifies Secp256r1 signature over instruction/account hashes, then creates
-/// a DeferredExec PDA storing the authorization for later execution.
+/// Authenticates the authorizer over the instruction/account hashes, then
+/// creates a DeferredExec PDA storing the authorization for later execution.
+///
+/// The authorizer is either an Owner/Admin Authority PDA (Ed25519 or
+/// Secp256r1) or a Session PDA. A session-authorized DeferredExec is executed
+/// under the session's actions, exactly like a session `Execute`.
 ///
 /// # Accounts:
 /// 1. `[signer, writable]` Payer
 /// 2. `[]` Wallet PDA
-/// 3. `[writable]` Authority PDA (counter increment)
+/// 3. `[writable]` Authority or Session PDA (nonce increment)
 /// 4. `[writable]` DeferredExec PDA (created)
 /// 5. `[]` System Program
 /// 6. `[]` Rent Sysvar
-/// 7. `[]` Sysvar Instructions (for Secp256r1 precompile introspection)
+/// 7. `[]` Auth extra (Ed25519 / Session: signer keypair | Secp256r1: sysvar_instructions)
+/// 8. `[]` Wallets with `WALLET_FLAG_LIMITS`: Wallet Config PDA
+///
+/// The expiry offset must lie within the wallet's deferred lifetime bounds
+/// (10 to 9,000 slots, ~4 seconds to ~1 hour, by default).
 ///
 /// # Instruction Data (after discriminator):
 ///   [instructions_hash(32)][accounts_hash(32)][expiry_offset(2)][auth_payload(variable)]
@@ -43,6 +54,259 @@ pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
+) -> ProgramResult {
+    // Parse instruction data: [instructions_hash(32)][accounts_hash(32)][expiry_offset(2)][auth_payload(...)]
+    if instruction_data.len() < 66 {
+        // 32 + 32 + 2 minimum
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let limits = authorize_limits(accounts, program_id)?;
+    let args = DeferredArgs {
+        instructions_hash: instruction_data[0..32].try_into().unwrap(),
+        accounts_hash: instruction_data[32..64].try_into().unwrap(),
+        expires_at: expiry_from_offset(
+            u16::from_le_bytes(instruction_data[64..66].try_into().unwrap()),
+            &limits.get()?,
+        )?,
+        max_executions: 1,
+        min_interval_slots: 0,
+        trailer: &[],
+        kind: None,
+    };
+
+    // The signed_payload for Authorize is: instructions_hash || accounts_hash || expiry_offset
+    let (signed_payload, auth_payload) = instruction_data.split_at(66);
+    create_authorization(
+        program_id,
+        accounts,
+        &limits,
+        &args,
+        signed_payload,
+        auth_payload,
+        6,
+    )
+}
+
+/// Process the AuthorizeRecurring instruction.
+///
+/// Same as `Authorize`, but the DeferredExec can be executed up to
+/// `max_executions` times, at least `min_interval_slots` apart (e.g. a keeper
+/// running a DCA swap). It is closed by its last run, or reclaimed with
+/// `ReclaimDeferred` once expired.
+///
+/// # Accounts:
+///   Same as `Authorize`.
+///
+/// # Instruction Data (after discriminator):
+///   [instructions_hash(32)][accounts_hash(32)][expiry_offset(2)]
+///   [max_executions(2)][min_interval_slots(4)][auth_payload(variable)]
+pub fn process_recurring(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let limits = authorize_limits(accounts, program_id)?;
+    let args = parse_recurring_args(instruction_data, &limits.get()?)?;
+
+    // Signed payload: every fixed argument, so the schedule is covered too
+    let (signed_payload, auth_payload) = instruction_data.split_at(72);
+    create_authorization(
+        program_id,
+        accounts,
+        &limits,
+        &args,
+        signed_payload,
+        auth_payload,
+        17,
+    )
+}
+
+/// Process the AuthorizeConditional instruction.
+///
+/// Same as `AuthorizeRecurring`, plus a predicate list stored with the
+/// DeferredExec (see `state::predicate`). `ExecuteDeferred` evaluates every
+/// predicate before any CPI and fails with `DeferredConditionNotMet` unless
+/// all of them hold, e.g. a limit order that swaps once a price crosses N.
+///
+/// # Accounts:
+///   Same as `Authorize`.
+///
+/// # Instruction Data (after discriminator):
+///   [instructions_hash(32)][accounts_hash(32)][expiry_offset(2)]
+///   [max_executions(2)][min_interval_slots(4)]
+///   [predicates_len(2)][predicates(predicates_len)][auth_payload(variable)]
+pub fn process_conditional(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let limits = authorize_limits(accounts, program_id)?;
+    let mut args = parse_recurring_args(instruction_data, &limits.get()?)?;
+    if instruction_data.len() < 74 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let predicates_len = u16::from_le_bytes(instruction_data[72..74].try_into().unwrap()) as usize;
+    let predicates = instruction_data
+        .get(74..74 + predicates_len)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    if parse_predicates(predicates)?.is_empty() {
+        return Err(AuthError::InvalidDeferredPredicate.into());
+    }
+    args.trailer = predicates;
+
+    // Signed payload: every argument including the whole predicate list
+    let (signed_payload, auth_payload) = instruction_data.split_at(74 + predicates_len);
+    create_authorization(
+        program_id,
+        accounts,
+        &limits,
+        &args,
+        signed_payload,
+        auth_payload,
+        18,
+    )
+}
+
+/// Process the AuthorizeUntil instruction.
+///
+/// Same as `Authorize`, but with an absolute `expires_at` slot instead of a
+/// u16 offset, for approvals that have to wait longer than ~1 hour. The
+/// lifetime is bounded by the wallet's `max_deferred_expiry_slots`
+/// (Wallet Config PDA, or the ~1 hour default when the wallet has none).
+///
+/// # Accounts:
+/// 1.-8. Same as `Authorize`
+///
+/// # Instruction Data (after discriminator):
+///   [instructions_hash(32)][accounts_hash(32)][expires_at(8)][auth_payload(variable)]
+pub fn process_until(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 72 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let limits = authorize_limits(accounts, program_id)?;
+    let expires_at = u64::from_le_bytes(instruction_data[64..72].try_into().unwrap());
+    check_expires_at(expires_at, &limits.get()?)?;
+
+    let args = DeferredArgs {
+        instructions_hash: instruction_data[0..32].try_into().unwrap(),
+        accounts_hash: instruction_data[32..64].try_into().unwrap(),
+        expires_at,
+        max_executions: 1,
+        min_interval_slots: 0,
+        trailer: &[],
+        kind: None,
+    };
+
+    // Signed payload: instructions_hash || accounts_hash || expires_at
+    let (signed_payload, auth_payload) = instruction_data.split_at(72);
+    create_authorization(
+        program_id,
+        accounts,
+        &limits,
+        &args,
+        signed_payload,
+        auth_payload,
+        19,
+    )
+}
+
+/// Checks that an absolute expiry lies within the wallet's deferred
+/// lifetime bounds: at least `min_deferred_expiry_slots` and at most
+/// `max_deferred_expiry_slots` ahead.
+pub(crate) fn check_expires_at(expires_at: u64, config: &WalletConfigAccount) -> ProgramResult {
+    let current_slot = Clock::get()?.slot;
+    if expires_at < current_slot.saturating_add(config.min_deferred_expiry_slots)
+        || expires_at > current_slot.saturating_add(config.max_deferred_expiry_slots)
+    {
+        return Err(AuthError::InvalidExpiryWindow.into());
+    }
+    Ok(())
+}
+
+/// Validates a relative expiry window against the wallet's deferred
+/// lifetime bounds and returns the absolute expiry slot.
+fn expiry_from_offset(
+    expiry_offset: u16,
+    limits: &WalletConfigAccount,
+) -> Result<u64, ProgramError> {
+    if !(limits.min_deferred_expiry_slots..=limits.max_deferred_expiry_slots)
+        .contains(&(expiry_offset as u64))
+    {
+        return Err(AuthError::InvalidExpiryWindow.into());
+    }
+    Ok(Clock::get()?.slot + expiry_offset as u64)
+}
+
+/// Security limits of the wallet (2nd account) of an `Authorize*`.
+fn authorize_limits<'a>(
+    accounts: &'a [AccountInfo],
+    program_id: &'a Pubkey,
+) -> Result<WalletLimits<'a>, ProgramError> {
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    Ok(WalletLimits::new(wallet_pda, accounts, program_id))
+}
+
+/// Arguments of a deferred authorization.
+pub(crate) struct DeferredArgs<'a> {
+    pub instructions_hash: [u8; 32],
+    pub accounts_hash: [u8; 32],
+    /// Absolute slot at which the authorization expires (already bounded).
+    pub expires_at: u64,
+    pub max_executions: u16,
+    pub min_interval_slots: u64,
+    /// Validated predicate list or proposal approval section, stored after
+    /// the DeferredExec header.
+    pub trailer: &'a [u8],
+    /// Creates a proposal or queued execution (`DEFERRED_AUTHORITY_KIND_*`)
+    /// instead of an authorization. Only an Owner/Admin Authority can create one.
+    pub kind: Option<u8>,
+}
+
+/// Parses `[instructions_hash(32)][accounts_hash(32)][expiry_offset(2)]
+/// [max_executions(2)][min_interval_slots(4)]`.
+fn parse_recurring_args(
+    instruction_data: &[u8],
+    limits: &WalletConfigAccount,
+) -> Result<DeferredArgs<'static>, ProgramError> {
+    if instruction_data.len() < 72 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let args = DeferredArgs {
+        instructions_hash: instruction_data[0..32].try_into().unwrap(),
+        accounts_hash: instruction_data[32..64].try_into().unwrap(),
+        expires_at: expiry_from_offset(
+            u16::from_le_bytes(instruction_data[64..66].try_into().unwrap()),
+            limits,
+        )?,
+        max_executions: u16::from_le_bytes(instruction_data[66..68].try_into().unwrap()),
+        min_interval_slots: u32::from_le_bytes(instruction_data[68..72].try_into().unwrap()) as u64,
+        trailer: &[],
+        kind: None,
+    };
+    if args.max_executions == 0 || args.max_executions > MAX_DEFERRED_EXECUTIONS {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok(args)
+}
+
+/// Authenticates the authorizer over `signed_payload` (within the signature
+/// age of `limits`) and creates the DeferredExec PDA. Shared by all
+/// `Authorize*` variants, `CreateProposal` and `QueueExecute`. An expiring
+/// Authority cannot authorize past its own `expires_at`.
+pub(crate) fn create_authorization(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    limits: &WalletLimits<'_>,
+    args: &DeferredArgs,
+    signed_payload: &[u8],
+    auth_payload: &[u8],
+    discriminator: u8,
 ) -> ProgramResult {
     // Parse accounts
     let payer = accounts
@@ -84,78 +348,47 @@ pub fn process(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Parse instruction data: [instructions_hash(32)][accounts_hash(32)][expiry_offset(2)][auth_payload(...)]
-    if instruction_data.len() < 66 {
-        // 32 + 32 + 2 minimum
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let instructions_hash: [u8; 32] = instruction_data[0..32].try_into().unwrap();
-    let accounts_hash: [u8; 32] = instruction_data[32..64].try_into().unwrap();
-    let expiry_offset =
-        u16::from_le_bytes(instruction_data[64..66].try_into().unwrap());
-    let auth_payload = &instruction_data[66..];
-
-    // Validate expiry window
-    if expiry_offset < MIN_EXPIRY_SLOTS || expiry_offset > MAX_EXPIRY_SLOTS {
-        return Err(AuthError::InvalidExpiryWindow.into());
-    }
-
-    // Read authority header
+    // Authenticate and advance the authorizer's nonce, which seeds the PDA
     let authority_data = unsafe { authority_pda.borrow_mut_data_unchecked() };
-    if authority_data.is_empty() || authority_data[0] != AccountDiscriminator::Authority as u8 {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    if authority_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let authority_header = unsafe {
-        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
-    };
-
-    if authority_header.wallet != *wallet_pda.key() {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    // Only Secp256r1 needs deferred execution
-    if authority_header.authority_type != 1 {
-        return Err(AuthError::InvalidAuthenticationKind.into());
-    }
-
-    // Only Owner or Admin can authorize (not Spender)
-    if authority_header.role > 1 {
-        return Err(AuthError::PermissionDenied.into());
-    }
-
-    // The signed_payload for Authorize is: instructions_hash || accounts_hash || expiry_offset
-    let mut signed_payload = Vec::with_capacity(66);
-    signed_payload.extend_from_slice(&instructions_hash);
-    signed_payload.extend_from_slice(&accounts_hash);
-    signed_payload.extend_from_slice(&expiry_offset.to_le_bytes());
-
-    // Authenticate — this verifies the Secp256r1 signature and increments the counter
-    Secp256r1Authenticator.authenticate(
-        accounts,
-        authority_data,
-        auth_payload,
-        &signed_payload,
-        &[6], // discriminator for Authorize
-        program_id,
-    )?;
-
-    // Read the counter value that was just committed
-    let updated_header = unsafe {
-        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+    let account_discriminator = authority_data
+        .first()
+        .copied()
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let (authority_kind, nonce_for_seed) = match account_discriminator {
+        d if d == AccountDiscriminator::Authority as u8 => {
+            let nonce = authenticate_authority(
+                accounts,
+                limits,
+                authority_data,
+                wallet_pda.key(),
+                auth_payload,
+                signed_payload,
+                discriminator,
+                program_id,
+            )?;
+            // An authorization cannot outlive its authorizer
+            if authority_expired(authority_data, args.expires_at)? {
+                return Err(AuthError::InvalidExpiryWindow.into());
+            }
+            (
+                args.kind.unwrap_or(DEFERRED_AUTHORITY_KIND_AUTHORITY),
+                nonce,
+            )
+        },
+        d if d == AccountDiscriminator::Session as u8 => {
+            // Proposals and queued executions are Owner/Admin only
+            if args.kind.is_some() {
+                return Err(AuthError::PermissionDenied.into());
+            }
+            let nonce =
+                authenticate_session(accounts, authority_data, wallet_pda.key(), program_id)?;
+            (DEFERRED_AUTHORITY_KIND_SESSION, nonce)
+        },
+        _ => return Err(ProgramError::InvalidAccountData),
     };
-    let counter_for_seed = updated_header.counter;
-
-    // Compute expiry
-    let clock = Clock::get()?;
-    let expires_at = clock.slot + expiry_offset as u64;
 
     // Derive DeferredExec PDA
-    let counter_bytes = counter_for_seed.to_le_bytes();
+    let counter_bytes = nonce_for_seed.to_le_bytes();
     let seeds: &[&[u8]] = &[
         b"deferred",
         wallet_pda.key().as_ref(),
@@ -170,7 +403,7 @@ pub fn process(
 
     // Compute rent
     let rent = Rent::get()?;
-    let space = std::mem::size_of::<DeferredExecAccount>();
+    let space = DEFERRED_EXEC_HEADER_SIZE + args.trailer.len();
     let rent_lamports = rent.minimum_balance(space);
 
     // Create DeferredExec PDA
@@ -198,13 +431,17 @@ pub fn process(
         discriminator: AccountDiscriminator::DeferredExec as u8,
         version: CURRENT_ACCOUNT_VERSION,
         bump,
-        _padding: [0u8; 5],
-        instructions_hash,
-        accounts_hash,
+        authority_kind,
+        max_executions: args.max_executions,
+        executions_done: 0,
+        instructions_hash: args.instructions_hash,
+        accounts_hash: args.accounts_hash,
         wallet: *wallet_pda.key(),
         authority: *authority_pda.key(),
         payer: *payer.key(),
-        expires_at,
+        expires_at: args.expires_at,
+        min_interval_slots: args.min_interval_slots,
+        last_executed_slot: 0,
     };
 
     let deferred_data = unsafe { deferred_pda.borrow_mut_data_unchecked() };
@@ -214,6 +451,119 @@ pub fn process(
             deferred,
         );
     }
+    deferred_data[DEFERRED_EXEC_HEADER_SIZE..].copy_from_slice(args.trailer);
 
     Ok(())
 }
+
+/// Authenticates an Owner/Admin Authority PDA and returns its advanced counter.
+///
+/// Secp256r1 verifies the signature over `signed_payload` and increments the
+/// counter itself. Ed25519 only checks that the key signed the transaction
+/// (which covers the hashes), so the counter is incremented here to keep each
+/// DeferredExec PDA unique.
+#[allow(clippy::too_many_arguments)]
+fn authenticate_authority(
+    accounts: &[AccountInfo],
+    limits: &WalletLimits<'_>,
+    authority_data: &mut [u8],
+    wallet: &Pubkey,
+    auth_payload: &[u8],
+    signed_payload: &[u8],
+    discriminator: u8,
+    program_id: &Pubkey,
+) -> Result<u32, ProgramError> {
+    if authority_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let authority_header = unsafe {
+        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+    };
+
+    if authority_header.wallet != *wallet {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only Owner or Admin can authorize (not Spender)
+    if authority_header.role > 1 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    match authority_header.authority_type {
+        0 => {
+            // Ed25519
+            Ed25519Authenticator.authenticate(
+                accounts,
+                authority_data,
+                auth_payload,
+                signed_payload,
+                &[discriminator],
+                program_id,
+            )?;
+            let mut updated_header = authority_header;
+            updated_header.counter = updated_header
+                .counter
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            unsafe {
+                std::ptr::write_unaligned(
+                    authority_data.as_mut_ptr() as *mut AuthorityAccountHeader,
+                    updated_header,
+                );
+            }
+        },
+        1 => {
+            // Secp256r1 — verifies the signature and increments the counter
+            Secp256r1Authenticator::new(limits)?.authenticate(
+                accounts,
+                authority_data,
+                auth_payload,
+                signed_payload,
+                &[discriminator],
+                program_id,
+            )?;
+        },
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+
+    // Read the counter value that was just committed
+    let updated_header = unsafe {
+        std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+    };
+    Ok(updated_header.counter)
+}
+
+/// Authenticates a Session PDA and returns its advanced `deferred_nonce`.
+///
+/// The session key must sign the transaction, and the session must be usable
+/// right now (wallet, expiry, finalized actions, live creator). Its actions
+/// are enforced when the authorization is executed, not here.
+fn authenticate_session(
+    accounts: &[AccountInfo],
+    session_data: &mut [u8],
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u32, ProgramError> {
+    // Same anti-CPI guard as a session-authenticated Execute
+    if get_stack_height() > 1 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    check_session_usable(session_data, wallet, accounts, program_id)?;
+
+    let mut session = read_session(session_data).ok_or(ProgramError::InvalidAccountData)?;
+    if !accounts
+        .iter()
+        .any(|acc| acc.is_signer() && *acc.key() == session.session_key)
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    session.deferred_nonce = session
+        .deferred_nonce
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let nonce = session.deferred_nonce;
+    write_session(session_data, &session);
+    Ok(nonce)
+}
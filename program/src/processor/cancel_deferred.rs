@@ -0,0 +1,134 @@
+use crate::{
+    auth::admin::authenticate_admin,
+    error::AuthError,
+    state::{
+        deferred::{DeferredExecAccount, DEFERRED_AUTHORITY_KIND_SESSION},
+        session::read_session,
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Process the CancelDeferred instruction.
+///
+/// Closes a pending DeferredExec account before it expires, so its payload can
+/// no longer be executed. Rent is refunded to the payer stored in the account.
+///
+/// The canceller is any Owner/Admin Authority of the wallet, or the Session
+/// that authorized it. A session may cancel its own authorizations even after
+/// it has expired.
+///
+/// A Deferred Buffer left behind can be closed afterwards with `ReclaimDeferred`.
+///
+/// # Accounts:
+/// 1. `[signer]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Owner/Admin Authority PDA or authorizing Session PDA
+/// 4. `[writable]` DeferredExec PDA (closed)
+/// 5. `[writable]` Refund destination (must match stored payer)
+/// 6. `[optional]` Auth extra (Ed25519 / Session: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   Secp256r1: [auth_payload(variable)]
+///   Ed25519 / Session: empty (auth is via signer)
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let authority_payload = instruction_data;
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let canceller_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let deferred_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let refund_dest = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify ownership of program accounts
+    if wallet_pda.owner() != program_id
+        || canceller_pda.owner() != program_id
+        || deferred_pda.owner() != program_id
+    {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Read DeferredExec account
+    let deferred_data = unsafe { deferred_pda.borrow_mut_data_unchecked() };
+    if deferred_data.len() < std::mem::size_of::<DeferredExecAccount>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let deferred =
+        unsafe { std::ptr::read_unaligned(deferred_data.as_ptr() as *const DeferredExecAccount) };
+
+    if deferred.discriminator != AccountDiscriminator::DeferredExec as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if deferred.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Rent always goes back to whoever funded the authorization
+    if deferred.payer != *refund_dest.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let canceller_data = unsafe { canceller_pda.borrow_data_unchecked() };
+    let canceller_discriminator = canceller_data
+        .first()
+        .copied()
+        .ok_or(ProgramError::InvalidAccountData)?;
+
+    if canceller_discriminator == AccountDiscriminator::Session as u8 {
+        // Only the session that authorized it
+        if deferred.authority_kind != DEFERRED_AUTHORITY_KIND_SESSION
+            || deferred.authority != *canceller_pda.key()
+        {
+            return Err(AuthError::PermissionDenied.into());
+        }
+        let session = read_session(canceller_data).ok_or(ProgramError::InvalidAccountData)?;
+        if session.wallet != *wallet_pda.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !accounts
+            .iter()
+            .any(|acc| acc.is_signer() && *acc.key() == session.session_key)
+        {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    } else {
+        // Any Owner/Admin — the authorizing authority is always one of them.
+        // Bind the signature to the cancelled authorization.
+        authenticate_admin(
+            program_id,
+            accounts,
+            wallet_pda,
+            canceller_pda,
+            &WalletLimits::new(wallet_pda, accounts, program_id),
+            authority_payload,
+            deferred_pda.key().as_ref(),
+            16,
+        )?;
+    }
+
+    // Close the account — zero data and drain lamports
+    deferred_data.fill(0);
+
+    let deferred_lamports = deferred_pda.lamports();
+    let refund_lamports = unsafe { *refund_dest.borrow_mut_lamports_unchecked() };
+    unsafe {
+        *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
+            .checked_add(deferred_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *deferred_pda.borrow_mut_lamports_unchecked() = 0;
+    }
+
+    Ok(())
+}
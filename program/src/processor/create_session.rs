@@ -14,10 +14,16 @@ use crate::{
         ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
     },
     error::AuthError,
+    processor::freeze::check_not_frozen,
     state::{
-        action::validate_actions_buffer,
-        authority::AuthorityAccountHeader,
-        session::{SessionAccount, SESSION_HEADER_SIZE},
+        action::{validate_actions_buffer, MAX_ACTIONS_BUFFER_SIZE},
+        authority::{authority_expired, AuthorityAccountHeader},
+        policy_template::{gather_state, PolicyTemplateAccount, POLICY_TEMPLATE_HEADER_SIZE},
+        session::{
+            SessionAccount, SESSION_FLAGS_MASK, SESSION_FLAG_STAGED, SESSION_FLAG_TEMPLATE,
+            SESSION_HEADER_SIZE, SESSION_VERSION,
+        },
+        wallet_config::WalletLimits,
         AccountDiscriminator,
     },
 };
@@ -26,12 +32,21 @@ use crate::{
 ///
 /// Layout:
 /// - `session_key`: The public key of the ephemeral session signer (32 bytes).
-/// - `expires_at`: The absolute slot height when this session expires (8 bytes).
+/// - `expires_at`: The absolute slot height when this session expires (8 bytes), no later
+///   than an expiring creator's own expiry.
 /// - `actions_len`: Length of the actions buffer in bytes (2 bytes, u16 LE). 0 = no actions.
+///   Bit 15 (`CREATE_SESSION_FLAGS_PRESENT`) marks the flagged form below.
 /// - `actions`: Raw actions buffer (variable, `actions_len` bytes).
+/// - `flags`: Session flags (1 byte, `SESSION_FLAG_*`), only in the flagged form.
+/// - `template`: Policy Template PDA (32 bytes), present only if `flags` has
+///   `SESSION_FLAG_TEMPLATE`. Template-bound sessions must have `actions_len = 0`.
 ///
-/// Total fixed: 42 bytes minimum. Backwards compatible: old clients sending 40 bytes
-/// will have actions_len=0 (no actions).
+/// With `SESSION_FLAG_STAGED`, `actions` is only the first chunk of a larger buffer
+/// and is not validated yet; `AppendSessionActions` adds the rest and validates the
+/// whole buffer on its final chunk.
+///
+/// Backwards compatible: old clients sending 40 bytes, or `actions_len` without
+/// bit 15, get flags = 0 and their auth payload starts right after the actions.
 #[repr(C, align(8))]
 #[derive(NoPadding)]
 pub struct CreateSessionArgs {
@@ -39,13 +54,21 @@ pub struct CreateSessionArgs {
     pub expires_at: u64,
 }
 
+/// `CreateSessionArgs::actions_len` bit 15: a `flags` byte (and, for
+/// template-bound sessions, the template) follows the actions buffer.
+pub const CREATE_SESSION_FLAGS_PRESENT: u16 = 1 << 15;
+
 /// Parsed session creation arguments including optional actions.
 pub struct ParsedCreateSessionArgs {
     pub session_key: [u8; 32],
     pub expires_at: u64,
+    /// Session flags (`SESSION_FLAG_*`).
+    pub flags: u8,
+    /// Policy Template PDA for template-bound sessions.
+    pub template: Option<[u8; 32]>,
     /// Raw actions buffer bytes (empty if no actions).
     pub actions_bytes: Vec<u8>,
-    /// Byte offset where the actions section ends in instruction_data.
+    /// Byte offset where the actions + flags section ends in instruction_data.
     /// Everything after this is auth_payload for Secp256r1.
     pub args_end_offset: usize,
 }
@@ -62,50 +85,76 @@ impl ParsedCreateSessionArgs {
 
         // Check for actions buffer
         if data.len() >= 42 {
-            let actions_len = u16::from_le_bytes(data[40..42].try_into().unwrap()) as usize;
+            let actions_len_field = u16::from_le_bytes(data[40..42].try_into().unwrap());
+            let flags_present = actions_len_field & CREATE_SESSION_FLAGS_PRESENT != 0;
+            let actions_len = (actions_len_field & !CREATE_SESSION_FLAGS_PRESENT) as usize;
 
             // Cap actions buffer size to prevent BPF heap exhaustion.
-            // 16 actions * max ~128 bytes each = 2048 is generous.
-            // The BPF heap is 32KB; allocating 64KB (u16 max) would OOM.
-            const MAX_ACTIONS_BUFFER_SIZE: usize = 2048;
             if actions_len > MAX_ACTIONS_BUFFER_SIZE {
                 return Err(ProgramError::InvalidInstructionData);
             }
 
-            if actions_len > 0 {
-                let actions_start = 42;
-                let actions_end = actions_start + actions_len;
+            let actions_start = 42;
+            let actions_end = actions_start + actions_len;
 
-                if data.len() < actions_end {
-                    return Err(ProgramError::InvalidInstructionData);
-                }
+            if data.len() < actions_end {
+                return Err(ProgramError::InvalidInstructionData);
+            }
 
-                let actions_bytes = data[actions_start..actions_end].to_vec();
+            // In the flagged form, the flags byte follows the actions buffer.
+            let (flags, flags_end) = if flags_present {
+                let flags = *data
+                    .get(actions_end)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                (flags, actions_end + 1)
+            } else {
+                (0, actions_end)
+            };
+            if flags & !SESSION_FLAGS_MASK != 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            let staged = flags & SESSION_FLAG_STAGED != 0;
+            if staged && flags & SESSION_FLAG_TEMPLATE != 0 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
 
+            let actions_bytes = data[actions_start..actions_end].to_vec();
+            if !actions_bytes.is_empty() && !staged {
                 // Validate actions buffer at creation time
                 validate_actions_buffer(&actions_bytes)?;
-
-                return Ok(Self {
-                    session_key,
-                    expires_at,
-                    actions_bytes,
-                    args_end_offset: actions_end,
-                });
             }
 
-            // actions_len == 0
+            // Template-bound sessions take their rules from the template only.
+            let mut args_end_offset = flags_end;
+            let template = if flags & SESSION_FLAG_TEMPLATE != 0 {
+                if !actions_bytes.is_empty() || data.len() < args_end_offset + 32 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+                let mut template = [0u8; 32];
+                template.copy_from_slice(&data[args_end_offset..args_end_offset + 32]);
+                args_end_offset += 32;
+                Some(template)
+            } else {
+                None
+            };
+
             return Ok(Self {
                 session_key,
                 expires_at,
-                actions_bytes: Vec::new(),
-                args_end_offset: 42,
+                flags,
+                template,
+                actions_bytes,
+                args_end_offset,
             });
         }
 
-        // Legacy format: exactly 40 bytes, no actions
+        // Legacy format: exactly 40 bytes, no actions, no flags
         Ok(Self {
             session_key,
             expires_at,
+            flags: 0,
+            template: None,
             actions_bytes: Vec::new(),
             args_end_offset: 40,
         })
@@ -121,7 +170,8 @@ impl ParsedCreateSessionArgs {
 /// 1. Verifies the authorizing authority (must be Owner or Admin).
 /// 2. Validates optional actions buffer.
 /// 3. Derives a fresh Session PDA from `["session", wallet, session_key]`.
-/// 4. Allocates and initializes the Session account with expiry and actions.
+/// 4. Allocates and initializes the Session account with expiry, flags, creator and actions
+///    (or, for template-bound sessions, the template binding and initial counters).
 ///
 /// # Accounts:
 /// 1. `[signer, writable]` Payer: Pays for rent.
@@ -130,6 +180,8 @@ impl ParsedCreateSessionArgs {
 /// 4. `[writable]` Session PDA: The new session account.
 /// 5. `[]` System Program.
 /// 6. `[]` Rent Sysvar.
+/// 7. `...` Template-bound sessions: the Policy Template PDA, anywhere after the above.
+/// 8. `...` Wallets with `WALLET_FLAG_LIMITS`: the Wallet Config PDA, anywhere after the above.
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -178,6 +230,7 @@ pub fn process(
     if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
         return Err(ProgramError::InvalidAccountData);
     }
+    check_not_frozen(wallet_data)?;
 
     let auth_data = unsafe { authorizer_pda.borrow_mut_data_unchecked() };
 
@@ -200,23 +253,29 @@ pub fn process(
         return Err(AuthError::PermissionDenied.into());
     }
 
-    // Validate expires_at: must be in the future and within max session duration
+    // Validate expires_at: must be in the future and within the wallet's max
+    // session duration (~30 days at ~2.5 slots/sec = 6,480,000 slots by default)
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id);
     {
+        let max_session_slots = limits.get()?.max_session_slots;
         let clock = Clock::get()?;
         let current_slot = clock.slot;
         if args.expires_at <= current_slot {
             return Err(AuthError::InvalidSessionDuration.into());
         }
-        // Max session duration: ~30 days at ~2.5 slots/sec = 6,480,000 slots
-        const MAX_SESSION_SLOTS: u64 = 6_480_000;
-        if args.expires_at > current_slot.saturating_add(MAX_SESSION_SLOTS) {
+        if args.expires_at > current_slot.saturating_add(max_session_slots) {
+            return Err(AuthError::InvalidSessionDuration.into());
+        }
+        // A session cannot outlive the authority that created it
+        if authority_expired(auth_data, args.expires_at)? {
             return Err(AuthError::InvalidSessionDuration.into());
         }
     }
 
     // Authenticate Authorizer
-    // instruction_data layout: [args(40)][actions_len(2)][actions(N)][auth_payload...]
-    // args.args_end_offset points to the end of the args+actions section.
+    // instruction_data layout:
+    //   [args(40)][actions_len(2)][actions(N)][flags(1)?][template(32)?][auth_payload...]
+    // args.args_end_offset points to the end of the args+actions+flags+template section.
     let data_payload = &instruction_data[..args.args_end_offset];
     let authority_payload = if instruction_data.len() > args.args_end_offset {
         &instruction_data[args.args_end_offset..]
@@ -224,18 +283,22 @@ pub fn process(
         &[]
     };
 
-    // Ed25519 signed payload — includes payer + session_key + actions.
+    // Ed25519 signed payload — includes payer + session_key + actions + flags.
     // Note: Ed25519Authenticator only checks that the authority keypair is a tx signer,
     // so this payload is not cryptographically verified. The protection is that only the
     // keypair holder can sign the transaction. For Secp256r1, the data_payload IS verified.
-    let mut ed25519_payload = Vec::with_capacity(64 + args.actions_bytes.len());
+    let mut ed25519_payload = Vec::with_capacity(65 + args.actions_bytes.len());
     ed25519_payload.extend_from_slice(payer.key().as_ref());
     ed25519_payload.extend_from_slice(&args.session_key);
     ed25519_payload.extend_from_slice(&args.actions_bytes);
+    ed25519_payload.push(args.flags);
+    if let Some(template) = &args.template {
+        ed25519_payload.extend_from_slice(template);
+    }
 
     match auth_header.authority_type {
         0 => {
-            // Ed25519: Include payer + session_key + actions in signed payload
+            // Ed25519: Include payer + session_key + actions + flags + template in signed payload
             Ed25519Authenticator.authenticate(
                 accounts,
                 auth_data,
@@ -251,7 +314,7 @@ pub fn process(
             extended_data_payload.extend_from_slice(data_payload);
             extended_data_payload.extend_from_slice(payer.key().as_ref());
 
-            Secp256r1Authenticator.authenticate(
+            Secp256r1Authenticator::new(&limits)?.authenticate(
                 accounts,
                 auth_data,
                 authority_payload,
@@ -273,8 +336,26 @@ pub fn process(
     }
     check_zero_data(session_pda, ProgramError::AccountAlreadyInitialized)?;
 
-    // Create Session Account — variable size if actions are present
-    let space = SESSION_HEADER_SIZE + args.actions_bytes.len();
+    // Resolve the policy template (template-bound sessions only)
+    let template_actions: Option<&[u8]> = match &args.template {
+        Some(template_key) => Some(load_template_actions(
+            template_key,
+            accounts,
+            wallet_pda.key(),
+            program_id,
+        )?),
+        None => None,
+    };
+    let template_state_len = match template_actions {
+        Some(actions) => crate::state::policy_template::state_len(actions)?,
+        None => 0,
+    };
+
+    // Create Session Account — variable size if actions or a template binding are present
+    let space = match template_actions {
+        Some(_) => SESSION_HEADER_SIZE + 32 + template_state_len,
+        None => SESSION_HEADER_SIZE + args.actions_bytes.len(),
+    };
     let session_rent = rent.minimum_balance(space);
 
     let bump_arr = [bump];
@@ -300,11 +381,17 @@ pub fn process(
     let session = SessionAccount {
         discriminator: AccountDiscriminator::Session as u8,
         bump,
-        version: crate::state::CURRENT_ACCOUNT_VERSION,
-        _padding: [0; 5],
+        version: SESSION_VERSION,
+        flags: args.flags,
+        deferred_nonce: 0,
         wallet: *wallet_pda.key(),
         session_key: Pubkey::from(args.session_key),
         expires_at: args.expires_at,
+        creator: *authorizer_pda.key(),
+        last_used_slot: 0,
+        use_count: 0,
+        total_sol_out: 0,
+        total_token_out: 0,
     };
 
     // Write fixed header
@@ -322,12 +409,48 @@ pub fn process(
             .copy_from_slice(&args.actions_bytes);
     }
 
+    // Write template binding + initial counters seeded from the template
+    if let (Some(template_key), Some(actions)) = (&args.template, template_actions) {
+        data[SESSION_HEADER_SIZE..SESSION_HEADER_SIZE + 32].copy_from_slice(template_key);
+        gather_state(actions, &mut data[SESSION_HEADER_SIZE + 32..])?;
+    }
+
     Ok(())
 }
 
+/// Finds the Policy Template PDA `template_key` in `accounts`, checks it is a
+/// template of `wallet`, and returns its action buffer.
+fn load_template_actions<'a>(
+    template_key: &[u8; 32],
+    accounts: &'a [AccountInfo],
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<&'a [u8], ProgramError> {
+    let template_pda = accounts
+        .iter()
+        .find(|acc| acc.key() == template_key)
+        .ok_or(AuthError::InvalidPolicyTemplate)?;
+    if template_pda.owner() != program_id {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let template_data = unsafe { template_pda.borrow_data_unchecked() };
+    if template_data.len() <= POLICY_TEMPLATE_HEADER_SIZE {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let template =
+        unsafe { std::ptr::read_unaligned(template_data.as_ptr() as *const PolicyTemplateAccount) };
+    if template.discriminator != AccountDiscriminator::PolicyTemplate as u8
+        || template.wallet != *wallet
+    {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    Ok(&template_data[POLICY_TEMPLATE_HEADER_SIZE..])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::session::SESSION_FLAG_REQUIRE_CREATOR;
 
     #[test]
     fn test_create_session_args_from_bytes() {
@@ -355,11 +478,13 @@ mod tests {
         let mut data = Vec::new();
         data.extend_from_slice(&[7u8; 32]); // session_key
         data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
-        data.extend_from_slice(&0u16.to_le_bytes()); // actions_len = 0
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes()); // actions_len = 0
+        data.push(0); // flags
 
         let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
         assert!(args.actions_bytes.is_empty());
-        assert_eq!(args.args_end_offset, 42);
+        assert_eq!(args.flags, 0);
+        assert_eq!(args.args_end_offset, 43);
     }
 
     #[test]
@@ -375,12 +500,14 @@ mod tests {
         actions.extend_from_slice(&0u64.to_le_bytes()); // expires_at
         actions.extend_from_slice(&500_000u64.to_le_bytes()); // max
 
-        data.extend_from_slice(&(actions.len() as u16).to_le_bytes()); // actions_len
+        let actions_len = actions.len() as u16 | CREATE_SESSION_FLAGS_PRESENT;
+        data.extend_from_slice(&actions_len.to_le_bytes());
         data.extend_from_slice(&actions);
+        data.push(0); // flags
 
         let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
         assert_eq!(args.actions_bytes.len(), 19);
-        assert_eq!(args.args_end_offset, 42 + 19);
+        assert_eq!(args.args_end_offset, 42 + 19 + 1);
     }
 
     #[test]
@@ -396,8 +523,10 @@ mod tests {
         actions.extend_from_slice(&0u64.to_le_bytes());
         actions.extend_from_slice(&500_000u64.to_le_bytes());
 
-        data.extend_from_slice(&(actions.len() as u16).to_le_bytes());
+        let actions_len = actions.len() as u16 | CREATE_SESSION_FLAGS_PRESENT;
+        data.extend_from_slice(&actions_len.to_le_bytes());
         data.extend_from_slice(&actions);
+        data.push(0); // flags
 
         assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
     }
@@ -415,15 +544,18 @@ mod tests {
         actions.extend_from_slice(&0u64.to_le_bytes());
         actions.extend_from_slice(&500_000u64.to_le_bytes());
 
-        data.extend_from_slice(&(actions.len() as u16).to_le_bytes());
+        let actions_len = actions.len() as u16 | CREATE_SESSION_FLAGS_PRESENT;
+        data.extend_from_slice(&actions_len.to_le_bytes());
         data.extend_from_slice(&actions);
+        data.push(SESSION_FLAG_REQUIRE_CREATOR); // flags
 
         // Simulate trailing auth payload
         data.extend_from_slice(&[0xAA; 50]);
 
         let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
         assert_eq!(args.actions_bytes.len(), 19);
-        assert_eq!(args.args_end_offset, 42 + 19);
+        assert_eq!(args.flags, SESSION_FLAG_REQUIRE_CREATOR);
+        assert_eq!(args.args_end_offset, 42 + 19 + 1);
         // Trailing 50 bytes would be auth_payload — not parsed here
     }
 
@@ -461,11 +593,144 @@ mod tests {
         }
         assert!(actions.len() <= 2048);
 
-        data.extend_from_slice(&(actions.len() as u16).to_le_bytes());
+        let actions_len = actions.len() as u16 | CREATE_SESSION_FLAGS_PRESENT;
+        data.extend_from_slice(&actions_len.to_le_bytes());
         data.extend_from_slice(&actions);
+        data.push(0); // flags
 
         let result = ParsedCreateSessionArgs::from_bytes(&data);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().actions_bytes.len(), actions.len());
     }
+
+    #[test]
+    fn test_create_session_args_require_creator_flag() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes()); // actions_len = 0
+        data.push(SESSION_FLAG_REQUIRE_CREATOR); // flags
+
+        let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
+        assert_eq!(args.flags, SESSION_FLAG_REQUIRE_CREATOR);
+        assert_eq!(args.args_end_offset, 43);
+    }
+
+    #[test]
+    fn test_create_session_args_template_flag() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes()); // actions_len = 0
+        data.push(SESSION_FLAG_TEMPLATE); // flags
+        data.extend_from_slice(&[5u8; 32]); // template
+        data.extend_from_slice(&[0xAA; 3]); // auth payload
+
+        let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
+        assert_eq!(args.template, Some([5u8; 32]));
+        assert_eq!(args.args_end_offset, 75);
+    }
+
+    #[test]
+    fn test_create_session_args_template_with_actions_rejected() {
+        // SolMaxPerTx: header(11) + data(8)
+        let mut action = vec![3u8];
+        action.extend_from_slice(&8u16.to_le_bytes());
+        action.extend_from_slice(&0u64.to_le_bytes());
+        action.extend_from_slice(&500_000u64.to_le_bytes());
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&(action.len() as u16 | CREATE_SESSION_FLAGS_PRESENT).to_le_bytes());
+        data.extend_from_slice(&action);
+        data.push(SESSION_FLAG_TEMPLATE);
+        data.extend_from_slice(&[5u8; 32]);
+
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+
+        // Missing template key
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&12345678u64.to_le_bytes());
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes());
+        data.push(SESSION_FLAG_TEMPLATE);
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_create_session_args_staged_skips_validation() {
+        // A first chunk that cuts an action mid-way is fine for a staged session.
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&(5u16 | CREATE_SESSION_FLAGS_PRESENT).to_le_bytes()); // actions_len
+        data.extend_from_slice(&[3, 8, 0, 0, 0]); // partial SolMaxPerTx header
+        data.push(SESSION_FLAG_STAGED);
+
+        let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
+        assert_eq!(args.actions_bytes.len(), 5);
+
+        // Without the flag the same bytes are rejected.
+        let last = data.len() - 1;
+        data[last] = 0;
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+
+        // Staged + template is contradictory.
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&12345678u64.to_le_bytes());
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes());
+        data.push(SESSION_FLAG_STAGED | SESSION_FLAG_TEMPLATE);
+        data.extend_from_slice(&[5u8; 32]);
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_create_session_args_unknown_flag_rejected() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes()); // actions_len = 0
+        data.push(0x80); // undefined flag bit
+
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_create_session_args_missing_flags_rejected() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&CREATE_SESSION_FLAGS_PRESENT.to_le_bytes()); // no flags byte
+
+        assert!(ParsedCreateSessionArgs::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_create_session_args_unflagged_form() {
+        // Clients predating session flags: the auth payload follows the actions.
+        let mut action = vec![3u8];
+        action.extend_from_slice(&8u16.to_le_bytes());
+        action.extend_from_slice(&0u64.to_le_bytes());
+        action.extend_from_slice(&500_000u64.to_le_bytes());
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]); // session_key
+        data.extend_from_slice(&12345678u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(&(action.len() as u16).to_le_bytes());
+        data.extend_from_slice(&action);
+        data.push(0xFF); // auth payload, not a flags byte
+
+        let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
+        assert_eq!(args.flags, 0);
+        assert_eq!(args.actions_bytes.len(), 19);
+        assert_eq!(args.args_end_offset, 42 + 19);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&12345678u64.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        let args = ParsedCreateSessionArgs::from_bytes(&data).unwrap();
+        assert_eq!(args.flags, 0);
+        assert_eq!(args.args_end_offset, 42);
+    }
 }
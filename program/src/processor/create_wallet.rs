@@ -10,8 +10,12 @@ use pinocchio::{
 };
 
 use crate::{
+    auth::possession::{prove_possession, REGISTER_FLAG_PROVE_POSSESSION},
     error::AuthError,
-    state::{authority::AuthorityAccountHeader, wallet::WalletAccount, AccountDiscriminator},
+    state::{
+        authority::AuthorityAccountHeader, wallet::WalletAccount, wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
 };
 
 /// Arguments for the `CreateWallet` instruction.
@@ -20,6 +24,7 @@ use crate::{
 /// - `user_seed`: 32-byte seed for deterministic wallet derivation.
 /// - `authority_type`: 0 for Ed25519, 1 for Secp256r1.
 /// - `auth_bump`: Bump seed for the authority PDA (optional/informational).
+/// - `flags`: Registration flags (`REGISTER_FLAG_PROVE_POSSESSION`).
 /// - `_padding`: Reserved for alignment (ensure total size is multiple of 8).
 #[repr(C, align(8))]
 #[derive(NoPadding)]
@@ -27,7 +32,8 @@ pub struct CreateWalletArgs {
     pub user_seed: [u8; 32],
     pub authority_type: u8,
     pub auth_bump: u8,
-    pub _padding: [u8; 6], // 32+1+1+6 = 40 bytes
+    pub flags: u8,
+    pub _padding: [u8; 5], // 32+1+1+1+5 = 40 bytes
 }
 
 impl CreateWalletArgs {
@@ -43,13 +49,18 @@ impl CreateWalletArgs {
 
         let authority_type = fixed[32];
         let auth_bump = fixed[33];
-        // skip 6 padding bytes
+        let flags = fixed[34];
+        if flags & !REGISTER_FLAG_PROVE_POSSESSION != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // skip 5 padding bytes
 
         let args = Self {
             user_seed,
             authority_type,
             auth_bump,
-            _padding: [0; 6],
+            flags,
+            _padding: [0; 5],
         };
 
         Ok((args, rest))
@@ -63,6 +74,10 @@ impl CreateWalletArgs {
 /// 2. A `Vault` PDA: To hold assets (signer).
 /// 3. An `Authority` PDA: The initial owner (Admin/Owner role).
 ///
+/// With `REGISTER_FLAG_PROVE_POSSESSION` the owner key must authenticate
+/// against its new Authority PDA (challenge: its address), passing its auth
+/// payload after the authority data, and is marked verified.
+///
 /// # Accounts:
 /// 1. `[signer, writable]` Payer: Pays for account creation.
 /// 2. `[writable]` Wallet PDA: Derived from `["wallet", user_seed]`.
@@ -70,6 +85,8 @@ impl CreateWalletArgs {
 /// 4. `[writable]` Authority PDA: Derived from `["authority", wallet_pubkey, id_seed]`.
 /// 5. `[]` System Program.
 /// 6. `[]` Rent Sysvar.
+/// 7. `[]` Auth extra, with proof of possession (Ed25519: owner keypair
+///    signer | Secp256r1: sysvar_instructions).
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -187,7 +204,8 @@ pub fn process(
         discriminator: AccountDiscriminator::Wallet as u8,
         bump: wallet_bump,
         version: crate::state::CURRENT_ACCOUNT_VERSION,
-        _padding: [0; 5],
+        flags: 0,
+        _padding: [0; 4],
     };
     unsafe {
         std::ptr::write_unaligned(
@@ -238,9 +256,10 @@ pub fn process(
         role: 0,
         bump: auth_bump,
         version: crate::state::CURRENT_ACCOUNT_VERSION,
-        _padding1: [0; 3],
+        flags: 0,
+        _padding1: [0; 2],
         counter: 0,
-        _padding2: [0; 4],
+        last_used_slot: 0,
         wallet: *wallet_pda.key(),
     };
 
@@ -289,6 +308,19 @@ pub fn process(
         _ => unreachable!(),
     }
 
+    if args.flags & REGISTER_FLAG_PROVE_POSSESSION != 0 {
+        // A new wallet has the default limits
+        prove_possession(
+            accounts,
+            &WalletLimits::new(wallet_pda, accounts, program_id),
+            auth_account_data,
+            &rest[full_auth_data.len()..],
+            auth_pda.key().as_ref(),
+            &[0],
+            program_id,
+        )?;
+    }
+
     Ok(())
 }
 
@@ -324,7 +356,8 @@ mod tests {
         data.extend_from_slice(&user_seed);
         data.push(1); // authority_type = Secp256r1
         data.push(123); // bump
-        data.extend_from_slice(&[0; 6]); // padding
+        data.push(REGISTER_FLAG_PROVE_POSSESSION);
+        data.extend_from_slice(&[0; 5]); // padding
 
         // Payload for Secp256r1: credential_id_hash(32) + pubkey(33)
         let cred_id_hash = [4u8; 32];
@@ -336,6 +369,7 @@ mod tests {
         assert_eq!(args.user_seed, user_seed);
         assert_eq!(args.authority_type, 1);
         assert_eq!(args.auth_bump, 123);
+        assert_eq!(args.flags, REGISTER_FLAG_PROVE_POSSESSION);
         assert_eq!(rest.len(), 65); // from_bytes returns the raw remaining data
         assert_eq!(&rest[0..32], &cred_id_hash);
         assert_eq!(&rest[32..65], &pubkey);
@@ -345,5 +379,10 @@ mod tests {
     fn test_create_wallet_args_too_short() {
         let data = vec![0u8; 39]; // Need 40
         assert!(CreateWalletArgs::from_bytes(&data).is_err());
+
+        // Unknown flags are rejected
+        let mut data = vec![0u8; 40];
+        data[34] = 0x80;
+        assert!(CreateWalletArgs::from_bytes(&data).is_err());
     }
 }
@@ -0,0 +1,208 @@
+use assertions::sol_assert_bytes_eq;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    error::AuthError,
+    state::{
+        deferred::{
+            DeferredBufferAccount, DeferredExecAccount, DEFERRED_BUFFER_HEADER_SIZE,
+            MAX_DEFERRED_BUFFER_SIZE,
+        },
+        AccountDiscriminator, CURRENT_ACCOUNT_VERSION,
+    },
+    utils::{initialize_pda_account, transfer_lamports},
+};
+
+/// Process the WriteDeferredBuffer instruction.
+///
+/// Appends a chunk of serialized compact instructions to the Deferred Buffer
+/// PDA `["deferred_buffer", deferred_exec]`, creating it on the first write.
+/// Used after `Authorize` when the instructions do not fit in the
+/// `ExecuteDeferred` transaction; `ExecuteDeferred` then runs from the buffer.
+///
+/// Only the DeferredExec payer can write, and only before the authorization
+/// expires. No authority signature is needed: the buffer is only ever executed
+/// after its contents hash to the signed `instructions_hash`.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer (must match the DeferredExec payer)
+/// 2. `[]` DeferredExec PDA
+/// 3. `[writable]` Deferred Buffer PDA (created on the first write)
+/// 4. `[]` System Program
+///
+/// # Instruction Data (after discriminator):
+///   [offset(4)][chunk(N)]
+///
+/// `offset` must equal the bytes written so far, so a replayed or reordered
+/// chunk fails instead of corrupting the payload.
+pub fn process_write_deferred_buffer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let offset = u32::from_le_bytes(instruction_data[..4].try_into().unwrap()) as usize;
+    let chunk = &instruction_data[4..];
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let deferred_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let buffer_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Validate the DeferredExec authorization
+    if deferred_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let deferred_data = unsafe { deferred_pda.borrow_data_unchecked() };
+    if deferred_data.len() < std::mem::size_of::<DeferredExecAccount>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let deferred =
+        unsafe { std::ptr::read_unaligned(deferred_data.as_ptr() as *const DeferredExecAccount) };
+    if deferred.discriminator != AccountDiscriminator::DeferredExec as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if deferred.payer != *payer.key() {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.slot > deferred.expires_at {
+        return Err(AuthError::DeferredAuthorizationExpired.into());
+    }
+
+    // Derive the buffer PDA
+    let (buffer_key, bump) = find_program_address(
+        &[b"deferred_buffer", deferred_pda.key().as_ref()],
+        program_id,
+    );
+    if !sol_assert_bytes_eq(buffer_pda.key().as_ref(), buffer_key.as_ref(), 32) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let new_len = offset + chunk.len();
+    if new_len > MAX_DEFERRED_BUFFER_SIZE {
+        return Err(AuthError::InvalidDeferredBuffer.into());
+    }
+    let space = DEFERRED_BUFFER_HEADER_SIZE + new_len;
+    let rent = Rent::get()?;
+    let required = rent.minimum_balance(space);
+
+    if buffer_pda.data_is_empty() {
+        if offset != 0 {
+            return Err(AuthError::InvalidDeferredBuffer.into());
+        }
+
+        let bump_arr = [bump];
+        let seeds = [
+            Seed::from(b"deferred_buffer"),
+            Seed::from(deferred_pda.key().as_ref()),
+            Seed::from(&bump_arr),
+        ];
+        initialize_pda_account(
+            payer,
+            buffer_pda,
+            system_program,
+            space,
+            required,
+            program_id,
+            &seeds,
+        )?;
+
+        let header = DeferredBufferAccount {
+            discriminator: AccountDiscriminator::DeferredBuffer as u8,
+            version: CURRENT_ACCOUNT_VERSION,
+            bump,
+            _padding: [0u8; 5],
+            deferred: *deferred_pda.key(),
+            payer: *payer.key(),
+        };
+        let data = unsafe { buffer_pda.borrow_mut_data_unchecked() };
+        unsafe {
+            std::ptr::write_unaligned(data.as_mut_ptr() as *mut DeferredBufferAccount, header);
+        }
+    } else {
+        if buffer_pda.owner() != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let data = unsafe { buffer_pda.borrow_data_unchecked() };
+        if data.len() < DEFERRED_BUFFER_HEADER_SIZE
+            || data[0] != AccountDiscriminator::DeferredBuffer as u8
+        {
+            return Err(AuthError::InvalidDeferredBuffer.into());
+        }
+        if data.len() != DEFERRED_BUFFER_HEADER_SIZE + offset {
+            return Err(AuthError::InvalidDeferredBuffer.into());
+        }
+
+        let current = buffer_pda.lamports();
+        if required > current {
+            transfer_lamports(payer, buffer_pda, system_program, required - current)?;
+        }
+        buffer_pda.resize(space)?;
+    }
+
+    let data = unsafe { buffer_pda.borrow_mut_data_unchecked() };
+    data[DEFERRED_BUFFER_HEADER_SIZE + offset..].copy_from_slice(chunk);
+
+    Ok(())
+}
+
+/// Validates a Deferred Buffer belonging to `deferred_pda` and returns its payload.
+pub fn buffer_payload<'a>(
+    buffer_pda: &'a AccountInfo,
+    deferred_pda: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<&'a [u8], ProgramError> {
+    if buffer_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = unsafe { buffer_pda.borrow_data_unchecked() };
+    if data.len() < DEFERRED_BUFFER_HEADER_SIZE {
+        return Err(AuthError::InvalidDeferredBuffer.into());
+    }
+    let header = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const DeferredBufferAccount) };
+    if header.discriminator != AccountDiscriminator::DeferredBuffer as u8
+        || header.deferred != *deferred_pda
+    {
+        return Err(AuthError::InvalidDeferredBuffer.into());
+    }
+    Ok(&data[DEFERRED_BUFFER_HEADER_SIZE..])
+}
+
+/// Closes a Deferred Buffer: zeroes its data and moves its lamports to `refund_dest`.
+pub fn close_buffer(buffer_pda: &AccountInfo, refund_dest: &AccountInfo) -> ProgramResult {
+    let buffer_lamports = buffer_pda.lamports();
+    let refund_lamports = refund_dest.lamports();
+    unsafe {
+        *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
+            .checked_add(buffer_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *buffer_pda.borrow_mut_lamports_unchecked() = 0;
+    }
+    let data = unsafe { buffer_pda.borrow_mut_data_unchecked() };
+    data.fill(0);
+    Ok(())
+}
@@ -2,14 +2,22 @@ use crate::{
     auth::{
         ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
     },
-    compact::{parse_compact_instructions_ref_with_len, CompactInstructionRef},
+    compact::{
+        is_compact_v2, parse_compact_instructions_ref_with_len, AccountResolver,
+        CompactInstructionRef,
+    },
     error::AuthError,
-    processor::execute_actions::{
-        evaluate_post_actions, evaluate_pre_actions, snapshot_token_authorities,
-        snapshot_token_balances, verify_token_authorities_unchanged,
+    processor::freeze::check_not_frozen,
+    processor::session_enforcement::SessionEnforcement,
+    processor::step_up::{authenticate_step_up, find_step_up_cosigner, StepUpGuard},
+    processor::timelock::TimelockGuard,
+    state::{
+        authority::AuthorityAccountHeader,
+        session::read_session,
+        wallet_config::{load_execution_policy, WalletLimits},
+        AccountDiscriminator,
     },
-    state::{authority::AuthorityAccountHeader, session::has_actions, AccountDiscriminator},
-    utils::get_stack_height,
+    utils::{get_stack_height, split_trailing_payload},
 };
 use pinocchio::{
     account_info::AccountInfo,
@@ -27,16 +35,34 @@ use pinocchio::{
 ///
 /// # Logic:
 /// 1. **Authentication**: Verifies that the signer is a valid `Authority` or `Session` for this wallet.
-/// 2. **Session Checks**: If authenticated via Session, enforces slot expiry and action permissions.
+/// 2. **Session Checks**: If authenticated via Session, enforces slot expiry, the creator
+///    liveness flag and action permissions. A template-bound session is enforced against
+///    its Policy Template's actions merged with the session's own counters.
 /// 3. **Decompression**: Expands `CompactInstructions` (index-based references) into full Solana instructions.
 /// 4. **Execution**: Invokes the Instructions via CPI, signing with the Vault PDA.
+/// 5. **Timelock**: On a timelocked wallet, rejects calls to sensitive programs and vault
+///    SOL or token outflows above the configured thresholds; those must be queued with
+///    `QueueExecute`.
+/// 6. **Step-up**: On a wallet with a step-up authority, rejects vault outflows above the
+///    step-up thresholds unless that authority executes or co-signs.
+/// 7. **Usage Stats**: Stamps `last_used_slot` on the Authority, or `last_used_slot`,
+///    `use_count` and outflow totals on the Session.
 ///
 /// # Accounts:
 /// 1. `[signer]` Payer.
 /// 2. `[]` Wallet PDA.
 /// 3. `[signer]` Authority or Session PDA.
 /// 4. `[signer]` Vault PDA (Signer for CPI).
-/// 5. `...` Inner accounts referenced by instructions.
+/// 5. `...` Inner accounts referenced by instructions. A session created with
+///    `SESSION_FLAG_REQUIRE_CREATOR` must also pass its creator Authority PDA here, and a
+///    template-bound session its Policy Template PDA. A timelocked or step-up wallet must
+///    pass its Wallet Config PDA, and a step-up co-signer its `[writable]` Authority PDA.
+///
+/// # Instruction Data (after discriminator):
+///   [compact_instructions(variable)][auth_payload(variable)]
+///
+/// With a step-up co-signer, its auth payload (empty for Ed25519) and that
+/// payload's length (u16 LE) follow the executing authority's.
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -70,6 +96,7 @@ pub fn process(
     if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
         return Err(ProgramError::InvalidAccountData);
     }
+    check_not_frozen(wallet_data)?;
 
     if !authority_pda.is_writable() {
         return Err(ProgramError::InvalidAccountData);
@@ -92,10 +119,29 @@ pub fn process(
     // just to measure length.
     let (compact_instructions, compact_len) =
         parse_compact_instructions_ref_with_len(instruction_data)?;
+    // v2 streams may use the reserved indexes: payer, wallet, vault. v1 index
+    // bytes are always account positions.
+    let resolver = if is_compact_v2(instruction_data) {
+        AccountResolver::new(accounts, [0, 1, 3])
+    } else {
+        AccountResolver::positional(accounts)
+    };
 
-    // Track whether this is a session-based execution and the current slot
+    // Wallet Config of a wallet with a timelock or step-up authority
+    let policy = load_execution_policy(wallet_data, wallet_pda.key(), accounts, program_id)?;
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id)
+        .with_config(policy.as_ref().map(|config| &config.account));
+    let step_up_cosigner = policy
+        .as_ref()
+        .and_then(|config| find_step_up_cosigner(config, authority_pda, accounts));
+    let (authority_payload, step_up_payload) = if step_up_cosigner.is_some() {
+        split_trailing_payload(&instruction_data[compact_len..])?
+    } else {
+        (&instruction_data[compact_len..], &[][..])
+    };
+
+    // Track whether this is a session-based execution
     let mut is_session = false;
-    let mut session_slot: u64 = 0;
 
     match discriminator {
         2 => {
@@ -128,15 +174,13 @@ pub fn process(
                 }
                 1 => {
                     // Secp256r1 (WebAuthn)
-                    let data_payload = &instruction_data[..compact_len];
-                    let authority_payload = &instruction_data[compact_len..];
-                    let accounts_hash =
-                        compute_accounts_hash(accounts, &compact_instructions)?;
-                    let mut extended_payload = Vec::with_capacity(compact_len + 32);
-                    extended_payload.extend_from_slice(data_payload);
-                    extended_payload.extend_from_slice(&accounts_hash);
-
-                    Secp256r1Authenticator.authenticate(
+                    let extended_payload = signed_payload(
+                        &instruction_data[..compact_len],
+                        &resolver,
+                        &compact_instructions,
+                    )?;
+
+                    Secp256r1Authenticator::new(&limits)?.authenticate(
                         accounts,
                         authority_data,
                         authority_payload,
@@ -159,30 +203,7 @@ pub fn process(
                 return Err(AuthError::PermissionDenied.into());
             }
 
-            if authority_data.len()
-                < std::mem::size_of::<crate::state::session::SessionAccount>()
-            {
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            let session = unsafe {
-                std::ptr::read_unaligned(
-                    authority_data.as_ptr() as *const crate::state::session::SessionAccount,
-                )
-            };
-
-            let clock = Clock::get()?;
-            let current_slot = clock.slot;
-
-            // Verify Wallet
-            if session.wallet != *wallet_pda.key() {
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            // Verify Expiry
-            if current_slot > session.expires_at {
-                return Err(AuthError::SessionExpired.into());
-            }
+            let session = read_session(authority_data).ok_or(ProgramError::InvalidAccountData)?;
 
             // Verify Signer matches Session Key
             let mut signer_matched = false;
@@ -196,22 +217,35 @@ pub fn process(
                 return Err(ProgramError::MissingRequiredSignature);
             }
 
-            // Pre-CPI action checks (program whitelist/blacklist)
-            if has_actions(authority_data) {
-                evaluate_pre_actions(
-                    authority_data,
-                    &compact_instructions,
-                    accounts,
-                    current_slot,
-                )?;
-            }
-
             is_session = true;
-            session_slot = current_slot;
         }
         _ => return Err(ProgramError::InvalidAccountData),
     }
 
+    // The step-up authority co-signs the same compact instructions and accounts
+    let stepped_up = match step_up_cosigner {
+        Some(step_up_pda) => {
+            let extended_payload = signed_payload(
+                &instruction_data[..compact_len],
+                &resolver,
+                &compact_instructions,
+            )?;
+            authenticate_step_up(
+                accounts,
+                &limits,
+                step_up_pda,
+                wallet_pda.key(),
+                step_up_payload,
+                &extended_payload,
+                program_id,
+            )?;
+            true
+        },
+        None => policy
+            .as_ref()
+            .is_some_and(|config| config.account.step_up_authority == *authority_pda.key()),
+    };
+
     // Get vault bump for signing
     let (vault_key, vault_bump) =
         find_program_address(&[b"vault", wallet_pda.key().as_ref()], program_id);
@@ -221,44 +255,36 @@ pub fn process(
         return Err(ProgramError::InvalidSeeds);
     }
 
-    // Snapshot balances before CPI (for session action enforcement)
-    let vault_lamports_before = if is_session { vault_pda.lamports() } else { 0 };
-    let token_snapshots_before = if is_session {
-        // Reuse the existing `authority_data` borrow — no additional borrow of authority_pda.
-        snapshot_token_balances(authority_data, accounts, vault_pda.key())?
-    } else {
-        Vec::new()
-    };
-
-    // ── Session invariants (defense against System::Assign / SetAuthority escapes) ──
-    // A session that whitelists System Program (a common pattern for SOL transfers)
-    // could otherwise craft `System::Assign(vault, attacker)` — the lamport-based
-    // limits see no outflow, but ownership of the vault silently transfers to the
-    // attacker, who then drains it in a follow-up tx. Same class of attack via
-    // SPL Token's `SetAuthority` / `Approve` on vault-owned token accounts.
-    //
-    // Snapshot the vault's metadata + every listed-mint vault-owned token account's
-    // authority fields BEFORE the CPI loop; verify unchanged AFTER.
-    let session_has_actions = is_session && has_actions(authority_data);
-    let vault_owner_before = if session_has_actions {
-        Some(*vault_pda.owner())
-    } else {
-        None
-    };
-    let vault_data_len_before = if session_has_actions {
-        Some(unsafe { vault_pda.borrow_data_unchecked().len() })
+    // Session checks (wallet, expiry, staging, creator liveness), pre-CPI
+    // actions and the balance / authority snapshots for post-CPI enforcement.
+    let mut enforcement = if is_session {
+        Some(SessionEnforcement::begin(
+            authority_data,
+            wallet_pda.key(),
+            &compact_instructions,
+            &resolver,
+            vault_pda,
+            program_id,
+        )?)
     } else {
         None
     };
-    let token_authority_snapshots = if session_has_actions {
-        snapshot_token_authorities(authority_data, accounts, vault_pda.key())?
-    } else {
-        Vec::new()
-    };
 
-    // Track gross SOL outflow across all CPIs (for SolMaxPerTx check)
-    let mut vault_lamports_gross_out: u64 = 0;
-    let mut prev_vault_lamports = vault_lamports_before;
+    // Timelocked wallet: sensitive programs and large SOL outflows must be queued.
+    // Step-up wallet: large outflows need the step-up authority.
+    let (timelock, mut step_up) = match &policy {
+        Some(config) => (
+            TimelockGuard::begin(
+                config,
+                &compact_instructions,
+                &resolver,
+                accounts,
+                vault_pda,
+            )?,
+            StepUpGuard::begin(config, stepped_up, accounts, vault_pda),
+        ),
+        None => (None, None),
+    };
 
     // Reuse the same Vecs across all inner CPIs — allocated once, cleared +
     // repushed each iteration. Saves 2 Vec::with_capacity allocations per
@@ -277,7 +303,7 @@ pub fn process(
 
     // Execute each compact instruction
     for compact_ix in &compact_instructions {
-        let decompressed = compact_ix.decompress(accounts)?;
+        let decompressed = compact_ix.decompress(&resolver)?;
 
         // Prevent self-reentrancy (Issue #10)
         if decompressed.program_id.as_ref() == program_id.as_ref() {
@@ -298,7 +324,7 @@ pub fn process(
         let ix = Instruction {
             program_id: decompressed.program_id,
             accounts: &account_metas,
-            data: decompressed.data,
+            data: &decompressed.data,
         };
 
         let signer: Signer = (&seeds).into();
@@ -307,60 +333,61 @@ pub fn process(
             invoke_signed_unchecked(&ix, &cpi_accounts, &[signer]);
         }
 
-        // Track gross SOL outflow per CPI (used for SolMaxPerTx — not net balance diff).
-        if is_session {
-            let post = vault_pda.lamports();
-            if prev_vault_lamports > post {
-                vault_lamports_gross_out = vault_lamports_gross_out
-                    .saturating_add(prev_vault_lamports - post);
-            }
-            prev_vault_lamports = post;
+        if let Some(enforcement) = enforcement.as_mut() {
+            enforcement.record_cpi(vault_pda);
+        }
+        if let Some(step_up) = step_up.as_mut() {
+            step_up.record_cpi(vault_pda);
         }
     }
 
-    // ── Post-CPI session invariants ────────────────────────────────────
-    // Verify vault's ownership and data layout were not tampered with. Any
-    // change (System::Assign, Allocate, AllocateWithSeed, AssignWithSeed) is
-    // rejected. This complements the balance-based limits below.
-    if let Some(owner_before) = vault_owner_before {
-        if *vault_pda.owner() != owner_before {
-            return Err(AuthError::SessionVaultOwnerChanged.into());
-        }
+    if let Some(timelock) = timelock {
+        timelock.finish(accounts, vault_pda)?;
     }
-    if let Some(len_before) = vault_data_len_before {
-        let len_after = unsafe { vault_pda.borrow_data_unchecked().len() };
-        if len_after != len_before {
-            return Err(AuthError::SessionVaultDataLenChanged.into());
-        }
+    if let Some(step_up) = step_up {
+        step_up.finish(accounts, vault_pda)?;
     }
-    // Verify no SetAuthority / Approve on listed-mint vault-owned token accounts.
-    verify_token_authorities_unchanged(&token_authority_snapshots, accounts)?;
 
-    // Post-CPI action checks (spending limits)
-    // Reuse the existing `authority_data` borrow — no additional borrow of authority_pda.
-    if session_has_actions {
-        evaluate_post_actions(
-            authority_data,
-            accounts,
-            vault_pda.key(),
-            vault_lamports_before,
-            vault_pda.lamports(),
-            vault_lamports_gross_out,
-            &token_snapshots_before,
-            session_slot,
-        )?;
+    // ── Post-CPI session enforcement and usage statistics ──────────────
+    if let Some(enforcement) = enforcement {
+        enforcement.finish(authority_data, accounts, vault_pda)?;
+    } else {
+        let mut authority_header = unsafe {
+            std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+        };
+        authority_header.last_used_slot = Clock::get()?.slot.min(u32::MAX as u64) as u32;
+        unsafe {
+            std::ptr::write_unaligned(
+                authority_data.as_mut_ptr() as *mut AuthorityAccountHeader,
+                authority_header,
+            );
+        }
     }
 
     Ok(())
 }
 
+/// The payload a Secp256r1 authority signs for `Execute`: the compact
+/// instructions followed by the hash of the accounts they reference.
+fn signed_payload(
+    data_payload: &[u8],
+    resolver: &AccountResolver<'_>,
+    compact_instructions: &[CompactInstructionRef<'_>],
+) -> Result<Vec<u8>, ProgramError> {
+    let accounts_hash = compute_accounts_hash(resolver, compact_instructions)?;
+    let mut extended_payload = Vec::with_capacity(data_payload.len() + 32);
+    extended_payload.extend_from_slice(data_payload);
+    extended_payload.extend_from_slice(&accounts_hash);
+    Ok(extended_payload)
+}
+
 /// Compute SHA256 hash of all account pubkeys referenced by compact instructions (Issue #11).
 ///
 /// Optimisation: pass each 32-byte pubkey as a separate slice to sol_sha256
 /// instead of concatenating them into an owned Vec first. sol_sha256 accepts
 /// an array of slices natively, so the concat step was pure overhead.
 fn compute_accounts_hash(
-    accounts: &[AccountInfo],
+    resolver: &AccountResolver<'_>,
     compact_instructions: &[CompactInstructionRef<'_>],
 ) -> Result<[u8; 32], ProgramError> {
     // Collect slice references (16 bytes each) instead of copying 32-byte pubkeys.
@@ -369,18 +396,10 @@ fn compute_accounts_hash(
     let mut refs: Vec<&[u8]> = Vec::with_capacity(compact_instructions.len() * 4);
 
     for ix in compact_instructions {
-        let program_idx = ix.program_id_index as usize;
-        if program_idx >= accounts.len() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        refs.push(accounts[program_idx].key().as_ref());
+        refs.push(resolver.resolve(ix.program_id_index)?.key().as_ref());
 
         for &acc_idx in ix.accounts {
-            let idx = acc_idx as usize;
-            if idx >= accounts.len() {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            refs.push(accounts[idx].key().as_ref());
+            refs.push(resolver.resolve(acc_idx)?.key().as_ref());
         }
     }
 
@@ -1,7 +1,7 @@
 //! Session action evaluation for the Execute instruction.
 //!
 //! Provides pre-CPI and post-CPI checks for session-based execution.
-//! Pre-CPI: program whitelist/blacklist enforcement.
+//! Pre-CPI: fee payer pinning and program whitelist/blacklist enforcement.
 //! Post-CPI: spending limit enforcement with balance diffing.
 //!
 //! Security model (learned from Swig wallet):
@@ -15,11 +15,14 @@
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
 use crate::{
-    compact::CompactInstructionRef,
+    compact::{AccountResolver, CompactInstructionRef},
     error::AuthError,
     state::{
-        action::{parse_actions, read_u64, write_u64, ActionType, ActionView},
-        session::{has_actions, SESSION_HEADER_SIZE},
+        action::{
+            parse_actions, read_u64, sliding_window_record, sliding_window_spent, write_u64,
+            ActionType, ActionView,
+        },
+        session::{has_actions, session_header_size},
     },
 };
 
@@ -61,73 +64,74 @@ pub struct TokenAuthoritySnapshot {
     pub close_authority: [u8; 36],
 }
 
-/// Evaluate pre-CPI actions (program whitelist/blacklist).
+/// Evaluate pre-CPI actions (payer whitelist, program whitelist/blacklist).
 ///
 /// Call this BEFORE executing compact instructions.
 /// Returns early with Ok(()) if no actions exist.
 pub fn evaluate_pre_actions(
     session_data: &[u8],
     compact_instructions: &[CompactInstructionRef<'_>],
-    accounts: &[AccountInfo],
+    resolver: &AccountResolver<'_>,
     current_slot: u64,
 ) -> Result<(), ProgramError> {
     if !has_actions(session_data) {
         return Ok(());
     }
 
-    let actions_buf = &session_data[SESSION_HEADER_SIZE..];
+    let actions_buf = &session_data[session_header_size(session_data)..];
     let actions = parse_actions(actions_buf)?;
 
-    // Collect whitelist/blacklist program IDs.
-    // Expired whitelist actions are intentionally NOT added to `whitelisted`, but they still set
-    // `has_any_whitelist_action = true`. This means if a whitelist existed but has now expired,
+    // Each lookup binary-searches lists stored in ascending key order and scans
+    // every matching entry otherwise — no per-call heap collection.
+    //
+    // Expired whitelist entries do not admit anything, but the mere presence of a
+    // whitelist action still applies. This means if a whitelist existed but has now expired,
     // NO program is permitted — treating an expired whitelist as a hard deny rather than open
     // access. An expired blacklist entry, however, is silently dropped (the ban has lifted).
-    let mut whitelisted: Vec<[u8; 32]> = Vec::new();
-    let mut blacklisted: Vec<[u8; 32]> = Vec::new();
     let mut has_any_whitelist_action = false;
-
+    // Payer pinning follows the same fail-closed rule as the program whitelist:
+    // once the session carries any PayerWhitelist entry, only an active entry admits a payer.
+    let mut has_any_payer_action = false;
+    let mut has_any_blacklist_action = false;
     for action in &actions {
         match action.action_type {
-            ActionType::ProgramWhitelist => {
-                has_any_whitelist_action = true;
-                if !is_expired(action, current_slot) {
-                    let mut prog_id = [0u8; 32];
-                    prog_id.copy_from_slice(
-                        &actions_buf[action.data_offset..action.data_offset + 32],
-                    );
-                    whitelisted.push(prog_id);
-                }
-            }
-            ActionType::ProgramBlacklist => {
-                if !is_expired(action, current_slot) {
-                    let mut prog_id = [0u8; 32];
-                    prog_id.copy_from_slice(
-                        &actions_buf[action.data_offset..action.data_offset + 32],
-                    );
-                    blacklisted.push(prog_id);
-                }
-            }
+            ActionType::ProgramWhitelist => has_any_whitelist_action = true,
+            ActionType::ProgramBlacklist => has_any_blacklist_action = true,
+            ActionType::PayerWhitelist => has_any_payer_action = true,
             _ => {}
         }
     }
+    let is_active_entry = |action_type: ActionType, key: &[u8]| {
+        actions.has_active_key(actions_buf, action_type, key, current_slot)
+    };
+
+    // Payer pinning: accounts[0] must be a signing, whitelisted payer. A leaked
+    // session key is then only usable through the sponsor's relayer.
+    if has_any_payer_action {
+        let payer = resolver
+            .accounts()
+            .first()
+            .ok_or(AuthError::ActionPayerNotAllowed)?;
+        if !payer.is_signer() || !is_active_entry(ActionType::PayerWhitelist, payer.key()) {
+            return Err(AuthError::ActionPayerNotAllowed.into());
+        }
+    }
 
     // Enforce program restrictions on each instruction
     for ix in compact_instructions {
-        let prog_idx = ix.program_id_index as usize;
-        if prog_idx >= accounts.len() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        let target_program = accounts[prog_idx].key();
+        let target_program = resolver.resolve(ix.program_id_index)?.key();
 
         // Whitelist: if any whitelist action EVER existed (even expired), program must be in the
         // active set. An expired whitelist = deny all programs.
-        if has_any_whitelist_action && !whitelisted.iter().any(|p| p == target_program.as_ref()) {
+        if has_any_whitelist_action
+            && !is_active_entry(ActionType::ProgramWhitelist, target_program)
+        {
             return Err(AuthError::ActionProgramNotWhitelisted.into());
         }
 
-        // Blacklist: program must NOT be in the active set (expired entries already dropped above).
-        if blacklisted.iter().any(|p| p == target_program.as_ref()) {
+        // Blacklist: program must NOT be in the active set (expired entries are ignored).
+        if has_any_blacklist_action && is_active_entry(ActionType::ProgramBlacklist, target_program)
+        {
             return Err(AuthError::ActionProgramBlacklisted.into());
         }
     }
@@ -145,7 +149,7 @@ pub fn snapshot_token_balances(
         return Ok(Vec::new());
     }
 
-    let actions_buf = &session_data[SESSION_HEADER_SIZE..];
+    let actions_buf = &session_data[session_header_size(session_data)..];
     let actions = parse_actions(actions_buf)?;
 
     let mut mints: Vec<[u8; 32]> = Vec::new();
@@ -153,7 +157,8 @@ pub fn snapshot_token_balances(
         match action.action_type {
             ActionType::TokenLimit
             | ActionType::TokenRecurringLimit
-            | ActionType::TokenMaxPerTx => {
+            | ActionType::TokenMaxPerTx
+            | ActionType::TokenSlidingLimit => {
                 let mut mint = [0u8; 32];
                 mint.copy_from_slice(&actions_buf[action.data_offset..action.data_offset + 32]);
                 if !mints.iter().any(|m| m == &mint) {
@@ -197,7 +202,7 @@ pub fn snapshot_token_authorities(
         return Ok(Vec::new());
     }
 
-    let actions_buf = &session_data[SESSION_HEADER_SIZE..];
+    let actions_buf = &session_data[session_header_size(session_data)..];
     let actions = parse_actions(actions_buf)?;
 
     // Collect listed mints (same logic as snapshot_token_balances).
@@ -206,7 +211,8 @@ pub fn snapshot_token_authorities(
         match action.action_type {
             ActionType::TokenLimit
             | ActionType::TokenRecurringLimit
-            | ActionType::TokenMaxPerTx => {
+            | ActionType::TokenMaxPerTx
+            | ActionType::TokenSlidingLimit => {
                 let mut mint = [0u8; 32];
                 mint.copy_from_slice(&actions_buf[action.data_offset..action.data_offset + 32]);
                 if !mints.iter().any(|m| m == &mint) {
@@ -225,9 +231,7 @@ pub fn snapshot_token_authorities(
     let mut out = Vec::new();
     for acc in accounts {
         let owner = acc.owner();
-        if owner.as_ref() != &SPL_TOKEN_PROGRAM_ID
-            && owner.as_ref() != &SPL_TOKEN_2022_PROGRAM_ID
-        {
+        if *owner != SPL_TOKEN_PROGRAM_ID && *owner != SPL_TOKEN_2022_PROGRAM_ID {
             continue;
         }
         let data = unsafe { acc.borrow_data_unchecked() };
@@ -287,9 +291,7 @@ pub fn verify_token_authorities_unchanged(
 
         // Must still be owned by SPL Token (not re-assigned to another program)
         let owner = acc.owner();
-        if owner.as_ref() != &SPL_TOKEN_PROGRAM_ID
-            && owner.as_ref() != &SPL_TOKEN_2022_PROGRAM_ID
-        {
+        if *owner != SPL_TOKEN_PROGRAM_ID && *owner != SPL_TOKEN_2022_PROGRAM_ID {
             return Err(AuthError::SessionTokenAuthorityChanged.into());
         }
 
@@ -298,13 +300,13 @@ pub fn verify_token_authorities_unchanged(
             return Err(AuthError::SessionTokenAuthorityChanged.into());
         }
 
-        if &data[TOKEN_OWNER_OFFSET..TOKEN_OWNER_OFFSET + 32] != snap.owner {
+        if data[TOKEN_OWNER_OFFSET..TOKEN_OWNER_OFFSET + 32] != snap.owner {
             return Err(AuthError::SessionTokenAuthorityChanged.into());
         }
-        if &data[TOKEN_DELEGATE_OFFSET..TOKEN_DELEGATE_OFFSET + 36] != snap.delegate {
+        if data[TOKEN_DELEGATE_OFFSET..TOKEN_DELEGATE_OFFSET + 36] != snap.delegate {
             return Err(AuthError::SessionTokenAuthorityChanged.into());
         }
-        if &data[TOKEN_CLOSE_AUTHORITY_OFFSET..TOKEN_CLOSE_AUTHORITY_OFFSET + 36]
+        if data[TOKEN_CLOSE_AUTHORITY_OFFSET..TOKEN_CLOSE_AUTHORITY_OFFSET + 36]
             != snap.close_authority
         {
             return Err(AuthError::SessionTokenAuthorityChanged.into());
@@ -318,11 +320,12 @@ pub fn verify_token_authorities_unchanged(
 /// `vault_lamports_gross_out` is the sum of all per-CPI outflows from the vault, used for
 /// `SolMaxPerTx` (which must block even DeFi round-trips that appear net-zero).
 /// `vault_lamports_before`/`after` net diff is used for the cumulative limits (SolLimit,
-/// SolRecurringLimit), where net accounting is conservative and appropriate.
+/// SolRecurringLimit, SolSlidingLimit), where net accounting is conservative and appropriate.
 ///
 /// Security: This function first computes all spending deltas and validates
 /// ALL limits before writing any state. This ensures no partial state mutation
 /// if a later check fails.
+#[allow(clippy::too_many_arguments)]
 pub fn evaluate_post_actions(
     session_data: &mut [u8],
     accounts: &[AccountInfo],
@@ -344,7 +347,8 @@ pub fn evaluate_post_actions(
     // If nothing was spent, skip all checks (no state mutation needed for SOL).
     // Token checks still need to run.
 
-    let actions_buf_readonly = &session_data[SESSION_HEADER_SIZE..];
+    let header_size = session_header_size(session_data);
+    let actions_buf_readonly = &session_data[header_size..];
     let actions = parse_actions(actions_buf_readonly)?;
 
     // ── Phase 1: Validate all SOL limits (read-only check) ──────────
@@ -352,58 +356,62 @@ pub fn evaluate_post_actions(
     // if any SOL was spent and a limit action has expired, the tx is rejected.
     // This prevents a session with expired limits from becoming unrestricted.
     for action in &actions {
-        let action_expired = is_expired(action, current_slot);
-        let abs_data_offset = SESSION_HEADER_SIZE + action.data_offset;
+        let action_expired = is_expired(&action, current_slot);
+        let abs_data_offset = header_size + action.data_offset;
 
         match action.action_type {
-            ActionType::SolMaxPerTx => {
+            ActionType::SolMaxPerTx if vault_lamports_gross_out > 0 => {
                 // Use gross outflow so DeFi round-trips that return most lamports cannot bypass
                 // a per-tx cap (the net diff would be near-zero but gross could be large).
-                if vault_lamports_gross_out > 0 {
-                    if action_expired {
-                        return Err(AuthError::ActionSolMaxPerTxExceeded.into());
-                    }
-                    let max = read_u64(&session_data[abs_data_offset..], 0);
-                    if vault_lamports_gross_out > max {
-                        return Err(AuthError::ActionSolMaxPerTxExceeded.into());
-                    }
+                if action_expired {
+                    return Err(AuthError::ActionSolMaxPerTxExceeded.into());
+                }
+                let max = read_u64(&session_data[abs_data_offset..], 0);
+                if vault_lamports_gross_out > max {
+                    return Err(AuthError::ActionSolMaxPerTxExceeded.into());
                 }
             }
-            ActionType::SolLimit => {
-                if sol_spent > 0 {
-                    if action_expired {
-                        return Err(AuthError::ActionSolLimitExceeded.into());
-                    }
-                    let remaining = read_u64(&session_data[abs_data_offset..], 0);
-                    if sol_spent > remaining {
-                        return Err(AuthError::ActionSolLimitExceeded.into());
-                    }
+            ActionType::SolLimit if sol_spent > 0 => {
+                if action_expired {
+                    return Err(AuthError::ActionSolLimitExceeded.into());
+                }
+                let remaining = read_u64(&session_data[abs_data_offset..], 0);
+                if sol_spent > remaining {
+                    return Err(AuthError::ActionSolLimitExceeded.into());
                 }
             }
-            ActionType::SolRecurringLimit => {
-                if sol_spent > 0 {
-                    if action_expired {
-                        return Err(AuthError::ActionSolRecurringLimitExceeded.into());
-                    }
-                    let limit = read_u64(&session_data[abs_data_offset..], 0);
-                    let spent = read_u64(&session_data[abs_data_offset..], 8);
-                    let window = read_u64(&session_data[abs_data_offset..], 16);
-                    let last_reset = read_u64(&session_data[abs_data_offset..], 24);
-
-                    let effective_spent = if current_slot.saturating_sub(last_reset) > window {
-                        // Window expired — reset. But single tx can't exceed full limit.
-                        if sol_spent > limit {
-                            return Err(AuthError::ActionSolRecurringLimitExceeded.into());
-                        }
-                        0u64
-                    } else {
-                        spent
-                    };
-
-                    // Use saturating_add to prevent overflow
-                    if effective_spent.saturating_add(sol_spent) > limit {
+            ActionType::SolRecurringLimit if sol_spent > 0 => {
+                if action_expired {
+                    return Err(AuthError::ActionSolRecurringLimitExceeded.into());
+                }
+                let limit = read_u64(&session_data[abs_data_offset..], 0);
+                let spent = read_u64(&session_data[abs_data_offset..], 8);
+                let window = read_u64(&session_data[abs_data_offset..], 16);
+                let last_reset = read_u64(&session_data[abs_data_offset..], 24);
+
+                let effective_spent = if current_slot.saturating_sub(last_reset) > window {
+                    // Window expired — reset. But single tx can't exceed full limit.
+                    if sol_spent > limit {
                         return Err(AuthError::ActionSolRecurringLimitExceeded.into());
                     }
+                    0u64
+                } else {
+                    spent
+                };
+
+                // Use saturating_add to prevent overflow
+                if effective_spent.saturating_add(sol_spent) > limit {
+                    return Err(AuthError::ActionSolRecurringLimitExceeded.into());
+                }
+            }
+            ActionType::SolSlidingLimit if sol_spent > 0 => {
+                if action_expired {
+                    return Err(AuthError::ActionSolRecurringLimitExceeded.into());
+                }
+                let limit = read_u64(&session_data[abs_data_offset..], 0);
+                let used = sliding_window_spent(&session_data[abs_data_offset..], current_slot);
+                if used.saturating_add(sol_spent) > limit {
+                    return Err(AuthError::ActionSolRecurringLimitExceeded.into());
                 }
             }
             _ => {}
@@ -413,11 +421,14 @@ pub fn evaluate_post_actions(
     // ── Phase 1b: Validate all token limits (read-only check) ───────
     // Same policy as SOL limits: expired = treat as fully exhausted.
     for action in &actions {
-        let action_expired = is_expired(action, current_slot);
-        let abs_data_offset = SESSION_HEADER_SIZE + action.data_offset;
+        let action_expired = is_expired(&action, current_slot);
+        let abs_data_offset = header_size + action.data_offset;
 
         match action.action_type {
-            ActionType::TokenMaxPerTx | ActionType::TokenLimit | ActionType::TokenRecurringLimit => {
+            ActionType::TokenMaxPerTx
+            | ActionType::TokenLimit
+            | ActionType::TokenRecurringLimit
+            | ActionType::TokenSlidingLimit => {
                 let mut mint = [0u8; 32];
                 mint.copy_from_slice(&session_data[abs_data_offset..abs_data_offset + 32]);
 
@@ -476,6 +487,16 @@ pub fn evaluate_post_actions(
                                 return Err(AuthError::ActionTokenRecurringLimitExceeded.into());
                             }
                         }
+                        ActionType::TokenSlidingLimit => {
+                            let limit = read_u64(&session_data[abs_data_offset..], 32);
+                            let used = sliding_window_spent(
+                                &session_data[abs_data_offset + 32..],
+                                current_slot,
+                            );
+                            if used.saturating_add(token_spent) > limit {
+                                return Err(AuthError::ActionTokenRecurringLimitExceeded.into());
+                            }
+                        }
                         _ => {}
                     }
                 }
@@ -486,45 +507,48 @@ pub fn evaluate_post_actions(
 
     // ── Phase 2: All checks passed. Now write state mutations. ──────
     // Re-parse using a slice reference — no allocation needed, same bytes, same offsets.
-    let actions = parse_actions(&session_data[SESSION_HEADER_SIZE..])?;
+    let actions = parse_actions(&session_data[header_size..])?;
 
     for action in &actions {
-        if is_expired(action, current_slot) {
+        if is_expired(&action, current_slot) {
             continue;
         }
 
-        let abs_data_offset = SESSION_HEADER_SIZE + action.data_offset;
+        let abs_data_offset = header_size + action.data_offset;
 
         match action.action_type {
-            ActionType::SolLimit => {
-                if sol_spent > 0 {
-                    let remaining = read_u64(&session_data[abs_data_offset..], 0);
-                    write_u64(
-                        &mut session_data[abs_data_offset..],
-                        0,
-                        remaining.saturating_sub(sol_spent),
-                    );
-                }
+            ActionType::SolLimit if sol_spent > 0 => {
+                let remaining = read_u64(&session_data[abs_data_offset..], 0);
+                write_u64(
+                    &mut session_data[abs_data_offset..],
+                    0,
+                    remaining.saturating_sub(sol_spent),
+                );
             }
-            ActionType::SolRecurringLimit => {
-                if sol_spent > 0 {
-                    let _limit = read_u64(&session_data[abs_data_offset..], 0);
-                    let spent = read_u64(&session_data[abs_data_offset..], 8);
-                    let window = read_u64(&session_data[abs_data_offset..], 16);
-                    let last_reset = read_u64(&session_data[abs_data_offset..], 24);
-
-                    let (new_spent, new_last_reset) =
-                        if current_slot.saturating_sub(last_reset) > window {
-                            let aligned = (current_slot / window) * window;
-                            (sol_spent, aligned)
-                        } else {
-                            (spent.saturating_add(sol_spent), last_reset)
-                        };
+            ActionType::SolRecurringLimit if sol_spent > 0 => {
+                let _limit = read_u64(&session_data[abs_data_offset..], 0);
+                let spent = read_u64(&session_data[abs_data_offset..], 8);
+                let window = read_u64(&session_data[abs_data_offset..], 16);
+                let last_reset = read_u64(&session_data[abs_data_offset..], 24);
+
+                let (new_spent, new_last_reset) =
+                    if current_slot.saturating_sub(last_reset) > window {
+                        let aligned = (current_slot / window) * window;
+                        (sol_spent, aligned)
+                    } else {
+                        (spent.saturating_add(sol_spent), last_reset)
+                    };
 
-                    write_u64(&mut session_data[abs_data_offset..], 8, new_spent);
-                    write_u64(&mut session_data[abs_data_offset..], 24, new_last_reset);
-                }
+                write_u64(&mut session_data[abs_data_offset..], 8, new_spent);
+                write_u64(&mut session_data[abs_data_offset..], 24, new_last_reset);
             }
+            ActionType::SolSlidingLimit if sol_spent > 0 => {
+                sliding_window_record(
+                    &mut session_data[abs_data_offset..],
+                    current_slot,
+                    sol_spent,
+                );
+            },
             ActionType::TokenLimit => {
                 let mut mint = [0u8; 32];
                 mint.copy_from_slice(&session_data[abs_data_offset..abs_data_offset + 32]);
@@ -573,6 +597,25 @@ pub fn evaluate_post_actions(
                     write_u64(&mut session_data[abs_data_offset..], 56, new_last_reset);
                 }
             }
+            ActionType::TokenSlidingLimit => {
+                let mut mint = [0u8; 32];
+                mint.copy_from_slice(&session_data[abs_data_offset..abs_data_offset + 32]);
+                let before = token_snapshots_before
+                    .iter()
+                    .find(|s| s.mint == mint)
+                    .map(|s| s.amount)
+                    .unwrap_or(0);
+                let after = find_token_balance(accounts, vault_key, &mint).unwrap_or(0);
+                let token_spent = before.saturating_sub(after);
+
+                if token_spent > 0 {
+                    sliding_window_record(
+                        &mut session_data[abs_data_offset + 32..],
+                        current_slot,
+                        token_spent,
+                    );
+                }
+            }
             _ => {} // SolMaxPerTx, TokenMaxPerTx, whitelist/blacklist have no mutable state
         }
     }
@@ -580,6 +623,25 @@ pub fn evaluate_post_actions(
     Ok(())
 }
 
+/// Sum of per-mint net token outflows since `token_snapshots_before`.
+///
+/// Mints whose vault balance grew (or whose accounts were not passed post-CPI)
+/// contribute 0. Used for the session `total_token_out` usage statistic only —
+/// limit enforcement happens in `evaluate_post_actions`.
+pub fn compute_token_outflow(
+    accounts: &[AccountInfo],
+    vault_key: &Pubkey,
+    token_snapshots_before: &[TokenSnapshot],
+) -> u64 {
+    let mut total: u64 = 0;
+    for snapshot in token_snapshots_before {
+        let after =
+            find_token_balance(accounts, vault_key, &snapshot.mint).unwrap_or(snapshot.amount);
+        total = total.saturating_add(snapshot.amount.saturating_sub(after));
+    }
+    total
+}
+
 // ─── Helpers ──────────────────────────────────────────────────────────
 
 /// Check if an action has expired.
@@ -610,7 +672,7 @@ const SPL_TOKEN_2022_PROGRAM_ID: [u8; 32] = [
 ///
 /// Verifies each account is owned by SPL Token or Token-2022 to prevent fake
 /// accounts with fabricated mint/owner fields.
-fn find_token_balance(
+pub fn find_token_balance(
     accounts: &[AccountInfo],
     vault_key: &Pubkey,
     mint: &[u8; 32],
@@ -621,8 +683,7 @@ fn find_token_balance(
     for acc in accounts {
         // CRITICAL: Verify account is owned by SPL Token or Token-2022 program.
         let owner = acc.owner();
-        if owner.as_ref() != &SPL_TOKEN_PROGRAM_ID && owner.as_ref() != &SPL_TOKEN_2022_PROGRAM_ID
-        {
+        if *owner != SPL_TOKEN_PROGRAM_ID && *owner != SPL_TOKEN_2022_PROGRAM_ID {
             continue;
         }
 
@@ -654,6 +715,7 @@ fn find_token_balance(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::session::SESSION_HEADER_SIZE;
     use crate::state::action::ACTION_HEADER_SIZE;
 
     fn build_action(action_type: u8, expires_at: u64, data: &[u8]) -> Vec<u8> {
@@ -846,6 +908,110 @@ mod tests {
         }
     }
 
+    // ─── SolSlidingLimit ──────────────────────────────────────────
+
+    fn build_sol_sliding(limit: u64, window: u64) -> Vec<u8> {
+        let mut data = vec![0u8; crate::state::action::SOL_SLIDING_LIMIT_SIZE];
+        data[0..8].copy_from_slice(&limit.to_le_bytes());
+        data[8..16].copy_from_slice(&window.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_sol_sliding_limit_blocks_boundary_double_spend() {
+        // limit 1M per 800 slots (8 buckets of 100)
+        let actions = build_action(7, 0, &build_sol_sliding(1_000_000, 800));
+        let mut session_data = build_session_data(&actions);
+
+        // Full limit at the end of an aligned window
+        eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            2_000_000,
+            1_000_000,
+            &[],
+            799,
+        )
+        .unwrap();
+
+        // A recurring limit would reset at 800; the sliding window does not.
+        let result = eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            1_000_000,
+            999_999,
+            &[],
+            801,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            ProgramError::Custom(AuthError::ActionSolRecurringLimitExceeded as u32)
+        );
+
+        // A full window after the spend, the budget is back.
+        let result = eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            1_000_000,
+            0,
+            &[],
+            799 + 900,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_sol_sliding_limit_partial_expiry() {
+        let actions = build_action(7, 0, &build_sol_sliding(1_000, 800));
+        let mut session_data = build_session_data(&actions);
+
+        eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            10_000,
+            9_400,
+            &[],
+            1_000,
+        )
+        .unwrap(); // 600
+        eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            9_400,
+            9_000,
+            &[],
+            1_500,
+        )
+        .unwrap(); // 400
+
+        // At 1_950 the first spend (bucket 10) is out of range, the second (bucket 15) is not.
+        assert!(eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            9_000,
+            8_300,
+            &[],
+            1_950
+        )
+        .is_err());
+        assert!(eval_post(
+            &mut session_data,
+            &[],
+            &Pubkey::default(),
+            9_000,
+            8_400,
+            &[],
+            1_950
+        )
+        .is_ok());
+    }
+
     // ─── SolRecurringLimit ────────────────────────────────────────
 
     #[test]
@@ -1606,6 +1772,24 @@ mod tests {
         assert_eq!(read_u64(&session_data[abs_offset..], 0), 0);
     }
 
+    #[test]
+    fn test_token_outflow_no_snapshots_is_zero() {
+        assert_eq!(compute_token_outflow(&[], &Pubkey::default(), &[]), 0);
+    }
+
+    #[test]
+    fn test_token_outflow_missing_accounts_counts_nothing() {
+        // Snapshot exists but no token accounts are visible post-CPI: treat as unchanged.
+        let snapshots = [TokenSnapshot {
+            mint: [1u8; 32],
+            amount: 500,
+        }];
+        assert_eq!(
+            compute_token_outflow(&[], &Pubkey::default(), &snapshots),
+            0
+        );
+    }
+
     #[test]
     fn test_no_token_snapshot_means_no_change() {
         // If a token mint has a limit but no before-snapshot, token_spent = 0
@@ -1630,15 +1814,78 @@ mod tests {
         let mut session_data = vec![0u8; SESSION_HEADER_SIZE];
         session_data[0] = 3;
 
-        let result = evaluate_pre_actions(&session_data, &[], &[], 100);
+        let result = evaluate_pre_actions(
+            &session_data,
+            &[],
+            &AccountResolver::new(&[], [0, 1, 3]),
+            100,
+        );
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_pre_actions_payer_whitelist_requires_payer() {
+        // A pinned session with no payer account available must fail closed.
+        let actions = build_action(20, 0, &[9u8; 32]);
+        let session_data = build_session_data(&actions);
+
+        let result = evaluate_pre_actions(
+            &session_data,
+            &[],
+            &AccountResolver::new(&[], [0, 1, 3]),
+            100,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            ProgramError::Custom(AuthError::ActionPayerNotAllowed as u32)
+        );
+    }
+
+    #[test]
+    fn test_pre_actions_expired_payer_whitelist_still_pinned() {
+        // Expired entries are dropped but the session stays pinned (deny all).
+        let actions = build_action(20, 50, &[9u8; 32]);
+        let session_data = build_session_data(&actions);
+
+        let result = evaluate_pre_actions(
+            &session_data,
+            &[],
+            &AccountResolver::new(&[], [0, 1, 3]),
+            100,
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            ProgramError::Custom(AuthError::ActionPayerNotAllowed as u32)
+        );
+    }
+
     // ── Session creation: actions_len cap ────────────────────────────
     // (tested in session/create.rs but we verify the constant here)
 
     #[test]
-    fn test_max_actions_constant_is_16() {
-        assert_eq!(crate::state::action::MAX_ACTIONS, 16);
+    fn test_max_actions_constant_is_64() {
+        assert_eq!(crate::state::action::MAX_ACTIONS, 64);
+    }
+
+    #[test]
+    fn test_pre_actions_large_sorted_whitelist() {
+        // 40 whitelisted programs — well past the old 16-action ceiling.
+        let mut actions = Vec::new();
+        for i in 0..40u8 {
+            actions.extend_from_slice(&build_action(10, 0, &[i; 32]));
+        }
+        let session_data = build_session_data(&actions);
+        crate::state::action::validate_actions_buffer(&actions).unwrap();
+
+        let parsed = parse_actions(&actions).unwrap();
+        assert!(parsed.has_active_key(&actions, ActionType::ProgramWhitelist, &[27u8; 32], 100));
+        assert!(!parsed.has_active_key(&actions, ActionType::ProgramWhitelist, &[40u8; 32], 100));
+        assert!(evaluate_pre_actions(
+            &session_data,
+            &[],
+            &AccountResolver::new(&[], [0, 1, 3]),
+            100
+        )
+        .is_ok());
     }
 }
@@ -1,7 +1,29 @@
 use crate::{
-    compact::{parse_compact_instructions_ref_with_len, CompactInstructionRef},
+    compact::{
+        is_compact_v2, parse_compact_instructions_ref_with_len, AccountResolver,
+        CompactInstructionRef,
+    },
     error::AuthError,
-    state::{deferred::DeferredExecAccount, AccountDiscriminator},
+    processor::{
+        deferred_buffer::{buffer_payload, close_buffer},
+        freeze::check_not_frozen,
+        proposal::verify_proposal_approvals,
+        session_enforcement::SessionEnforcement,
+        step_up::{deferred_stepped_up, StepUpGuard},
+        timelock::TimelockGuard,
+    },
+    state::{
+        authority::{authority_expired, AuthorityAccountHeader},
+        deferred::{
+            timelock_not_before, DeferredExecAccount, DEFERRED_AUTHORITY_KIND_AUTHORITY,
+            DEFERRED_AUTHORITY_KIND_PROPOSAL, DEFERRED_AUTHORITY_KIND_SESSION,
+            DEFERRED_AUTHORITY_KIND_TIMELOCK, DEFERRED_EXEC_HEADER_SIZE,
+        },
+        predicate::parse_predicates,
+        wallet_config::load_execution_policy,
+        AccountDiscriminator,
+    },
+    utils::get_stack_height,
 };
 use pinocchio::{
     account_info::AccountInfo,
@@ -16,18 +38,42 @@ use pinocchio::{
 /// Process the ExecuteDeferred instruction (deferred execution tx2).
 ///
 /// Verifies the compact instructions against the stored hash, executes them
-/// via CPI with vault PDA signing, then closes the DeferredExec account.
+/// via CPI with vault PDA signing, then closes the DeferredExec account. A
+/// multi-use authorization is only closed by its last allowed run; earlier
+/// runs record the run and must be `min_interval_slots` apart. A conditional
+/// authorization's predicates must all hold before any CPI.
+/// A proposal runs once, after enough of its approvers have approved; a
+/// queued execution once its timelock has elapsed. On a timelocked wallet,
+/// every other DeferredExec is subject to the same timelock checks as `Execute`.
+/// On a wallet with a step-up authority, outflows above the step-up thresholds
+/// need a DeferredExec authorized, queued or approved by that authority.
+/// An Authority-authorized DeferredExec only runs while its authorizer is
+/// still an unexpired authority of the wallet.
+/// A session-authorized DeferredExec is enforced against the session's
+/// actions (pre-checks, snapshots, vault invariants, spending limits) and
+/// updates its usage statistics.
 ///
 /// # Accounts:
 /// 1. `[signer, writable]` Payer
 /// 2. `[]` Wallet PDA
 /// 3. `[writable]` Vault PDA (signer for CPI)
-/// 4. `[writable]` DeferredExec PDA (read + closed)
+/// 4. `[writable]` DeferredExec PDA (read + closed, or run recorded)
 /// 5. `[writable]` Refund destination (receives rent refund)
-/// 6. `...` Inner accounts referenced by compact instructions
+/// 6. `[writable, optional]` Deferred Buffer PDA (only when instruction data is empty)
+/// 7. `...` Inner accounts referenced by compact instructions. An Authority-authorized
+///    DeferredExec must also pass its authorizer's Authority PDA here, and a
+///    session-authorized one its `[writable]` Session PDA (plus the creator
+///    Authority / Policy Template PDAs a session `Execute` would need). A conditional
+///    DeferredExec must pass every account its predicates read. A proposal must
+///    pass the Authority PDAs of at least `threshold` of its approvers. A timelocked
+///    or step-up wallet must pass its Wallet Config PDA.
 ///
 /// # Instruction Data (after discriminator):
 ///   [compact_instructions(variable)]
+///
+/// Empty instruction data executes from the Deferred Buffer instead: its
+/// whole payload must be the compact instructions, and it is closed (rent to
+/// the refund destination) after the CPIs.
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -65,6 +111,7 @@ pub fn process(
     if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
         return Err(ProgramError::InvalidAccountData);
     }
+    check_not_frozen(wallet_data)?;
 
     // Read DeferredExec account (read-only borrow for validation)
     {
@@ -74,7 +121,7 @@ pub fn process(
         }
     }
 
-    let deferred = unsafe {
+    let mut deferred = unsafe {
         let data = deferred_pda.borrow_data_unchecked();
         std::ptr::read_unaligned(data.as_ptr() as *const DeferredExecAccount)
     };
@@ -99,24 +146,67 @@ pub fn process(
         return Err(AuthError::DeferredAuthorizationExpired.into());
     }
 
+    // Empty instruction data: the compact instructions were written to the
+    // Deferred Buffer (accounts[5]) after Authorize.
+    let buffer_pda = if instruction_data.is_empty() {
+        let buffer_pda = accounts.get(5).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !buffer_pda.is_writable() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Some(buffer_pda)
+    } else {
+        None
+    };
+    let payload = match buffer_pda {
+        Some(buffer_pda) => buffer_payload(buffer_pda, deferred_pda.key(), program_id)?,
+        None => instruction_data,
+    };
+
     // Parse compact instructions and track consumed length. We hash the
-    // raw instruction_data[..consumed] directly — the parse/encode format
+    // raw payload[..consumed] directly — the parse/encode format
     // is byte-identical, so there's no need to re-serialize.
-    let (compact_instructions, compact_len) =
-        parse_compact_instructions_ref_with_len(instruction_data)?;
+    let (compact_instructions, compact_len) = parse_compact_instructions_ref_with_len(payload)?;
+    if buffer_pda.is_some() && compact_len != payload.len() {
+        return Err(AuthError::InvalidDeferredBuffer.into());
+    }
+    // v2 streams may use the reserved indexes: payer, wallet, vault. v1 index
+    // bytes are always account positions.
+    let resolver = if is_compact_v2(payload) {
+        AccountResolver::new(accounts, [0, 1, 2])
+    } else {
+        AccountResolver::positional(accounts)
+    };
 
     // Verify instructions hash against the exact bytes we parsed from
-    let instructions_hash = compute_sha256(&instruction_data[..compact_len]);
+    let instructions_hash = compute_sha256(&payload[..compact_len]);
     if instructions_hash != deferred.instructions_hash {
         return Err(AuthError::DeferredHashMismatch.into());
     }
 
     // Verify accounts hash
-    let accounts_hash = compute_accounts_hash(accounts, &compact_instructions)?;
+    let accounts_hash = compute_accounts_hash(&resolver, &compact_instructions)?;
     if accounts_hash != deferred.accounts_hash {
         return Err(AuthError::DeferredHashMismatch.into());
     }
 
+    // Conditional authorization: every predicate must hold before any CPI.
+    // The predicate accounts are passed among the remaining accounts.
+    // Proposals and queued executions store other data there instead.
+    let trailer = unsafe { &deferred_pda.borrow_data_unchecked()[DEFERRED_EXEC_HEADER_SIZE..] };
+    let predicates = match deferred.authority_kind {
+        DEFERRED_AUTHORITY_KIND_PROPOSAL | DEFERRED_AUTHORITY_KIND_TIMELOCK => &[],
+        _ => trailer,
+    };
+    for predicate in parse_predicates(predicates)? {
+        let account = accounts
+            .iter()
+            .find(|acc| acc.key().as_ref() == predicate.account)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !predicate.holds(unsafe { account.borrow_data_unchecked() }) {
+            return Err(AuthError::DeferredConditionNotMet.into());
+        }
+    }
+
     // Derive vault PDA and verify
     let (vault_key, vault_bump) =
         find_program_address(&[b"vault", wallet_pda.key().as_ref()], program_id);
@@ -125,20 +215,134 @@ pub fn process(
         return Err(ProgramError::InvalidSeeds);
     }
 
-    // Close the DeferredExec account BEFORE CPI execution.
-    // All validation is complete — hashes verified, expiry checked.
-    // Closing before CPI avoids stale-pointer issues with invoke_signed_unchecked.
-    // If any CPI fails, the entire transaction reverts atomically.
-    let deferred_lamports = unsafe { *deferred_pda.borrow_mut_lamports_unchecked() };
-    let refund_lamports = unsafe { *refund_dest.borrow_mut_lamports_unchecked() };
-    unsafe {
-        *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
-            .checked_add(deferred_lamports)
-            .ok_or(ProgramError::ArithmeticOverflow)?;
-        *deferred_pda.borrow_mut_lamports_unchecked() = 0;
+    // A session-authorized execution runs under the session's actions, exactly
+    // like a session `Execute`. The Session PDA is passed among the inner accounts.
+    let session_pda = match deferred.authority_kind {
+        DEFERRED_AUTHORITY_KIND_AUTHORITY => {
+            // The authorizer must not have been removed or expired since
+            let authority_pda = accounts
+                .iter()
+                .find(|acc| *acc.key() == deferred.authority)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if authority_pda.owner() != program_id {
+                return Err(ProgramError::IllegalOwner);
+            }
+            let authority_data = unsafe { authority_pda.borrow_data_unchecked() };
+            if authority_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let header = unsafe {
+                std::ptr::read_unaligned(authority_data.as_ptr() as *const AuthorityAccountHeader)
+            };
+            if header.discriminator != AccountDiscriminator::Authority as u8
+                || header.wallet != *wallet_pda.key()
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if authority_expired(authority_data, clock.slot)? {
+                return Err(AuthError::AuthorityExpired.into());
+            }
+            None
+        },
+        DEFERRED_AUTHORITY_KIND_PROPOSAL => {
+            verify_proposal_approvals(trailer, wallet_pda.key(), accounts, program_id)?;
+            None
+        },
+        DEFERRED_AUTHORITY_KIND_TIMELOCK => {
+            if clock.slot < timelock_not_before(trailer)? {
+                return Err(AuthError::TimelockNotElapsed.into());
+            }
+            None
+        },
+        DEFERRED_AUTHORITY_KIND_SESSION => {
+            // Same anti-CPI guard as a session-authenticated Execute
+            if get_stack_height() > 1 {
+                return Err(AuthError::PermissionDenied.into());
+            }
+            let session_pda = accounts
+                .iter()
+                .find(|acc| *acc.key() == deferred.authority)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if session_pda.owner() != program_id {
+                return Err(ProgramError::IllegalOwner);
+            }
+            if !session_pda.is_writable() {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            Some(session_pda)
+        },
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+    let mut enforcement = match session_pda {
+        Some(session_pda) => Some(SessionEnforcement::begin(
+            unsafe { session_pda.borrow_data_unchecked() },
+            wallet_pda.key(),
+            &compact_instructions,
+            &resolver,
+            vault_pda,
+            program_id,
+        )?),
+        None => None,
+    };
+
+    // A queued execution already waited out the timelock; everything else
+    // on a timelocked wallet is held to it like a direct `Execute`.
+    let policy = load_execution_policy(wallet_data, wallet_pda.key(), accounts, program_id)?;
+    let (timelock, mut step_up) = match &policy {
+        Some(config) => (
+            if deferred.authority_kind == DEFERRED_AUTHORITY_KIND_TIMELOCK {
+                None
+            } else {
+                TimelockGuard::begin(
+                    config,
+                    &compact_instructions,
+                    &resolver,
+                    accounts,
+                    vault_pda,
+                )?
+            },
+            StepUpGuard::begin(
+                config,
+                deferred_stepped_up(config, &deferred, trailer)?,
+                accounts,
+                vault_pda,
+            ),
+        ),
+        None => (None, None),
+    };
+
+    // Count this run. A multi-use authorization must respect its interval;
+    // the last allowed run closes the account.
+    let is_last_run = deferred.record_execution(clock.slot)?;
+
+    if is_last_run {
+        // Close the DeferredExec account BEFORE CPI execution.
+        // All validation is complete — hashes verified, expiry checked.
+        // Closing before CPI avoids stale-pointer issues with invoke_signed_unchecked.
+        // If any CPI fails, the entire transaction reverts atomically.
+        let deferred_lamports = unsafe { *deferred_pda.borrow_mut_lamports_unchecked() };
+        let refund_lamports = unsafe { *refund_dest.borrow_mut_lamports_unchecked() };
+        unsafe {
+            *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
+                .checked_add(deferred_lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            *deferred_pda.borrow_mut_lamports_unchecked() = 0;
+        }
+        let close_data = unsafe { deferred_pda.borrow_mut_data_unchecked() };
+        close_data.fill(0);
+    } else {
+        // Persist the run count, also before CPI
+        if !deferred_pda.is_writable() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let deferred_data = unsafe { deferred_pda.borrow_mut_data_unchecked() };
+        unsafe {
+            std::ptr::write_unaligned(
+                deferred_data.as_mut_ptr() as *mut DeferredExecAccount,
+                deferred,
+            );
+        }
     }
-    let close_data = unsafe { deferred_pda.borrow_mut_data_unchecked() };
-    close_data.fill(0);
 
     // Reuse Vecs across inner CPI iterations — allocated once, cleared +
     // repushed each iteration. Same optimisation as execute::immediate.
@@ -155,7 +359,7 @@ pub fn process(
 
     // Execute each compact instruction via CPI with vault PDA signing
     for compact_ix in &compact_instructions {
-        let decompressed = compact_ix.decompress(accounts)?;
+        let decompressed = compact_ix.decompress(&resolver)?;
 
         // Prevent self-reentrancy
         if decompressed.program_id.as_ref() == program_id.as_ref() {
@@ -176,7 +380,7 @@ pub fn process(
         let ix = Instruction {
             program_id: decompressed.program_id,
             accounts: &account_metas,
-            data: decompressed.data,
+            data: &decompressed.data,
         };
 
         let signer: Signer = (&seeds).into();
@@ -184,6 +388,38 @@ pub fn process(
         unsafe {
             invoke_signed_unchecked(&ix, &cpi_accounts, &[signer]);
         }
+
+        if let Some(enforcement) = enforcement.as_mut() {
+            enforcement.record_cpi(vault_pda);
+        }
+        if let Some(step_up) = step_up.as_mut() {
+            step_up.record_cpi(vault_pda);
+        }
+    }
+
+    if let Some(timelock) = timelock {
+        timelock.finish(accounts, vault_pda)?;
+    }
+    if let Some(step_up) = step_up {
+        step_up.finish(accounts, vault_pda)?;
+    }
+
+    // Post-CPI session enforcement and usage statistics
+    if let (Some(enforcement), Some(session_pda)) = (enforcement, session_pda) {
+        enforcement.finish(
+            unsafe { session_pda.borrow_mut_data_unchecked() },
+            accounts,
+            vault_pda,
+        )?;
+    }
+
+    // The compact instructions borrow the buffer, so it is closed only after
+    // the CPIs. Its rent goes to the same payer as the DeferredExec rent.
+    // A multi-use authorization keeps its buffer until the last run.
+    if let Some(buffer_pda) = buffer_pda {
+        if is_last_run {
+            close_buffer(buffer_pda, refund_dest)?;
+        }
     }
 
     Ok(())
@@ -212,24 +448,16 @@ fn compute_sha256(data: &[u8]) -> [u8; 32] {
 /// Compute SHA256 hash of all account pubkeys referenced by compact instructions.
 /// Matches execute::immediate::compute_accounts_hash.
 fn compute_accounts_hash(
-    accounts: &[AccountInfo],
+    resolver: &AccountResolver<'_>,
     compact_instructions: &[CompactInstructionRef<'_>],
 ) -> Result<[u8; 32], ProgramError> {
     let mut refs: Vec<&[u8]> = Vec::with_capacity(compact_instructions.len() * 4);
 
     for ix in compact_instructions {
-        let program_idx = ix.program_id_index as usize;
-        if program_idx >= accounts.len() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-        refs.push(accounts[program_idx].key().as_ref());
+        refs.push(resolver.resolve(ix.program_id_index)?.key().as_ref());
 
         for &acc_idx in ix.accounts {
-            let idx = acc_idx as usize;
-            if idx >= accounts.len() {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            refs.push(accounts[idx].key().as_ref());
+            refs.push(resolver.resolve(acc_idx)?.key().as_ref());
         }
     }
 
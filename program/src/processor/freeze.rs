@@ -0,0 +1,269 @@
+//! Emergency wallet freeze.
+//!
+//! `Freeze` sets `WALLET_FLAG_FROZEN` on the Wallet PDA. Any Owner can call
+//! it, as can the guardian key of the wallet config, so a suspected
+//! compromise is contained in one transaction instead of removing authorities
+//! one by one. While frozen, `Execute`, `ExecuteDeferred` and `CreateSession`
+//! fail with `WalletFrozen`, and `SetWalletConfig` can only clear or replace
+//! the guardian; authority management keeps working so the Owners can evict
+//! a compromised key.
+//!
+//! Only an Owner can `Unfreeze`. With an `unfreeze_delay_slots` in the config
+//! this takes two calls: the first requests the unfreeze, the second completes
+//! it once the delay has passed. An Owner `Freeze` in between cancels the
+//! request, which covers a compromised Owner key. The guardian cannot freeze
+//! a frozen wallet, so it cannot hold the wallet frozen by cancelling every
+//! request.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    auth::admin::authenticate_admin,
+    error::AuthError,
+    state::{
+        authority::AuthorityAccountHeader,
+        wallet::{wallet_flags, WalletAccount, WALLET_FLAG_FROZEN, WALLET_FLAG_LIMITS},
+        wallet_config::{WalletConfigAccount, WalletLimits},
+        AccountDiscriminator,
+    },
+};
+
+/// Rejects operations on a frozen wallet.
+pub fn check_not_frozen(wallet_data: &[u8]) -> ProgramResult {
+    if wallet_flags(wallet_data) & WALLET_FLAG_FROZEN != 0 {
+        return Err(AuthError::WalletFrozen.into());
+    }
+    Ok(())
+}
+
+/// Process the Freeze instruction.
+///
+/// Freezes the wallet. The caller is either an Owner Authority PDA (signature
+/// over `freeze_payload`, `[28]` discriminator) or the wallet's guardian
+/// signing the transaction. An Owner freezing a frozen wallet cancels its
+/// pending unfreeze; the guardian gets `WalletFrozen`.
+///
+/// # Accounts:
+/// 1. `[signer]` Payer
+/// 2. `[writable]` Wallet PDA
+/// 3. `[writable]` Owner Authority PDA (counter incremented for Secp256r1),
+///    or `[signer]` Guardian
+/// 4. `[writable]` Wallets with `WALLET_FLAG_LIMITS`: Wallet Config PDA
+/// 5. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [auth_payload(variable)] (Owner with Secp256r1) or empty
+pub fn process_freeze(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let caller = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+    let by_owner = caller.owner() == program_id;
+    if by_owner {
+        check_owner(caller)?;
+        authenticate_admin(
+            program_id,
+            accounts,
+            wallet_pda,
+            caller,
+            &limits,
+            instruction_data,
+            &freeze_payload(accounts, wallet_pda, &limits)?,
+            28,
+        )?;
+    } else {
+        check_wallet(program_id, wallet_pda)?;
+        let guardian = limits.get()?.guardian;
+        if guardian == [0; 32] || *caller.key() != guardian || !caller.is_signer() {
+            return Err(AuthError::PermissionDenied.into());
+        }
+    }
+
+    if !wallet_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let unfreeze_at = limits.get()?.unfreeze_at;
+    let wallet_data = unsafe { wallet_pda.borrow_mut_data_unchecked() };
+    if apply_freeze(wallet_data, by_owner, unfreeze_at)? {
+        write_unfreeze_at(limits.config_pda()?, 0);
+    }
+
+    Ok(())
+}
+
+/// Sets `WALLET_FLAG_FROZEN` and returns whether the pending unfreeze
+/// (`unfreeze_at`) must be cancelled. Only an Owner cancels one; the
+/// guardian cannot freeze a frozen wallet (`WalletFrozen`).
+fn apply_freeze(
+    wallet_data: &mut [u8],
+    by_owner: bool,
+    unfreeze_at: u64,
+) -> Result<bool, ProgramError> {
+    if !by_owner {
+        check_not_frozen(wallet_data)?;
+    }
+    wallet_data[std::mem::offset_of!(WalletAccount, flags)] |= WALLET_FLAG_FROZEN;
+    Ok(by_owner && unfreeze_at != 0)
+}
+
+/// The payload an Owner signs for Freeze and Unfreeze: the wallet, the payer
+/// and, for a wallet with `WALLET_FLAG_LIMITS`, its Wallet Config PDA, so a
+/// relayer cannot reuse the signature with other accounts.
+fn freeze_payload(
+    accounts: &[AccountInfo],
+    wallet_pda: &AccountInfo,
+    limits: &WalletLimits<'_>,
+) -> Result<Vec<u8>, ProgramError> {
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let mut payload = Vec::with_capacity(96);
+    payload.extend_from_slice(wallet_pda.key().as_ref());
+    payload.extend_from_slice(payer.key().as_ref());
+    if wallet_flags(unsafe { wallet_pda.borrow_data_unchecked() }) & WALLET_FLAG_LIMITS != 0 {
+        payload.extend_from_slice(limits.config_pda()?.key().as_ref());
+    }
+    Ok(payload)
+}
+
+/// Process the Unfreeze instruction.
+///
+/// An Owner (signature over `freeze_payload`, `[29]` discriminator)
+/// unfreezes the wallet. Without an unfreeze delay this is immediate.
+/// Otherwise the first call records `unfreeze_at = now + unfreeze_delay_slots`
+/// in the wallet config and the wallet stays frozen; a call from that slot on
+/// unfreezes it (`TimelockNotElapsed` before).
+///
+/// # Accounts:
+/// 1. `[signer]` Payer
+/// 2. `[writable]` Wallet PDA
+/// 3. `[writable]` Owner Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Wallets with `WALLET_FLAG_LIMITS`: Wallet Config PDA
+/// 5. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [auth_payload(variable)] (Secp256r1) or empty (Ed25519)
+pub fn process_unfreeze(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let owner_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+    check_owner(owner_auth_pda)?;
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        owner_auth_pda,
+        &limits,
+        instruction_data,
+        &freeze_payload(accounts, wallet_pda, &limits)?,
+        29,
+    )?;
+
+    if !wallet_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let wallet_data = unsafe { wallet_pda.borrow_mut_data_unchecked() };
+    if wallet_flags(wallet_data) & WALLET_FLAG_FROZEN == 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let config = limits.get()?;
+    if config.unfreeze_delay_slots > 0 {
+        let config_pda = limits.config_pda()?;
+        let current_slot = Clock::get()?.slot;
+        if config.unfreeze_at == 0 {
+            // Request: the wallet stays frozen until the delay has passed
+            let unfreeze_at = current_slot
+                .checked_add(config.unfreeze_delay_slots)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            write_unfreeze_at(config_pda, unfreeze_at);
+            return Ok(());
+        }
+        if current_slot < config.unfreeze_at {
+            return Err(AuthError::TimelockNotElapsed.into());
+        }
+        write_unfreeze_at(config_pda, 0);
+    }
+    wallet_data[std::mem::offset_of!(WalletAccount, flags)] &= !WALLET_FLAG_FROZEN;
+
+    Ok(())
+}
+
+/// Only Owners (role 0) can freeze or unfreeze through an Authority PDA.
+fn check_owner(auth_pda: &AccountInfo) -> ProgramResult {
+    let auth_data = unsafe { auth_pda.borrow_data_unchecked() };
+    if auth_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let header =
+        unsafe { std::ptr::read_unaligned(auth_data.as_ptr() as *const AuthorityAccountHeader) };
+    if header.role != 0 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+    Ok(())
+}
+
+fn check_wallet(program_id: &Pubkey, wallet_pda: &AccountInfo) -> ProgramResult {
+    if wallet_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+fn write_unfreeze_at(config_pda: &AccountInfo, unfreeze_at: u64) {
+    let offset = std::mem::offset_of!(WalletConfigAccount, unfreeze_at);
+    let data = unsafe { config_pda.borrow_mut_data_unchecked() };
+    data[offset..offset + 8].copy_from_slice(&unfreeze_at.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_not_frozen() {
+        let mut wallet_data = [0u8; 8];
+        wallet_data[0] = AccountDiscriminator::Wallet as u8;
+        assert!(check_not_frozen(&wallet_data).is_ok());
+        wallet_data[std::mem::offset_of!(WalletAccount, flags)] = WALLET_FLAG_FROZEN;
+        assert!(check_not_frozen(&wallet_data).is_err());
+    }
+
+    #[test]
+    fn test_guardian_cannot_cancel_pending_unfreeze() {
+        let mut wallet_data = [0u8; 8];
+        wallet_data[0] = AccountDiscriminator::Wallet as u8;
+
+        // The guardian freezes an unfrozen wallet
+        assert_eq!(apply_freeze(&mut wallet_data, false, 0), Ok(false));
+        assert!(check_not_frozen(&wallet_data).is_err());
+
+        // Once an Owner requested the unfreeze, the guardian cannot re-freeze
+        // to reset it
+        assert_eq!(
+            apply_freeze(&mut wallet_data, false, 1_000),
+            Err(AuthError::WalletFrozen.into())
+        );
+
+        // An Owner can still cancel the request
+        assert_eq!(apply_freeze(&mut wallet_data, true, 1_000), Ok(true));
+        assert_eq!(apply_freeze(&mut wallet_data, true, 0), Ok(false));
+    }
+}
@@ -5,30 +5,47 @@ use pinocchio::{
     instruction::Seed,
     program_error::ProgramError,
     pubkey::{find_program_address, Pubkey},
-    sysvars::rent::Rent,
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult,
 };
 
 use crate::{
     auth::{
-        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+        ed25519::Ed25519Authenticator,
+        possession::{prove_possession, REGISTER_FLAG_PROVE_POSSESSION},
+        secp256r1::Secp256r1Authenticator,
+        traits::Authenticator,
     },
     error::AuthError,
-    state::{authority::AuthorityAccountHeader, AccountDiscriminator},
+    state::{
+        authority::{
+            authority_data_len, read_authority_expiry, AuthorityAccountHeader, AuthorityExpiry,
+            AUTHORITY_EXPIRY_SIZE, AUTHORITY_FLAG_EXPIRES,
+        },
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+    utils::split_trailing_payload,
 };
 
+/// `AddAuthorityArgs::flags`: the new authority expires. Its `expires_at(8)`
+/// slot follows the authority data.
+pub const REGISTER_FLAG_EXPIRES: u8 = 1 << 1;
+
 /// Arguments for the `AddAuthority` instruction.
 ///
 /// Layout:
 /// - `authority_type`: 0 for Ed25519, 1 for Secp256r1.
 /// - `new_role`: Role to assign (0=Owner, 1=Admin, 2=Spender).
+/// - `flags`: Registration flags (`REGISTER_FLAG_PROVE_POSSESSION`, `REGISTER_FLAG_EXPIRES`).
 /// - `_padding`: Reserved to align to 8-byte boundary.
 #[repr(C, align(8))]
 #[derive(NoPadding)]
 pub struct AddAuthorityArgs {
     pub authority_type: u8,
     pub new_role: u8,
-    pub _padding: [u8; 6],
+    pub flags: u8,
+    pub _padding: [u8; 5],
 }
 
 impl AddAuthorityArgs {
@@ -41,11 +58,16 @@ impl AddAuthorityArgs {
         // Manual deserialization for safety
         let authority_type = fixed[0];
         let new_role = fixed[1];
+        let flags = fixed[2];
+        if flags & !(REGISTER_FLAG_PROVE_POSSESSION | REGISTER_FLAG_EXPIRES) != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
 
         let args = Self {
             authority_type,
             new_role,
-            _padding: [0; 6],
+            flags,
+            _padding: [0; 5],
         };
 
         Ok((args, rest))
@@ -60,8 +82,17 @@ impl AddAuthorityArgs {
 /// 1. **Authentication**: Verifies the `admin_authority` (must be Admin or Owner).
 /// 2. **Authorization**: Checks permission levels:
 ///    - `Owner` (0) can add any role.
-///    - `Admin` (1) can only add `Spender` (2).
+///    - `Admin` (1) can only add `Spender` (2), unless the wallet config
+///      forbids it (`WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS` cleared).
 /// 3. **Execution**: Creates a new PDA `["authority", wallet, id_hash]` and initializes it.
+///    With `REGISTER_FLAG_EXPIRES` (Admins and Spenders only) it stores the
+///    signed `expires_at` slot and the payer as funder: after that slot the
+///    authority fails authentication and anyone can close it with
+///    `CloseExpiredAuthority`.
+/// 4. **Proof of possession** (if `REGISTER_FLAG_PROVE_POSSESSION`): the new
+///    key authenticates against the new PDA (challenge: its address) and the
+///    authority is marked verified. The auth payload is then
+///    `[admin_auth_payload][new_auth_payload][new_auth_payload_len(2)]`.
 ///
 /// # Accounts:
 /// 1. `[signer, writable]` Payer.
@@ -69,6 +100,9 @@ impl AddAuthorityArgs {
 /// 3. `[signer]` Admin Authority: Existing authority authorizing this action.
 /// 4. `[writable]` New Authority: The PDA to create.
 /// 5. `[]` System Program.
+/// 6. `[]` Rent Sysvar.
+/// 7. `[]` Auth extras (Ed25519: signer keypairs | Secp256r1: sysvar_instructions).
+/// 8. `[]` Wallets with `WALLET_FLAG_LIMITS`, Admin signer: Wallet Config PDA.
 pub fn process_add_authority(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -104,13 +138,28 @@ pub fn process_add_authority(
         _ => return Err(AuthError::InvalidAuthenticationKind.into()),
     };
 
+    let expires_at = if args.flags & REGISTER_FLAG_EXPIRES != 0 {
+        let bytes = rest
+            .get(full_auth_data.len()..full_auth_data.len() + 8)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+    } else {
+        None
+    };
+
     // Split data_payload and authority_payload
-    // data_payload = everything up to and including the new authority data
-    let data_payload_len = 8 + full_auth_data.len(); // args + full_auth_data
+    // data_payload = everything up to and including the new authority data (and expiry)
+    let data_payload_len = 8 + full_auth_data.len() + if expires_at.is_some() { 8 } else { 0 };
     if instruction_data.len() < data_payload_len {
         return Err(ProgramError::InvalidInstructionData);
     }
     let (data_payload, authority_payload) = instruction_data.split_at(data_payload_len);
+    let prove = args.flags & REGISTER_FLAG_PROVE_POSSESSION != 0;
+    let (authority_payload, new_authority_payload) = if prove {
+        split_trailing_payload(authority_payload)?
+    } else {
+        (authority_payload, &[][..])
+    };
 
     let account_info_iter = &mut accounts.iter();
     let payer = account_info_iter
@@ -173,6 +222,7 @@ pub fn process_add_authority(
     ed25519_payload.extend_from_slice(payer.key().as_ref());
     ed25519_payload.extend_from_slice(new_auth_pda.key().as_ref());
 
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id);
     match admin_header.authority_type {
         0 => {
             // Ed25519: Include payer + new_auth_pda in signed payload
@@ -188,7 +238,7 @@ pub fn process_add_authority(
             extended_data_payload.extend_from_slice(data_payload);
             extended_data_payload.extend_from_slice(payer.key().as_ref());
 
-            Secp256r1Authenticator.authenticate(
+            Secp256r1Authenticator::new(&limits)?.authenticate(
                 accounts,
                 admin_data,
                 authority_payload,
@@ -210,6 +260,18 @@ pub fn process_add_authority(
     if admin_header.role != 0 && (admin_header.role != 1 || args.new_role != 2) {
         return Err(AuthError::PermissionDenied.into());
     }
+    if admin_header.role == 1 && !limits.get()?.admin_adds_spenders() {
+        return Err(AuthError::PermissionDenied.into());
+    }
+    if let Some(expires_at) = expires_at {
+        // Owners never expire, so a wallet cannot lose its last Owner to expiry
+        if args.new_role == 0 {
+            return Err(AuthError::PermissionDenied.into());
+        }
+        if expires_at <= Clock::get()?.slot {
+            return Err(AuthError::InvalidExpiryWindow.into());
+        }
+    }
 
     // Logic
     let (new_auth_key, bump) = find_program_address(
@@ -223,11 +285,11 @@ pub fn process_add_authority(
 
     // Fixed sizes per auth type (see wallet/create.rs for layout).
     let header_size = std::mem::size_of::<AuthorityAccountHeader>();
-    let space = match args.authority_type {
-        0 => header_size + 32,                // Ed25519: pubkey
-        1 => header_size + 32 + 33 + 32,      // Secp256r1: cred ∥ pubkey ∥ rpIdHash
-        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
-    };
+    let key_data_len = authority_data_len(args.authority_type)?;
+    let mut space = header_size + key_data_len; // Ed25519: pubkey | Secp256r1: cred ∥ pubkey ∥ rpIdHash
+    if expires_at.is_some() {
+        space += AUTHORITY_EXPIRY_SIZE;
+    }
     let rent_lamports = rent.minimum_balance(space);
 
     // Use secure transfer-allocate-assign pattern to prevent DoS (Issue #4)
@@ -256,14 +318,31 @@ pub fn process_add_authority(
         role: args.new_role,
         bump,
         version: crate::state::CURRENT_ACCOUNT_VERSION,
-        _padding1: [0; 3],
+        flags: if expires_at.is_some() {
+            AUTHORITY_FLAG_EXPIRES
+        } else {
+            0
+        },
+        _padding1: [0; 2],
         counter: 0,
-        _padding2: [0; 4],
+        last_used_slot: 0,
         wallet: *wallet_pda.key(),
     };
     unsafe {
         *(data.as_mut_ptr() as *mut AuthorityAccountHeader) = header;
     }
+    if let Some(expires_at) = expires_at {
+        let expiry = AuthorityExpiry {
+            expires_at,
+            funder: *payer.key(),
+        };
+        unsafe {
+            std::ptr::write_unaligned(
+                data[header_size + key_data_len..].as_mut_ptr() as *mut AuthorityExpiry,
+                expiry,
+            );
+        }
+    }
 
     // Write variable data. For Secp256r1 hash rpId once here so every Execute
     // saves a sol_sha256 syscall.
@@ -295,6 +374,18 @@ pub fn process_add_authority(
         _ => unreachable!(),
     }
 
+    if prove {
+        prove_possession(
+            accounts,
+            &limits,
+            data,
+            new_authority_payload,
+            new_auth_pda.key().as_ref(),
+            &[1],
+            program_id,
+        )?;
+    }
+
     Ok(())
 }
 
@@ -304,9 +395,12 @@ pub fn process_add_authority(
 ///
 /// # Logic:
 /// 1. **Authentication**: Verifies the `admin_authority`.
-/// 2. **Authorization**:
-///    - `Owner` can remove anyone (except potentially the last owner, though not explicitly enforced here).
+/// 2. **Authorization** (see `check_remove_permission`):
+///    - `Owner` can remove anyone else, including another `Owner`.
 ///    - `Admin` can only remove `Spender`.
+///
+///    Self-removal is rejected, so an Owner is only ever removed by another
+///    Owner that remains: the last Owner of a wallet can never be removed.
 /// 3. **Execution**: Securely closes the account by zeroing data and transferring lamports.
 ///
 /// # Accounts:
@@ -388,7 +482,8 @@ pub fn process_remove_authority(
             Ed25519Authenticator.authenticate(accounts, admin_data, &[], &data_payload, &[2], program_id)?;
         },
         1 => {
-            Secp256r1Authenticator.authenticate(
+            let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+            Secp256r1Authenticator::new(&limits)?.authenticate(
                 accounts,
                 admin_data,
                 authority_payload,
@@ -424,19 +519,8 @@ pub fn process_remove_authority(
         return Err(AuthError::PermissionDenied.into());
     }
 
-    // Prevent removing an Owner — ownership must be transferred, not removed.
-    // This prevents accidentally locking the wallet by removing the last owner.
-    if target_header.role == 0 {
-        return Err(AuthError::PermissionDenied.into());
-    }
-
     // Role-based permission check
-    if admin_header.role != 0 {
-        // Admin can only remove Spender
-        if admin_header.role != 1 || target_header.role != 2 {
-            return Err(AuthError::PermissionDenied.into());
-        }
-    }
+    check_remove_permission(admin_header.role, target_header.role)?;
 
     // Guard: if target == refund_dest the double-write would burn lamports and
     // trigger a Solana lamport conservation error, aborting after doing work.
@@ -458,17 +542,100 @@ pub fn process_remove_authority(
     Ok(())
 }
 
+/// Processes the `CloseExpiredAuthority` instruction.
+///
+/// Permissionless cleanup of an authority registered with
+/// `REGISTER_FLAG_EXPIRES` once its `expires_at` slot has passed. The account
+/// is closed like `RemoveAuthority`, with rent going back to the funder that
+/// paid for it at registration.
+///
+/// # Accounts:
+/// 1. `[]` Wallet PDA.
+/// 2. `[writable]` Expired Authority: PDA to close.
+/// 3. `[writable]` Funder: must match the funder stored at registration.
+pub fn process_close_expired_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let wallet_pda = account_info_iter
+        .next()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let target_auth_pda = account_info_iter
+        .next()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let funder = account_info_iter
+        .next()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if wallet_pda.owner() != program_id || target_auth_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let target_data = unsafe { target_auth_pda.borrow_data_unchecked() };
+    if target_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let target_header =
+        unsafe { std::ptr::read_unaligned(target_data.as_ptr() as *const AuthorityAccountHeader) };
+    if target_header.discriminator != AccountDiscriminator::Authority as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if target_header.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only authorities registered with an expiry can be closed, and only after it
+    let expiry = read_authority_expiry(target_data)?.ok_or(AuthError::PermissionDenied)?;
+    if Clock::get()?.slot <= expiry.expires_at {
+        return Err(AuthError::PermissionDenied.into());
+    }
+    if *funder.key() != expiry.funder {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // Same lamport double-write guard as RemoveAuthority
+    if target_auth_pda.key() == funder.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let target_lamports = unsafe { *target_auth_pda.borrow_mut_lamports_unchecked() };
+    let funder_lamports = unsafe { *funder.borrow_mut_lamports_unchecked() };
+    unsafe {
+        *funder.borrow_mut_lamports_unchecked() = funder_lamports
+            .checked_add(target_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *target_auth_pda.borrow_mut_lamports_unchecked() = 0;
+    }
+    let target_data = unsafe { target_auth_pda.borrow_mut_data_unchecked() };
+    target_data.fill(0);
+
+    Ok(())
+}
+
+/// Whether an authority of `admin_role` may remove one of `target_role`
+/// (another authority). Owners may remove any role, including other Owners:
+/// since the remover is itself an Owner and self-removal is rejected, a
+/// wallet always keeps at least one Owner. Admins may only remove Spenders.
+fn check_remove_permission(admin_role: u8, target_role: u8) -> ProgramResult {
+    match (admin_role, target_role) {
+        (0, _) | (1, 2) => Ok(()),
+        _ => Err(AuthError::PermissionDenied.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_add_authority_args_from_bytes() {
-        // [type(1)][role(1)][padding(6)]
+        // [type(1)][role(1)][flags(1)][padding(5)]
         let mut data = Vec::new();
         data.push(0); // Ed25519
         data.push(2); // Spender
-        data.extend_from_slice(&[0; 6]); // padding
+        data.push(REGISTER_FLAG_PROVE_POSSESSION);
+        data.extend_from_slice(&[0; 5]); // padding
 
         let extra_data = [1u8; 32];
         data.extend_from_slice(&extra_data);
@@ -476,7 +643,19 @@ mod tests {
         let (args, rest) = AddAuthorityArgs::from_bytes(&data).unwrap();
         assert_eq!(args.authority_type, 0);
         assert_eq!(args.new_role, 2);
+        assert_eq!(args.flags, REGISTER_FLAG_PROVE_POSSESSION);
         assert_eq!(rest, &extra_data);
+
+        data[2] = REGISTER_FLAG_PROVE_POSSESSION | REGISTER_FLAG_EXPIRES;
+        let (args, _) = AddAuthorityArgs::from_bytes(&data).unwrap();
+        assert_eq!(
+            args.flags,
+            REGISTER_FLAG_PROVE_POSSESSION | REGISTER_FLAG_EXPIRES
+        );
+
+        // Unknown flags are rejected
+        data[2] = 0x80;
+        assert!(AddAuthorityArgs::from_bytes(&data).is_err());
     }
 
     #[test]
@@ -484,4 +663,20 @@ mod tests {
         let data = vec![0u8; 7]; // Need 8
         assert!(AddAuthorityArgs::from_bytes(&data).is_err());
     }
+
+    #[test]
+    fn test_check_remove_permission() {
+        // Owners remove any role, including another Owner
+        for target_role in 0..=2 {
+            assert!(check_remove_permission(0, target_role).is_ok());
+        }
+        // Admins only remove Spenders
+        assert!(check_remove_permission(1, 2).is_ok());
+        assert!(check_remove_permission(1, 1).is_err());
+        assert!(check_remove_permission(1, 0).is_err());
+        // Spenders remove nothing
+        for target_role in 0..=2 {
+            assert!(check_remove_permission(2, target_role).is_err());
+        }
+    }
 }
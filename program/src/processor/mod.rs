@@ -2,13 +2,24 @@
 //!
 //! Each module corresponds to a specific instruction in the IDL.
 
+pub mod append_session_actions;
 pub mod authorize;
+pub mod cancel_deferred;
 pub mod create_session;
 pub mod create_wallet;
+pub mod deferred_buffer;
 pub mod execute;
 pub mod execute_actions;
 pub mod execute_deferred;
+pub mod freeze;
 pub mod manage_authority;
+pub mod policy_template;
+pub mod proposal;
 pub mod reclaim_deferred;
 pub mod revoke_session;
+pub mod rotate_session;
+pub mod session_enforcement;
+pub mod step_up;
+pub mod timelock;
 pub mod transfer_ownership;
+pub mod wallet_config;
@@ -0,0 +1,320 @@
+use assertions::{check_zero_data, sol_assert_bytes_eq};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    auth::admin::authenticate_admin,
+    error::AuthError,
+    state::{
+        action::{validate_actions_buffer, MAX_ACTIONS_BUFFER_SIZE},
+        policy_template::{
+            same_state_layout, state_len, PolicyTemplateAccount, POLICY_TEMPLATE_HEADER_SIZE,
+        },
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+    utils::{initialize_pda_account, transfer_lamports},
+};
+
+/// Splits `[actions_len(2)][actions(N)][rest...]` and validates the actions buffer.
+///
+/// Templates must carry at least one action and fit in `MAX_ACTIONS_BUFFER_SIZE`.
+fn parse_template_actions(data: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+    if data.len() < 2 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let actions_len = u16::from_le_bytes([data[0], data[1]]) as usize;
+    if actions_len == 0 || actions_len > MAX_ACTIONS_BUFFER_SIZE {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let actions_end = 2 + actions_len;
+    if data.len() < actions_end {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let actions = &data[2..actions_end];
+    validate_actions_buffer(actions)?;
+    Ok((actions, &data[actions_end..]))
+}
+
+/// Process the CreatePolicyTemplate instruction.
+///
+/// Creates a wallet-scoped Policy Template PDA `["policy_template", wallet, template_id]`
+/// holding a validated action buffer. Sessions created with `SESSION_FLAG_TEMPLATE`
+/// reference it instead of embedding their own copy of the rules.
+///
+/// Only Owner or Admin can create templates.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Admin/Owner Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Policy Template PDA (created)
+/// 5. `[]` System Program
+/// 6. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [template_id(4)][actions_len(2)][actions(N)][auth_payload(variable)]
+pub fn process_create_policy_template(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 4 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let template_id_bytes: [u8; 4] = instruction_data[..4].try_into().unwrap();
+    let template_id = u32::from_le_bytes(template_id_bytes);
+    let (actions, authority_payload) = parse_template_actions(&instruction_data[4..])?;
+    let signed_len = instruction_data.len() - authority_payload.len();
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let admin_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let template_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Validate system_program is the correct System Program (audit N2)
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Bind the signature to the payer and the template id + actions
+    let mut data_payload = Vec::with_capacity(32 + signed_len);
+    data_payload.extend_from_slice(payer.key().as_ref());
+    data_payload.extend_from_slice(&instruction_data[..signed_len]);
+
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        admin_auth_pda,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        authority_payload,
+        &data_payload,
+        11,
+    )?;
+
+    // Derive the Policy Template PDA
+    let (template_key, bump) = find_program_address(
+        &[
+            b"policy_template",
+            wallet_pda.key().as_ref(),
+            &template_id_bytes,
+        ],
+        program_id,
+    );
+    if !sol_assert_bytes_eq(template_pda.key().as_ref(), template_key.as_ref(), 32) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_zero_data(template_pda, ProgramError::AccountAlreadyInitialized)?;
+
+    let template_state_len = state_len(actions)?;
+
+    let space = POLICY_TEMPLATE_HEADER_SIZE + actions.len();
+    let rent = Rent::get()?;
+    let template_rent = rent.minimum_balance(space);
+
+    let bump_arr = [bump];
+    let seeds = [
+        Seed::from(b"policy_template"),
+        Seed::from(wallet_pda.key().as_ref()),
+        Seed::from(&template_id_bytes),
+        Seed::from(&bump_arr),
+    ];
+
+    initialize_pda_account(
+        payer,
+        template_pda,
+        system_program,
+        space,
+        template_rent,
+        program_id,
+        &seeds,
+    )?;
+
+    let data = unsafe { template_pda.borrow_mut_data_unchecked() };
+    let header = PolicyTemplateAccount {
+        discriminator: AccountDiscriminator::PolicyTemplate as u8,
+        bump,
+        version: crate::state::CURRENT_ACCOUNT_VERSION,
+        _padding: [0; 1],
+        template_id,
+        wallet: *wallet_pda.key(),
+        state_len: template_state_len as u32,
+        revision: 0,
+    };
+    unsafe {
+        std::ptr::write_unaligned(data.as_mut_ptr() as *mut PolicyTemplateAccount, header);
+    }
+    data[POLICY_TEMPLATE_HEADER_SIZE..].copy_from_slice(actions);
+
+    Ok(())
+}
+
+/// Process the UpdatePolicyTemplate instruction.
+///
+/// Replaces a template's action buffer. The new buffer must keep the same
+/// stateful actions (limits, recurring and sliding limits) in the same order
+/// with the same mints and windows, so every bound session's counters still
+/// line up; static rules (whitelists, blacklists, per-tx caps, recurring and
+/// sliding caps, expiries) may change freely and apply to all bound sessions at
+/// their next `Execute`. Lifetime `SolLimit` / `TokenLimit` budgets are
+/// per-session state, so a new amount only seeds sessions created afterwards.
+///
+/// The account is resized to fit; extra rent is paid by the payer and excess
+/// rent is refunded to it.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Admin/Owner Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Policy Template PDA
+/// 5. `[]` System Program
+/// 6. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [actions_len(2)][actions(N)][auth_payload(variable)]
+pub fn process_update_policy_template(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (actions, authority_payload) = parse_template_actions(instruction_data)?;
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let admin_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let template_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if template_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Bind the signature to the specific template + new actions
+    let mut data_payload = Vec::with_capacity(32 + actions.len());
+    data_payload.extend_from_slice(template_pda.key().as_ref());
+    data_payload.extend_from_slice(actions);
+
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        admin_auth_pda,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        authority_payload,
+        &data_payload,
+        12,
+    )?;
+
+    // Validate template account
+    let data = unsafe { template_pda.borrow_data_unchecked() };
+    if data.len() <= POLICY_TEMPLATE_HEADER_SIZE {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let mut header =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const PolicyTemplateAccount) };
+    if header.discriminator != AccountDiscriminator::PolicyTemplate as u8
+        || header.wallet != *wallet_pda.key()
+    {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+
+    // Existing sessions' state must still line up with the new actions
+    if !same_state_layout(&data[POLICY_TEMPLATE_HEADER_SIZE..], actions)?
+        || state_len(actions)? != header.state_len as usize
+    {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+
+    // Resize and rebalance rent
+    let space = POLICY_TEMPLATE_HEADER_SIZE + actions.len();
+    let rent = Rent::get()?;
+    let required = rent.minimum_balance(space);
+    let current = template_pda.lamports();
+    if required > current {
+        transfer_lamports(payer, template_pda, system_program, required - current)?;
+    }
+    template_pda.resize(space)?;
+    if current > required {
+        let payer_lamports = payer.lamports();
+        unsafe {
+            *payer.borrow_mut_lamports_unchecked() = payer_lamports
+                .checked_add(current - required)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            *template_pda.borrow_mut_lamports_unchecked() = required;
+        }
+    }
+
+    header.revision = header.revision.wrapping_add(1);
+    let data = unsafe { template_pda.borrow_mut_data_unchecked() };
+    unsafe {
+        std::ptr::write_unaligned(data.as_mut_ptr() as *mut PolicyTemplateAccount, header);
+    }
+    data[POLICY_TEMPLATE_HEADER_SIZE..].copy_from_slice(actions);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sol_limit_action(remaining: u64) -> Vec<u8> {
+        let mut buf = vec![1u8];
+        buf.extend_from_slice(&8u16.to_le_bytes());
+        buf.extend_from_slice(&0u64.to_le_bytes());
+        buf.extend_from_slice(&remaining.to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn test_parse_template_actions_splits_auth_payload() {
+        let actions = sol_limit_action(1_000);
+        let mut data = (actions.len() as u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&actions);
+        data.extend_from_slice(&[9u8; 5]);
+
+        let (parsed, rest) = parse_template_actions(&data).unwrap();
+        assert_eq!(parsed, actions.as_slice());
+        assert_eq!(rest, &[9u8; 5]);
+    }
+
+    #[test]
+    fn test_parse_template_actions_rejects_empty_and_truncated() {
+        assert!(parse_template_actions(&0u16.to_le_bytes()).is_err());
+
+        let actions = sol_limit_action(1_000);
+        let mut data = (actions.len() as u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&actions[..actions.len() - 1]);
+        assert!(parse_template_actions(&data).is_err());
+    }
+}
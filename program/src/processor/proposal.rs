@@ -0,0 +1,220 @@
+use crate::{
+    auth::admin::authenticate_admin,
+    error::AuthError,
+    processor::authorize::{check_expires_at, create_authorization, DeferredArgs},
+    state::{
+        authority::{authority_expired, AuthorityAccountHeader},
+        deferred::{
+            DeferredExecAccount, DEFERRED_AUTHORITY_KIND_PROPOSAL, DEFERRED_EXEC_HEADER_SIZE,
+        },
+        proposal::{
+            approved_approvers, build_approval_section, read_approval_section, record_approval,
+        },
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Process the CreateProposal instruction.
+///
+/// Creates a multi-approver proposal: a DeferredExec PDA of kind
+/// `DEFERRED_AUTHORITY_KIND_PROPOSAL` storing the instruction/account hashes,
+/// the approver set and the wallet's `proposal_threshold` at creation. Each
+/// approver approves with `ApproveProposal`; once the threshold is met, anyone
+/// can run it once with `ExecuteDeferred`.
+///
+/// The proposer must be an Owner/Admin Authority. If it is one of the
+/// approvers, its signature counts as its approval.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Proposer Owner/Admin Authority PDA (counter incremented)
+/// 4. `[writable]` Proposal (DeferredExec) PDA (created)
+/// 5. `[]` System Program
+/// 6. `[]` Rent Sysvar
+/// 7. `[]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+/// 8. `[]` Wallets with `WALLET_FLAG_LIMITS`: Wallet Config PDA
+///
+/// # Instruction Data (after discriminator):
+///   [instructions_hash(32)][accounts_hash(32)][expires_at(8)][approver_count(1)]
+///   [approvers(32 × approver_count)][auth_payload(variable)]
+pub fn process_create_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 73 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let proposer_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+    let config = limits.get()?;
+    let expires_at = u64::from_le_bytes(instruction_data[64..72].try_into().unwrap());
+    check_expires_at(expires_at, &config)?;
+
+    let approvers_end = 73 + instruction_data[72] as usize * 32;
+    let approvers = instruction_data
+        .get(73..approvers_end)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let mut approval_section = build_approval_section(config.proposal_threshold, approvers)?;
+    if approvers
+        .chunks_exact(32)
+        .any(|approver| approver == proposer_pda.key().as_ref())
+    {
+        record_approval(&mut approval_section, proposer_pda.key().as_ref())?;
+    }
+
+    let args = DeferredArgs {
+        instructions_hash: instruction_data[0..32].try_into().unwrap(),
+        accounts_hash: instruction_data[32..64].try_into().unwrap(),
+        expires_at,
+        max_executions: 1,
+        min_interval_slots: 0,
+        trailer: &approval_section,
+        kind: Some(DEFERRED_AUTHORITY_KIND_PROPOSAL),
+    };
+
+    // Signed payload: hashes, expiry and the approver set
+    let (signed_payload, auth_payload) = instruction_data.split_at(approvers_end);
+    create_authorization(
+        program_id,
+        accounts,
+        &limits,
+        &args,
+        signed_payload,
+        auth_payload,
+        21,
+    )
+}
+
+/// Process the ApproveProposal instruction.
+///
+/// Records the approval of one of the proposal's approvers. The signature is
+/// bound to the proposal PDA. A proposal can be approved until it expires.
+///
+/// # Accounts:
+/// 1. `[signer]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Approver Owner/Admin Authority PDA
+/// 4. `[writable]` Proposal (DeferredExec) PDA
+/// 5. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   Secp256r1: [auth_payload(variable)]
+///   Ed25519: empty (auth is via signer)
+pub fn process_approve_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let authority_payload = instruction_data;
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let approver_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let proposal_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if proposal_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !proposal_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let proposal_data = unsafe { proposal_pda.borrow_mut_data_unchecked() };
+    if proposal_data.len() < DEFERRED_EXEC_HEADER_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let proposal =
+        unsafe { std::ptr::read_unaligned(proposal_data.as_ptr() as *const DeferredExecAccount) };
+    if proposal.discriminator != AccountDiscriminator::DeferredExec as u8
+        || proposal.authority_kind != DEFERRED_AUTHORITY_KIND_PROPOSAL
+        || proposal.wallet != *wallet_pda.key()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if Clock::get()?.slot > proposal.expires_at {
+        return Err(AuthError::DeferredAuthorizationExpired.into());
+    }
+
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        approver_pda,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        authority_payload,
+        proposal_pda.key().as_ref(),
+        22,
+    )?;
+
+    record_approval(
+        &mut proposal_data[DEFERRED_EXEC_HEADER_SIZE..],
+        approver_pda.key().as_ref(),
+    )
+}
+
+/// Verifies that a proposal has at least `threshold` approvals from approvers
+/// that are still Owner/Admin Authorities of `wallet`.
+///
+/// Approving Authority PDAs must be passed among `accounts`; an approver that
+/// was removed, demoted or expired since approving no longer counts.
+pub fn verify_proposal_approvals(
+    approval_section: &[u8],
+    wallet: &Pubkey,
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (threshold, approvals, approvers) = read_approval_section(approval_section)?;
+    let current_slot = Clock::get()?.slot;
+    let live_approvals = approved_approvers(approvals, approvers)
+        .filter(|approver| {
+            accounts
+                .iter()
+                .find(|acc| acc.key().as_ref() == *approver)
+                .is_some_and(|acc| is_owner_or_admin(acc, wallet, program_id, current_slot))
+        })
+        .count();
+    if live_approvals < threshold as usize {
+        return Err(AuthError::ProposalThresholdNotMet.into());
+    }
+    Ok(())
+}
+
+/// Returns whether `account` is an initialized, unexpired Owner/Admin
+/// Authority PDA of `wallet`.
+fn is_owner_or_admin(
+    account: &AccountInfo,
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+    current_slot: u64,
+) -> bool {
+    if account.owner() != program_id {
+        return false;
+    }
+    let data = unsafe { account.borrow_data_unchecked() };
+    if data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return false;
+    }
+    let header =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const AuthorityAccountHeader) };
+    header.discriminator == AccountDiscriminator::Authority as u8
+        && header.wallet == *wallet
+        && header.role <= 1
+        && authority_expired(data, current_slot) == Ok(false)
+}
@@ -1,6 +1,10 @@
 use crate::{
     error::AuthError,
-    state::{deferred::DeferredExecAccount, AccountDiscriminator},
+    processor::deferred_buffer::{buffer_payload, close_buffer},
+    state::{
+        deferred::{DeferredBufferAccount, DeferredExecAccount},
+        AccountDiscriminator,
+    },
 };
 use pinocchio::{
     account_info::AccountInfo,
@@ -15,10 +19,15 @@ use pinocchio::{
 /// Closes an expired DeferredExec account and refunds rent to the original payer.
 /// Only the original payer can reclaim, and only after the authorization has expired.
 ///
+/// A Deferred Buffer passed as the 4th account is closed as well. If the
+/// DeferredExec account is already gone (executed from instruction data), only
+/// the leftover buffer is closed.
+///
 /// # Accounts:
 /// 1. `[signer]` Payer (must match stored payer)
 /// 2. `[writable]` DeferredExec PDA (closed)
 /// 3. `[writable]` Refund destination
+/// 4. `[writable, optional]` Deferred Buffer PDA (closed)
 ///
 /// # Instruction Data (after discriminator):
 ///   (none)
@@ -37,11 +46,31 @@ pub fn process(
         .get(2)
         .ok_or(ProgramError::NotEnoughAccountKeys)?;
 
+    let buffer_pda = accounts.get(3);
+
     // Validate signer
     if !payer.is_signer() {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
+    if let Some(buffer_pda) = buffer_pda {
+        buffer_payload(buffer_pda, deferred_pda.key(), program_id)?;
+        let buffer = unsafe {
+            std::ptr::read_unaligned(
+                buffer_pda.borrow_data_unchecked().as_ptr() as *const DeferredBufferAccount
+            )
+        };
+        if buffer.payer != *payer.key() {
+            return Err(AuthError::UnauthorizedReclaim.into());
+        }
+        close_buffer(buffer_pda, refund_dest)?;
+
+        // Orphaned buffer: its authorization was already executed and closed
+        if deferred_pda.data_is_empty() {
+            return Ok(());
+        }
+    }
+
     // Verify ownership
     if deferred_pda.owner() != program_id {
         return Err(ProgramError::IllegalOwner);
@@ -2,8 +2,7 @@ use crate::{
     auth::{ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator},
     error::AuthError,
     state::{
-        authority::AuthorityAccountHeader,
-        session::SessionAccount,
+        authority::AuthorityAccountHeader, session::read_session, wallet_config::WalletLimits,
         AccountDiscriminator,
     },
 };
@@ -111,7 +110,8 @@ pub fn process(
             )?;
         }
         1 => {
-            Secp256r1Authenticator.authenticate(
+            let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+            Secp256r1Authenticator::new(&limits)?.authenticate(
                 accounts, admin_data, authority_payload, &data_payload, &[9], program_id,
             )?;
         }
@@ -120,12 +120,7 @@ pub fn process(
 
     // Validate session account
     let session_data = unsafe { session_pda.borrow_mut_data_unchecked() };
-    if session_data.len() < std::mem::size_of::<SessionAccount>() {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    let session = unsafe {
-        std::ptr::read_unaligned(session_data.as_ptr() as *const SessionAccount)
-    };
+    let session = read_session(session_data).ok_or(ProgramError::InvalidAccountData)?;
 
     if session.discriminator != AccountDiscriminator::Session as u8 {
         return Err(AuthError::InvalidSessionAccount.into());
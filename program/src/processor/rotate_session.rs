@@ -0,0 +1,227 @@
+use assertions::{check_zero_data, sol_assert_bytes_eq};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
+    error::AuthError,
+    state::{
+        authority::AuthorityAccountHeader,
+        session::{read_session, rekey_session},
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
+    utils::initialize_pda_account,
+};
+
+/// Process the RotateSession instruction.
+///
+/// Moves a session to a new session key without losing its state. The session
+/// PDA is seeded by the key, so rotation creates a fresh PDA for the new key,
+/// copies the header and the full action buffer (spent counters, recurring
+/// windows, usage statistics) byte-for-byte, rebinds `session_key` + `bump`, and
+/// closes the old PDA. Expiry, flags and creator are unchanged.
+///
+/// Only Owner or Admin can rotate. The old session key may co-sign the
+/// transaction but is not required — rotating a leaked or lost key must not
+/// depend on it.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer (funds the new PDA, receives the old PDA's rent)
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Admin/Owner Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Old Session PDA (closed)
+/// 5. `[writable]` New Session PDA (created)
+/// 6. `[]` System Program
+/// 7. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [new_session_key(32)][auth_payload(variable)]
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 32 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let new_session_key: Pubkey = instruction_data[..32].try_into().unwrap();
+    let authority_payload = &instruction_data[32..];
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let admin_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let old_session_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let new_session_pda = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(5).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Validate system_program is the correct System Program (audit N2)
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify ownership of program accounts
+    if wallet_pda.owner() != program_id
+        || admin_auth_pda.owner() != program_id
+        || old_session_pda.owner() != program_id
+    {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Validate Wallet discriminator
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Authority PDA must be writable (counter increment for Secp256r1)
+    if !admin_auth_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Read authority header
+    let admin_data = unsafe { admin_auth_pda.borrow_mut_data_unchecked() };
+    if admin_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let admin_header =
+        unsafe { std::ptr::read_unaligned(admin_data.as_ptr() as *const AuthorityAccountHeader) };
+
+    if admin_header.discriminator != AccountDiscriminator::Authority as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if admin_header.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Only Owner (0) or Admin (1) can rotate sessions
+    if admin_header.role > 1 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+
+    // Bind the signature to the specific old session + new key, and to the
+    // payer that collects the old session's rent (as CreateSession does)
+    let mut data_payload = Vec::with_capacity(96);
+    data_payload.extend_from_slice(old_session_pda.key().as_ref());
+    data_payload.extend_from_slice(&new_session_key);
+    data_payload.extend_from_slice(payer.key().as_ref());
+
+    // Authenticate
+    match admin_header.authority_type {
+        0 => {
+            Ed25519Authenticator.authenticate(
+                accounts,
+                admin_data,
+                &[],
+                &data_payload,
+                &[10],
+                program_id,
+            )?;
+        },
+        1 => {
+            let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+            Secp256r1Authenticator::new(&limits)?.authenticate(
+                accounts,
+                admin_data,
+                authority_payload,
+                &data_payload,
+                &[10],
+                program_id,
+            )?;
+        },
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+
+    // Validate old session account
+    let old_data = unsafe { old_session_pda.borrow_mut_data_unchecked() };
+    let old_session = read_session(old_data).ok_or(ProgramError::InvalidAccountData)?;
+
+    if old_session.discriminator != AccountDiscriminator::Session as u8 {
+        return Err(AuthError::InvalidSessionAccount.into());
+    }
+    if old_session.wallet != *wallet_pda.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if old_session.session_key == new_session_key {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // An expired session has nothing left to rotate — revoke it instead.
+    let clock = Clock::get()?;
+    if clock.slot > old_session.expires_at {
+        return Err(AuthError::SessionExpired.into());
+    }
+
+    // Derive the new Session PDA
+    let (new_session_key_pda, bump) = find_program_address(
+        &[b"session", wallet_pda.key().as_ref(), &new_session_key],
+        program_id,
+    );
+    if !sol_assert_bytes_eq(
+        new_session_pda.key().as_ref(),
+        new_session_key_pda.as_ref(),
+        32,
+    ) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_zero_data(new_session_pda, ProgramError::AccountAlreadyInitialized)?;
+
+    // Create the new Session account with the same size (header + action buffer)
+    let space = old_data.len();
+    let rent = Rent::get()?;
+    let session_rent = rent.minimum_balance(space);
+
+    let bump_arr = [bump];
+    let seeds = [
+        Seed::from(b"session"),
+        Seed::from(wallet_pda.key().as_ref()),
+        Seed::from(&new_session_key),
+        Seed::from(&bump_arr),
+    ];
+
+    initialize_pda_account(
+        payer,
+        new_session_pda,
+        system_program,
+        space,
+        session_rent,
+        program_id,
+        &seeds,
+    )?;
+
+    // Copy header + action buffer, then rebind to the new key
+    let new_data = unsafe { new_session_pda.borrow_mut_data_unchecked() };
+    new_data.copy_from_slice(old_data);
+    rekey_session(new_data, &new_session_key, bump);
+
+    // Close the old session account — zero data and refund rent to the payer
+    old_data.fill(0);
+
+    let old_lamports = old_session_pda.lamports();
+    let payer_lamports = unsafe { *payer.borrow_mut_lamports_unchecked() };
+    unsafe {
+        *payer.borrow_mut_lamports_unchecked() = payer_lamports
+            .checked_add(old_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *old_session_pda.borrow_mut_lamports_unchecked() = 0;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Session policy enforcement shared by `Execute` and `ExecuteDeferred`.
+//!
+//! A session-authenticated batch of CPIs runs inside a `SessionEnforcement`:
+//! `begin` validates the session and runs the pre-CPI checks and snapshots,
+//! `record_cpi` tracks gross vault outflow after each CPI, and `finish`
+//! verifies the vault invariants, applies the spending limits and persists the
+//! session's counters and usage statistics.
+
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    compact::{AccountResolver, CompactInstructionRef},
+    error::AuthError,
+    processor::execute_actions::{
+        compute_token_outflow, evaluate_post_actions, evaluate_pre_actions,
+        snapshot_token_authorities, snapshot_token_balances, verify_token_authorities_unchanged,
+        TokenAuthoritySnapshot, TokenSnapshot,
+    },
+    state::{
+        authority::{authority_expired, AuthorityAccountHeader},
+        policy_template::{
+            gather_state, scatter_state, PolicyTemplateAccount, POLICY_TEMPLATE_HEADER_SIZE,
+        },
+        session::{
+            has_actions, read_session, record_session_usage, uses_template, SessionAccount,
+            SESSION_FLAG_REQUIRE_CREATOR, SESSION_FLAG_STAGED, SESSION_HEADER_SIZE,
+        },
+        AccountDiscriminator,
+    },
+};
+
+/// State carried across the CPIs of one session-authenticated execution.
+pub struct SessionEnforcement {
+    /// Template-bound sessions: session header + template actions with the
+    /// session's counters scattered in. Enforcement runs on this buffer instead.
+    template_policy: Option<Vec<u8>>,
+    has_actions: bool,
+    current_slot: u64,
+    vault_lamports_before: u64,
+    prev_vault_lamports: u64,
+    /// Gross SOL outflow across all CPIs (for SolMaxPerTx)
+    vault_lamports_gross_out: u64,
+    token_snapshots_before: Vec<TokenSnapshot>,
+    vault_owner_before: Pubkey,
+    vault_data_len_before: usize,
+    token_authority_snapshots: Vec<TokenAuthoritySnapshot>,
+}
+
+impl SessionEnforcement {
+    /// Validates the session (`check_session_usable`) and runs the pre-CPI
+    /// checks and snapshots. The caller is responsible for authenticating the
+    /// session key.
+    pub fn begin(
+        session_data: &[u8],
+        wallet: &Pubkey,
+        compact_instructions: &[CompactInstructionRef<'_>],
+        resolver: &AccountResolver<'_>,
+        vault_pda: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let accounts = resolver.accounts();
+        let current_slot = check_session_usable(session_data, wallet, accounts, program_id)?;
+
+        let template_policy = if uses_template(session_data) {
+            Some(load_template_policy(
+                session_data,
+                wallet,
+                accounts,
+                program_id,
+            )?)
+        } else {
+            None
+        };
+        let policy_data: &[u8] = template_policy.as_deref().unwrap_or(session_data);
+        let has_actions = has_actions(policy_data);
+
+        // Pre-CPI action checks (payer pinning, program whitelist/blacklist)
+        if has_actions {
+            evaluate_pre_actions(policy_data, compact_instructions, resolver, current_slot)?;
+        }
+
+        // ── Session invariants (defense against System::Assign / SetAuthority escapes) ──
+        // A session that whitelists System Program (a common pattern for SOL transfers)
+        // could otherwise craft `System::Assign(vault, attacker)` — the lamport-based
+        // limits see no outflow, but ownership of the vault silently transfers to the
+        // attacker, who then drains it in a follow-up tx. Same class of attack via
+        // SPL Token's `SetAuthority` / `Approve` on vault-owned token accounts.
+        //
+        // Snapshot the vault's metadata + every listed-mint vault-owned token account's
+        // authority fields BEFORE the CPI loop; verify unchanged AFTER.
+        let token_snapshots_before =
+            snapshot_token_balances(policy_data, accounts, vault_pda.key())?;
+        let token_authority_snapshots = if has_actions {
+            snapshot_token_authorities(policy_data, accounts, vault_pda.key())?
+        } else {
+            Vec::new()
+        };
+        let vault_lamports_before = vault_pda.lamports();
+
+        Ok(Self {
+            template_policy,
+            has_actions,
+            current_slot,
+            vault_lamports_before,
+            prev_vault_lamports: vault_lamports_before,
+            vault_lamports_gross_out: 0,
+            token_snapshots_before,
+            vault_owner_before: *vault_pda.owner(),
+            vault_data_len_before: unsafe { vault_pda.borrow_data_unchecked().len() },
+            token_authority_snapshots,
+        })
+    }
+
+    /// Tracks gross SOL outflow per CPI (used for SolMaxPerTx — not net balance diff).
+    pub fn record_cpi(&mut self, vault_pda: &AccountInfo) {
+        let post = vault_pda.lamports();
+        if self.prev_vault_lamports > post {
+            self.vault_lamports_gross_out = self
+                .vault_lamports_gross_out
+                .saturating_add(self.prev_vault_lamports - post);
+        }
+        self.prev_vault_lamports = post;
+    }
+
+    /// Verifies the vault invariants, enforces the post-CPI spending limits and
+    /// writes the updated counters and usage statistics into `session_data`.
+    pub fn finish(
+        mut self,
+        session_data: &mut [u8],
+        accounts: &[AccountInfo],
+        vault_pda: &AccountInfo,
+    ) -> ProgramResult {
+        if self.has_actions {
+            // Verify vault's ownership and data layout were not tampered with. Any
+            // change (System::Assign, Allocate, AllocateWithSeed, AssignWithSeed) is
+            // rejected. This complements the balance-based limits below.
+            if *vault_pda.owner() != self.vault_owner_before {
+                return Err(AuthError::SessionVaultOwnerChanged.into());
+            }
+            let len_after = unsafe { vault_pda.borrow_data_unchecked().len() };
+            if len_after != self.vault_data_len_before {
+                return Err(AuthError::SessionVaultDataLenChanged.into());
+            }
+            // Verify no SetAuthority / Approve on listed-mint vault-owned token accounts.
+            verify_token_authorities_unchanged(&self.token_authority_snapshots, accounts)?;
+
+            // Post-CPI action checks (spending limits)
+            let policy_data: &mut [u8] = match self.template_policy.as_mut() {
+                Some(merged) => merged,
+                None => session_data,
+            };
+            evaluate_post_actions(
+                policy_data,
+                accounts,
+                vault_pda.key(),
+                self.vault_lamports_before,
+                vault_pda.lamports(),
+                self.vault_lamports_gross_out,
+                &self.token_snapshots_before,
+                self.current_slot,
+            )?;
+        }
+
+        // Persist the updated counters back into the template-bound session.
+        if let Some(merged) = &self.template_policy {
+            gather_state(
+                &merged[SESSION_HEADER_SIZE..],
+                &mut session_data[SESSION_HEADER_SIZE + 32..],
+            )?;
+        }
+
+        // ── Usage statistics (only reached on success) ─────────────────────
+        let sol_out = self
+            .vault_lamports_before
+            .saturating_sub(vault_pda.lamports());
+        let token_out =
+            compute_token_outflow(accounts, vault_pda.key(), &self.token_snapshots_before);
+        record_session_usage(session_data, self.current_slot, sol_out, token_out);
+
+        Ok(())
+    }
+}
+
+/// Checks that a session can authorize vault activity at the current slot and
+/// returns that slot.
+///
+/// Checks the wallet binding, slot expiry, that the action buffer is finalized
+/// and, for `SESSION_FLAG_REQUIRE_CREATOR`, the creator's liveness. Does not
+/// check the session key's signature.
+pub fn check_session_usable(
+    session_data: &[u8],
+    wallet: &Pubkey,
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let session = read_session(session_data).ok_or(ProgramError::InvalidAccountData)?;
+    if session.discriminator != AccountDiscriminator::Session as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify Wallet
+    if session.wallet != *wallet {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify Expiry
+    let current_slot = Clock::get()?.slot;
+    if current_slot > session.expires_at {
+        return Err(AuthError::SessionExpired.into());
+    }
+
+    // A session whose action buffer is still being appended has no policy yet.
+    if session.flags & SESSION_FLAG_STAGED != 0 {
+        return Err(AuthError::SessionNotFinalized.into());
+    }
+
+    // Creator liveness: the session dies with the Authority that created it.
+    if session.flags & SESSION_FLAG_REQUIRE_CREATOR != 0 {
+        verify_session_creator(&session, accounts, program_id, current_slot)?;
+    }
+
+    Ok(current_slot)
+}
+
+/// Verifies that the Authority PDA which created `session` is present in
+/// `accounts`, is still initialized, belongs to the session's wallet and has
+/// not expired.
+///
+/// `RemoveAuthority` zeroes and defunds the Authority account, so a removed
+/// creator fails the owner / discriminator checks below.
+fn verify_session_creator(
+    session: &SessionAccount,
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    current_slot: u64,
+) -> ProgramResult {
+    let creator = accounts
+        .iter()
+        .find(|acc| *acc.key() == session.creator)
+        .ok_or(AuthError::SessionCreatorRevoked)?;
+
+    if creator.owner() != program_id || creator.lamports() == 0 {
+        return Err(AuthError::SessionCreatorRevoked.into());
+    }
+    let creator_data = unsafe { creator.borrow_data_unchecked() };
+    if creator_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(AuthError::SessionCreatorRevoked.into());
+    }
+    let creator_header =
+        unsafe { std::ptr::read_unaligned(creator_data.as_ptr() as *const AuthorityAccountHeader) };
+    if creator_header.discriminator != AccountDiscriminator::Authority as u8
+        || creator_header.wallet != session.wallet
+        || authority_expired(creator_data, current_slot) != Ok(false)
+    {
+        return Err(AuthError::SessionCreatorRevoked.into());
+    }
+
+    Ok(())
+}
+
+/// Builds the effective policy of a template-bound session.
+///
+/// The session's trailing data is `[template(32)][state]`. The referenced
+/// Policy Template PDA must be in `accounts`, belong to `wallet`, and require
+/// exactly as much state as the session holds. Returns the session header
+/// followed by the template's actions with the session's state scattered in.
+fn load_template_policy(
+    session_data: &[u8],
+    wallet: &Pubkey,
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<Vec<u8>, ProgramError> {
+    if session_data.len() < SESSION_HEADER_SIZE + 32 {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let template_key = &session_data[SESSION_HEADER_SIZE..SESSION_HEADER_SIZE + 32];
+    let state = &session_data[SESSION_HEADER_SIZE + 32..];
+
+    let template_pda = accounts
+        .iter()
+        .find(|acc| acc.key().as_ref() == template_key)
+        .ok_or(AuthError::InvalidPolicyTemplate)?;
+    if template_pda.owner() != program_id {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let template_data = unsafe { template_pda.borrow_data_unchecked() };
+    if template_data.len() <= POLICY_TEMPLATE_HEADER_SIZE {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+    let template =
+        unsafe { std::ptr::read_unaligned(template_data.as_ptr() as *const PolicyTemplateAccount) };
+    if template.discriminator != AccountDiscriminator::PolicyTemplate as u8
+        || template.wallet != *wallet
+        || template.state_len as usize != state.len()
+    {
+        return Err(AuthError::InvalidPolicyTemplate.into());
+    }
+
+    let template_actions = &template_data[POLICY_TEMPLATE_HEADER_SIZE..];
+    let mut merged = Vec::with_capacity(SESSION_HEADER_SIZE + template_actions.len());
+    merged.extend_from_slice(&session_data[..SESSION_HEADER_SIZE]);
+    merged.extend_from_slice(template_actions);
+    scatter_state(&mut merged[SESSION_HEADER_SIZE..], state)?;
+    Ok(merged)
+}
@@ -0,0 +1,193 @@
+//! Step-up authentication for high-value executions.
+//!
+//! A wallet whose config sets a `step_up_authority` has `WALLET_FLAG_STEP_UP`
+//! on its Wallet PDA. Its executions run inside a `StepUpGuard`: a gross vault
+//! SOL outflow above `step_up_lamports`, or a vault token outflow above a
+//! configured per-mint threshold, fails with `StepUpRequired` unless the
+//! step-up authority approved the execution. For `Execute` that means it is
+//! the executing authority or co-signs the same instruction; for
+//! `ExecuteDeferred`, that it authorized (or approved) the DeferredExec.
+
+use crate::{
+    auth::{
+        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+    },
+    error::AuthError,
+    processor::execute_actions::find_token_balance,
+    state::{
+        authority::AuthorityAccountHeader,
+        deferred::{
+            DeferredExecAccount, DEFERRED_AUTHORITY_KIND_AUTHORITY,
+            DEFERRED_AUTHORITY_KIND_PROPOSAL, DEFERRED_AUTHORITY_KIND_TIMELOCK,
+        },
+        proposal::{approved_approvers, read_approval_section},
+        wallet_config::{WalletConfigView, WalletLimits},
+        AccountDiscriminator,
+    },
+};
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Step-up checks around an execution that the step-up authority did not approve.
+pub struct StepUpGuard {
+    max_lamports: u64,
+    prev_vault_lamports: u64,
+    vault_lamports_gross_out: u64,
+    token_thresholds: TokenThresholds,
+}
+
+impl StepUpGuard {
+    /// Returns `None` if the wallet has no step-up authority or it approved
+    /// the execution. Otherwise snapshots the vault balances.
+    pub fn begin(
+        config: &WalletConfigView<'_>,
+        stepped_up: bool,
+        accounts: &[AccountInfo],
+        vault_pda: &AccountInfo,
+    ) -> Option<Self> {
+        if !config.account.step_up_enabled() || stepped_up {
+            return None;
+        }
+        Some(Self {
+            max_lamports: config.account.step_up_lamports,
+            prev_vault_lamports: vault_pda.lamports(),
+            vault_lamports_gross_out: 0,
+            token_thresholds: TokenThresholds::begin(
+                config.step_up_thresholds(),
+                accounts,
+                vault_pda,
+            ),
+        })
+    }
+
+    /// Accumulates the vault's SOL outflow of one CPI.
+    pub fn record_cpi(&mut self, vault_pda: &AccountInfo) {
+        let post = vault_pda.lamports();
+        if self.prev_vault_lamports > post {
+            self.vault_lamports_gross_out = self
+                .vault_lamports_gross_out
+                .saturating_add(self.prev_vault_lamports - post);
+        }
+        self.prev_vault_lamports = post;
+    }
+
+    /// Rejects the execution if an outflow exceeded its step-up threshold.
+    pub fn finish(self, accounts: &[AccountInfo], vault_pda: &AccountInfo) -> ProgramResult {
+        if self.vault_lamports_gross_out > self.max_lamports
+            || self.token_thresholds.exceeded(accounts, vault_pda)
+        {
+            return Err(AuthError::StepUpRequired.into());
+        }
+        Ok(())
+    }
+}
+
+/// Vault token balances snapshotted against per-mint outflow thresholds
+/// (step-up and timelock).
+pub struct TokenThresholds {
+    /// `(mint, vault balance before, max outflow)` per token threshold.
+    entries: Vec<([u8; 32], u64, u64)>,
+}
+
+impl TokenThresholds {
+    /// Snapshots the vault balance of each `(mint, max_amount)` threshold.
+    pub fn begin(
+        thresholds: impl Iterator<Item = ([u8; 32], u64)>,
+        accounts: &[AccountInfo],
+        vault_pda: &AccountInfo,
+    ) -> Self {
+        let entries = thresholds
+            .map(|(mint, max)| {
+                let before = find_token_balance(accounts, vault_pda.key(), &mint).unwrap_or(0);
+                (mint, before, max)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Whether the vault's outflow of a mint exceeded its threshold.
+    pub fn exceeded(&self, accounts: &[AccountInfo], vault_pda: &AccountInfo) -> bool {
+        self.entries.iter().any(|(mint, before, max)| {
+            let after = find_token_balance(accounts, vault_pda.key(), mint).unwrap_or(*before);
+            before.saturating_sub(after) > *max
+        })
+    }
+}
+
+/// Returns the step-up authority's PDA if it was passed to co-sign, i.e.
+/// among `accounts` and not the executing authority itself.
+pub fn find_step_up_cosigner<'a>(
+    config: &WalletConfigView<'_>,
+    executing_pda: &AccountInfo,
+    accounts: &'a [AccountInfo],
+) -> Option<&'a AccountInfo> {
+    if !config.account.step_up_enabled() || *executing_pda.key() == config.account.step_up_authority
+    {
+        return None;
+    }
+    accounts
+        .iter()
+        .find(|acc| *acc.key() == config.account.step_up_authority)
+}
+
+/// Authenticates the step-up co-signer of an `Execute`: an Ed25519 authority
+/// signs the transaction, a Secp256r1 authority signs `signed_payload` (the
+/// compact instructions and accounts hash, as for the executing authority).
+pub fn authenticate_step_up(
+    accounts: &[AccountInfo],
+    limits: &WalletLimits<'_>,
+    step_up_pda: &AccountInfo,
+    wallet: &Pubkey,
+    auth_payload: &[u8],
+    signed_payload: &[u8],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if step_up_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !step_up_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let data = unsafe { step_up_pda.borrow_mut_data_unchecked() };
+    if data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let header =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const AuthorityAccountHeader) };
+    if header.discriminator != AccountDiscriminator::Authority as u8 || header.wallet != *wallet {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    match header.authority_type {
+        0 => Ed25519Authenticator.authenticate(accounts, data, &[], &[], &[4], program_id),
+        1 => Secp256r1Authenticator::new(limits)?.authenticate(
+            accounts,
+            data,
+            auth_payload,
+            signed_payload,
+            &[4],
+            program_id,
+        ),
+        _ => Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+}
+
+/// Whether the step-up authority approved a DeferredExec: it authorized or
+/// queued it, or approved the proposal. Session authorizations never are.
+pub fn deferred_stepped_up(
+    config: &WalletConfigView<'_>,
+    deferred: &DeferredExecAccount,
+    trailer: &[u8],
+) -> Result<bool, ProgramError> {
+    let step_up_authority = config.account.step_up_authority.as_ref();
+    Ok(match deferred.authority_kind {
+        DEFERRED_AUTHORITY_KIND_AUTHORITY | DEFERRED_AUTHORITY_KIND_TIMELOCK => {
+            deferred.authority.as_ref() == step_up_authority
+        },
+        DEFERRED_AUTHORITY_KIND_PROPOSAL => {
+            let (_, approvals, approvers) = read_approval_section(trailer)?;
+            approved_approvers(approvals, approvers).any(|approver| approver == step_up_authority)
+        },
+        _ => false,
+    })
+}
@@ -0,0 +1,161 @@
+//! Execution timelock.
+//!
+//! A wallet whose config sets `timelock_slots` has `WALLET_FLAG_TIMELOCK` on
+//! its Wallet PDA. Its direct executions (`Execute` and every
+//! `ExecuteDeferred` except a queued one) run inside a `TimelockGuard`: calls
+//! to a sensitive program, a vault SOL outflow above `timelock_max_lamports`,
+//! or a vault token outflow above a configured per-mint threshold, fail with
+//! `TimelockRequired`. Such operations are
+//! queued with `QueueExecute` instead, and can run `timelock_slots` later
+//! unless an Owner/Admin vetoes them with `CancelDeferred`.
+
+use crate::{
+    compact::{AccountResolver, CompactInstructionRef},
+    error::AuthError,
+    processor::{
+        authorize::{create_authorization, DeferredArgs},
+        step_up::TokenThresholds,
+    },
+    state::{
+        deferred::DEFERRED_AUTHORITY_KIND_TIMELOCK,
+        wallet_config::{
+            load_execution_policy, WalletConfigAccount, WalletConfigView, WalletLimits,
+        },
+    },
+};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+/// Timelock checks around a direct execution of a timelocked wallet.
+pub struct TimelockGuard {
+    max_lamports: u64,
+    vault_lamports_before: u64,
+    token_thresholds: TokenThresholds,
+}
+
+impl TimelockGuard {
+    /// Returns `None` if the wallet config (see `load_execution_policy`) has
+    /// no timelock. Otherwise rejects calls to its sensitive programs and
+    /// snapshots the vault balances.
+    pub fn begin(
+        config: &WalletConfigView<'_>,
+        compact_instructions: &[CompactInstructionRef<'_>],
+        resolver: &AccountResolver<'_>,
+        accounts: &[AccountInfo],
+        vault_pda: &AccountInfo,
+    ) -> Result<Option<Self>, ProgramError> {
+        if !config.account.timelock_enabled() {
+            return Ok(None);
+        }
+
+        for ix in compact_instructions {
+            let target = resolver.resolve(ix.program_id_index)?.key();
+            if config
+                .sensitive_programs
+                .chunks_exact(32)
+                .any(|program| program == target.as_ref())
+            {
+                return Err(AuthError::TimelockRequired.into());
+            }
+        }
+
+        Ok(Some(Self {
+            max_lamports: config.account.timelock_max_lamports,
+            vault_lamports_before: vault_pda.lamports(),
+            token_thresholds: TokenThresholds::begin(
+                config.timelock_thresholds(),
+                accounts,
+                vault_pda,
+            ),
+        }))
+    }
+
+    /// Rejects the execution if the vault lost more than `timelock_max_lamports`
+    /// or a token above its timelock threshold.
+    pub fn finish(self, accounts: &[AccountInfo], vault_pda: &AccountInfo) -> ProgramResult {
+        let sol_out = self
+            .vault_lamports_before
+            .saturating_sub(vault_pda.lamports());
+        if sol_out > self.max_lamports || self.token_thresholds.exceeded(accounts, vault_pda) {
+            return Err(AuthError::TimelockRequired.into());
+        }
+        Ok(())
+    }
+}
+
+/// Process the QueueExecute instruction.
+///
+/// Queues an execution of a timelocked wallet: a DeferredExec PDA of kind
+/// `DEFERRED_AUTHORITY_KIND_TIMELOCK` that `ExecuteDeferred` runs once, no
+/// earlier than `timelock_slots` after queueing and without the timelock
+/// checks. The proposer is recorded as the DeferredExec `authority`. Until it
+/// runs, any Owner/Admin can veto it with `CancelDeferred`.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer
+/// 2. `[]` Wallet PDA
+/// 3. `[writable]` Proposer Owner/Admin Authority PDA (counter incremented)
+/// 4. `[writable]` Queued (DeferredExec) PDA (created)
+/// 5. `[]` System Program
+/// 6. `[]` Rent Sysvar
+/// 7. `[]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+/// 8. `[]` Wallet Config PDA (the wallet must have `WALLET_FLAG_TIMELOCK`)
+///
+/// # Instruction Data (after discriminator):
+///   [instructions_hash(32)][accounts_hash(32)][expires_at(8)][auth_payload(variable)]
+pub fn process_queue_execute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < 72 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    let config = load_execution_policy(wallet_data, wallet_pda.key(), accounts, program_id)?
+        .map(|config| config.account)
+        .filter(WalletConfigAccount::timelock_enabled)
+        .ok_or(AuthError::InvalidWalletConfig)?;
+
+    // Runnable from `not_before`, then for at most the wallet's deferred lifetime
+    let not_before = Clock::get()?
+        .slot
+        .checked_add(config.timelock_slots)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let expires_at = u64::from_le_bytes(instruction_data[64..72].try_into().unwrap());
+    if expires_at <= not_before
+        || expires_at > not_before.saturating_add(config.max_deferred_expiry_slots)
+    {
+        return Err(AuthError::InvalidExpiryWindow.into());
+    }
+
+    let not_before_bytes = not_before.to_le_bytes();
+    let args = DeferredArgs {
+        instructions_hash: instruction_data[0..32].try_into().unwrap(),
+        accounts_hash: instruction_data[32..64].try_into().unwrap(),
+        expires_at,
+        max_executions: 1,
+        min_interval_slots: 0,
+        trailer: &not_before_bytes,
+        kind: Some(DEFERRED_AUTHORITY_KIND_TIMELOCK),
+    };
+
+    // Signed payload: instructions_hash || accounts_hash || expires_at
+    let (signed_payload, auth_payload) = instruction_data.split_at(72);
+    create_authorization(
+        program_id,
+        accounts,
+        &WalletLimits::new(wallet_pda, accounts, program_id).with_config(Some(&config)),
+        &args,
+        signed_payload,
+        auth_payload,
+        23,
+    )
+}
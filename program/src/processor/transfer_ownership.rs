@@ -11,10 +11,16 @@ use pinocchio::{
 use crate::{
     // Unified authentication helpers.
     auth::{
-        ed25519::Ed25519Authenticator, secp256r1::Secp256r1Authenticator, traits::Authenticator,
+        admin::authenticate_admin, ed25519::Ed25519Authenticator, possession::prove_possession,
+        secp256r1::Secp256r1Authenticator, traits::Authenticator,
     },
     error::AuthError,
-    state::{authority::AuthorityAccountHeader, AccountDiscriminator},
+    state::{
+        authority::{authority_data_len, AuthorityAccountHeader},
+        pending_owner::{read_pending_owner, PendingOwnerAccount, PENDING_OWNER_HEADER_SIZE},
+        wallet_config::WalletLimits,
+        AccountDiscriminator,
+    },
 };
 
 /// Processes the `TransferOwnership` instruction.
@@ -66,39 +72,7 @@ pub fn process(
     instruction_data: &[u8],
 ) -> ProgramResult {
     let (args, rest) = TransferOwnershipArgs::from_bytes(instruction_data)?;
-
-    let (id_seed, full_auth_data) = match args.auth_type {
-        0 => {
-            if rest.len() < 32 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let (pubkey, _) = rest.split_at(32);
-            (pubkey, pubkey)
-        },
-        1 => {
-            // [credential_id_hash(32)] [pubkey(33)] [rpIdLen(1)] [rpId(N)]
-            if rest.len() < 66 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let (hash, rest_after_hash) = rest.split_at(32);
-            let rp_id_len = rest_after_hash[33] as usize;
-            if rp_id_len == 0 || rp_id_len > 253 {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let total_auth_data = 32 + 33 + 1 + rp_id_len;
-            if rest.len() < total_auth_data {
-                return Err(ProgramError::InvalidInstructionData);
-            }
-            let full_data = &rest[..total_auth_data];
-            (hash, full_data)
-        },
-        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
-    };
-
-    // Issue #15: Prevent transferring ownership to zero address / SystemProgram
-    if id_seed.iter().all(|&x| x == 0) {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let (id_seed, full_auth_data) = parse_new_owner(args.auth_type, rest)?;
 
     // Split data_payload and authority_payload
     let data_payload_len = 1 + full_auth_data.len(); // auth_type + full_auth_data
@@ -191,7 +165,8 @@ pub fn process(
                 extended_data_payload.extend_from_slice(payer.key().as_ref());
                 extended_data_payload.extend_from_slice(refund_dest.key().as_ref());
 
-                Secp256r1Authenticator.authenticate(
+                let limits = WalletLimits::new(wallet_pda, accounts, program_id);
+                Secp256r1Authenticator::new(&limits)?.authenticate(
                     accounts,
                     data,
                     authority_payload,
@@ -251,59 +226,443 @@ pub fn process(
         role: 0,
         bump,
         version: crate::state::CURRENT_ACCOUNT_VERSION,
-        _padding1: [0; 3],
+        flags: 0,
+        _padding1: [0; 2],
         counter: 0,
-        _padding2: [0; 4],
+        last_used_slot: 0,
         wallet: *wallet_pda.key(),
     };
     unsafe {
         std::ptr::write_unaligned(data.as_mut_ptr() as *mut AuthorityAccountHeader, header);
     }
 
-    // Write variable data. For Secp256r1 hash rpId once here so every Execute
-    // saves a sol_sha256 syscall.
-    match args.auth_type {
+    write_owner_data(args.auth_type, full_auth_data, &mut data[header_size..]);
+
+    let current_lamports = unsafe { *current_owner.borrow_mut_lamports_unchecked() };
+    let refund_lamports = unsafe { *refund_dest.borrow_mut_lamports_unchecked() };
+    unsafe {
+        *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
+            .checked_add(current_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *current_owner.borrow_mut_lamports_unchecked() = 0;
+    }
+    let current_data = unsafe { current_owner.borrow_mut_data_unchecked() };
+    current_data.fill(0);
+
+    Ok(())
+}
+
+/// Splits the new Owner's `[id(32)]` (Ed25519 pubkey) or
+/// `[credential_id_hash(32)][pubkey(33)][rpIdLen(1)][rpId(N)]` (Secp256r1)
+/// off `data`, returning `(id_seed, full_auth_data)`.
+fn parse_new_owner(auth_type: u8, data: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+    let (id_seed, full_auth_data) = match auth_type {
+        0 => {
+            if data.len() < 32 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let (pubkey, _) = data.split_at(32);
+            (pubkey, pubkey)
+        },
+        1 => {
+            // [credential_id_hash(32)] [pubkey(33)] [rpIdLen(1)] [rpId(N)]
+            if data.len() < 66 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let (hash, rest_after_hash) = data.split_at(32);
+            let rp_id_len = rest_after_hash[33] as usize;
+            if rp_id_len == 0 || rp_id_len > 253 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            let total_auth_data = 32 + 33 + 1 + rp_id_len;
+            if data.len() < total_auth_data {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            (hash, &data[..total_auth_data])
+        },
+        _ => return Err(AuthError::InvalidAuthenticationKind.into()),
+    };
+
+    // Issue #15: Prevent transferring ownership to zero address / SystemProgram
+    if id_seed.iter().all(|&x| x == 0) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok((id_seed, full_auth_data))
+}
+
+/// Writes the stored authority data of a new Owner into `out`. For Secp256r1
+/// rpId is hashed once here so every Execute saves a sol_sha256 syscall.
+fn write_owner_data(auth_type: u8, full_auth_data: &[u8], out: &mut [u8]) {
+    match auth_type {
         0 => {
-            data[header_size..header_size + 32].copy_from_slice(&full_auth_data[..32]);
+            out[..32].copy_from_slice(&full_auth_data[..32]);
         }
         1 => {
-            data[header_size..header_size + 32].copy_from_slice(&full_auth_data[..32]);
-            data[header_size + 32..header_size + 32 + 33]
-                .copy_from_slice(&full_auth_data[32..32 + 33]);
+            out[..32 + 33].copy_from_slice(&full_auth_data[..32 + 33]);
             let rp_id_len = full_auth_data[32 + 33] as usize;
             let rp_id = &full_auth_data[32 + 33 + 1..32 + 33 + 1 + rp_id_len];
-            let rp_id_hash_offset = header_size + 32 + 33;
+            let rp_id_hash = &mut out[32 + 33..32 + 33 + 32];
             #[cfg(target_os = "solana")]
             unsafe {
                 let _ = pinocchio::syscalls::sol_sha256(
                     [rp_id].as_ptr() as *const u8,
                     1,
-                    data[rp_id_hash_offset..rp_id_hash_offset + 32].as_mut_ptr(),
+                    rp_id_hash.as_mut_ptr(),
                 );
             }
             #[cfg(not(target_os = "solana"))]
             {
                 let _ = rp_id;
-                data[rp_id_hash_offset..rp_id_hash_offset + 32].fill(0);
+                rp_id_hash.fill(0);
             }
         }
         _ => unreachable!(),
     }
+}
 
-    let current_lamports = unsafe { *current_owner.borrow_mut_lamports_unchecked() };
-    let refund_lamports = unsafe { *refund_dest.borrow_mut_lamports_unchecked() };
+/// Reads `authority_pda` as an Owner Authority of `wallet`.
+fn require_owner(
+    authority_pda: &AccountInfo,
+    wallet: &Pubkey,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if authority_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = unsafe { authority_pda.borrow_data_unchecked() };
+    if data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let auth = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const AuthorityAccountHeader) };
+    if auth.discriminator != AccountDiscriminator::Authority as u8 || auth.wallet != *wallet {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if auth.role != 0 {
+        return Err(AuthError::PermissionDenied.into());
+    }
+    Ok(())
+}
+
+/// Moves all lamports of `account` to `destination` and zeroes its data.
+fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let account_lamports = unsafe { *account.borrow_mut_lamports_unchecked() };
+    let destination_lamports = unsafe { *destination.borrow_mut_lamports_unchecked() };
     unsafe {
-        *refund_dest.borrow_mut_lamports_unchecked() = refund_lamports
-            .checked_add(current_lamports)
+        *destination.borrow_mut_lamports_unchecked() = destination_lamports
+            .checked_add(account_lamports)
             .ok_or(ProgramError::ArithmeticOverflow)?;
-        *current_owner.borrow_mut_lamports_unchecked() = 0;
+        *account.borrow_mut_lamports_unchecked() = 0;
     }
-    let current_data = unsafe { current_owner.borrow_mut_data_unchecked() };
-    current_data.fill(0);
+    unsafe { account.borrow_mut_data_unchecked() }.fill(0);
+    Ok(())
+}
+
+/// Processes the `ProposeOwnership` instruction.
+///
+/// First step of a two-step ownership transfer: an Owner records the next
+/// Owner in the wallet's Pending Owner PDA `["pending_owner", wallet]`.
+/// Nothing changes until the new authority proves possession of its key with
+/// `AcceptOwnership`, so a mistyped key or credential cannot lock the wallet
+/// out. One transfer can be pending at a time; `CancelOwnershipTransfer`
+/// withdraws it.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer (pays the Pending Owner rent).
+/// 2. `[]` Wallet PDA.
+/// 3. `[writable]` Current Owner Authority PDA (proposer).
+/// 4. `[writable]` Pending Owner PDA (created).
+/// 5. `[]` Refund Destination (later receives the proposer and Pending Owner rent).
+/// 6. `[]` System Program.
+/// 7. `[]` Rent Sysvar.
+/// 8. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [new_type(1)][new owner id data, as `TransferOwnership`][auth_payload(variable)]
+pub fn process_propose_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (args, rest) = TransferOwnershipArgs::from_bytes(instruction_data)?;
+    let (_, full_auth_data) = parse_new_owner(args.auth_type, rest)?;
+    let (data_payload, authority_payload) = instruction_data.split_at(1 + full_auth_data.len());
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let current_owner = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let pending_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let refund_dest = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(5).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let rent_sysvar = accounts.get(6).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    // Guard: closing current_owner to itself would burn lamports.
+    if current_owner.key() == refund_dest.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    require_owner(current_owner, wallet_pda.key(), program_id)?;
+
+    // Bind the signature to the new owner, the pending PDA and the refund destination
+    let mut signed_payload = Vec::with_capacity(data_payload.len() + 64);
+    signed_payload.extend_from_slice(data_payload);
+    signed_payload.extend_from_slice(pending_pda.key().as_ref());
+    signed_payload.extend_from_slice(refund_dest.key().as_ref());
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        current_owner,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        authority_payload,
+        &signed_payload,
+        24,
+    )?;
+
+    let (pending_key, bump) =
+        find_program_address(&[b"pending_owner", wallet_pda.key().as_ref()], program_id);
+    if !sol_assert_bytes_eq(pending_pda.key().as_ref(), pending_key.as_ref(), 32) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_zero_data(pending_pda, ProgramError::AccountAlreadyInitialized)?;
+
+    let space = PENDING_OWNER_HEADER_SIZE + authority_data_len(args.auth_type)?;
+    let rent = Rent::from_account_info(rent_sysvar)?.minimum_balance(space);
+    let bump_arr = [bump];
+    let seeds = [
+        Seed::from(b"pending_owner"),
+        Seed::from(wallet_pda.key().as_ref()),
+        Seed::from(&bump_arr),
+    ];
+    crate::utils::initialize_pda_account(
+        payer,
+        pending_pda,
+        system_program,
+        space,
+        rent,
+        program_id,
+        &seeds,
+    )?;
+
+    let pending = PendingOwnerAccount {
+        discriminator: AccountDiscriminator::PendingOwner as u8,
+        bump,
+        version: crate::state::CURRENT_ACCOUNT_VERSION,
+        auth_type: args.auth_type,
+        _padding: [0; 4],
+        wallet: *wallet_pda.key(),
+        proposer: *current_owner.key(),
+        refund_destination: *refund_dest.key(),
+    };
+    let data = unsafe { pending_pda.borrow_mut_data_unchecked() };
+    unsafe {
+        std::ptr::write_unaligned(data.as_mut_ptr() as *mut PendingOwnerAccount, pending);
+    }
+    write_owner_data(
+        args.auth_type,
+        full_auth_data,
+        &mut data[PENDING_OWNER_HEADER_SIZE..],
+    );
 
     Ok(())
 }
 
+/// Processes the `AcceptOwnership` instruction.
+///
+/// Second step of a two-step ownership transfer. The pending new Owner
+/// authenticates with its own key — an Ed25519 key signs the transaction, a
+/// Secp256r1 passkey signs a WebAuthn assertion bound to the Pending Owner
+/// PDA (counter 1) — proving possession. Its Owner Authority PDA is then
+/// created, and the proposer and the Pending Owner PDA are closed.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer (pays the new Owner rent).
+/// 2. `[]` Wallet PDA.
+/// 3. `[writable]` Pending Owner PDA (closed).
+/// 4. `[writable]` Proposer Owner Authority PDA (closed).
+/// 5. `[writable]` New Owner Authority PDA (created).
+/// 6. `[writable]` Refund Destination (as recorded by `ProposeOwnership`).
+/// 7. `[]` System Program.
+/// 8. `[]` Rent Sysvar.
+/// 9. `[optional]` Auth extra (Ed25519: new owner keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   Secp256r1: [auth_payload(variable)]
+///   Ed25519: empty (auth is via signer)
+pub fn process_accept_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let authority_payload = instruction_data;
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let pending_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let current_owner = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let new_owner = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let refund_dest = accounts.get(5).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(6).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let rent_sysvar = accounts.get(7).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if wallet_pda.owner() != program_id || pending_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let wallet_data = unsafe { wallet_pda.borrow_data_unchecked() };
+    if wallet_data.is_empty() || wallet_data[0] != AccountDiscriminator::Wallet as u8 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !pending_pda.is_writable() || !current_owner.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (pending, owner_data) = read_pending_owner(
+        unsafe { pending_pda.borrow_data_unchecked() },
+        wallet_pda.key(),
+    )?;
+    if pending.proposer != *current_owner.key() || pending.refund_destination != *refund_dest.key()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // The proposer must still be an Owner; a removed proposer's transfer is void
+    require_owner(current_owner, wallet_pda.key(), program_id)?;
+
+    let id_seed = &owner_data[..32];
+    let (new_key, bump) = find_program_address(
+        &[b"authority", wallet_pda.key().as_ref(), id_seed],
+        program_id,
+    );
+    if !sol_assert_bytes_eq(new_owner.key().as_ref(), new_key.as_ref(), 32) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_zero_data(new_owner, ProgramError::AccountAlreadyInitialized)?;
+
+    // Assemble the new Owner's account data and authenticate against it
+    // before it exists: the signature proves possession of the pending key.
+    let header_size = std::mem::size_of::<AuthorityAccountHeader>();
+    let mut authority_data = vec![0u8; header_size + owner_data.len()];
+    let header = AuthorityAccountHeader {
+        discriminator: AccountDiscriminator::Authority as u8,
+        authority_type: pending.auth_type,
+        role: 0,
+        bump,
+        version: crate::state::CURRENT_ACCOUNT_VERSION,
+        flags: 0,
+        _padding1: [0; 2],
+        counter: 0,
+        last_used_slot: 0,
+        wallet: *wallet_pda.key(),
+    };
+    unsafe {
+        std::ptr::write_unaligned(
+            authority_data.as_mut_ptr() as *mut AuthorityAccountHeader,
+            header,
+        );
+    }
+    authority_data[header_size..].copy_from_slice(owner_data);
+
+    // Accepted owners are marked verified
+    prove_possession(
+        accounts,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        &mut authority_data,
+        authority_payload,
+        pending_pda.key().as_ref(),
+        &[25],
+        program_id,
+    )?;
+
+    let rent = Rent::from_account_info(rent_sysvar)?.minimum_balance(authority_data.len());
+    let bump_arr = [bump];
+    let seeds = [
+        Seed::from(b"authority"),
+        Seed::from(wallet_pda.key().as_ref()),
+        Seed::from(id_seed),
+        Seed::from(&bump_arr),
+    ];
+    crate::utils::initialize_pda_account(
+        payer,
+        new_owner,
+        system_program,
+        authority_data.len(),
+        rent,
+        program_id,
+        &seeds,
+    )?;
+    // Includes the counter advanced by a Secp256r1 assertion
+    unsafe { new_owner.borrow_mut_data_unchecked() }.copy_from_slice(&authority_data);
+
+    close_account(current_owner, refund_dest)?;
+    close_account(pending_pda, refund_dest)
+}
+
+/// Processes the `CancelOwnershipTransfer` instruction.
+///
+/// Any Owner withdraws a pending ownership transfer before it is accepted.
+/// The signature is bound to the Pending Owner PDA; its rent goes to the
+/// recorded refund destination.
+///
+/// # Accounts:
+/// 1. `[signer]` Payer.
+/// 2. `[]` Wallet PDA.
+/// 3. `[writable]` Owner Authority PDA.
+/// 4. `[writable]` Pending Owner PDA (closed).
+/// 5. `[writable]` Refund Destination (as recorded by `ProposeOwnership`).
+/// 6. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   Secp256r1: [auth_payload(variable)]
+///   Ed25519: empty (auth is via signer)
+pub fn process_cancel_ownership_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let authority_payload = instruction_data;
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let owner_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let pending_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let refund_dest = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pending_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !pending_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (pending, _) = read_pending_owner(
+        unsafe { pending_pda.borrow_data_unchecked() },
+        wallet_pda.key(),
+    )?;
+    if pending.refund_destination != *refund_dest.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    require_owner(owner_pda, wallet_pda.key(), program_id)?;
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        owner_pda,
+        &WalletLimits::new(wallet_pda, accounts, program_id),
+        authority_payload,
+        pending_pda.key().as_ref(),
+        26,
+    )?;
+
+    close_account(pending_pda, refund_dest)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
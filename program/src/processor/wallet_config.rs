@@ -0,0 +1,298 @@
+use assertions::sol_assert_bytes_eq;
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    auth::admin::authenticate_admin,
+    error::AuthError,
+    state::{
+        authority::AuthorityAccountHeader,
+        wallet::{
+            wallet_flags, WalletAccount, WALLET_FLAG_FROZEN, WALLET_FLAG_LIMITS,
+            WALLET_FLAG_STEP_UP, WALLET_FLAG_TIMELOCK,
+        },
+        wallet_config::{
+            only_guardian_changed, read_wallet_config, timelock_not_weakened, WalletConfigAccount,
+            WalletConfigView, WalletLimits, STEP_UP_MINT_ENTRY_SIZE, WALLET_CONFIG_HEADER_SIZE,
+        },
+        AccountDiscriminator, CURRENT_ACCOUNT_VERSION,
+    },
+    utils::{initialize_pda_account, transfer_lamports},
+};
+
+/// Size of the fixed SetWalletConfig arguments (before the sensitive programs).
+const CONFIG_ARGS_SIZE: usize = 26;
+
+/// Size of the step-up arguments (after the timelock mints, before the step-up mints).
+const STEP_UP_ARGS_SIZE: usize = 41;
+
+/// Size of the security limit arguments (after the step-up mints).
+const LIMITS_ARGS_SIZE: usize = 25;
+
+/// Size of the emergency freeze arguments (after the security limits).
+const FREEZE_ARGS_SIZE: usize = 40;
+
+/// Process the SetWalletConfig instruction.
+///
+/// Writes the wallet's Wallet Config PDA `["wallet_config", wallet]`,
+/// creating it on first use. Every field must be within its program-wide
+/// bound (`WalletConfigAccount::validate`). The account is resized to fit the
+/// sensitive program, timelock mint and step-up mint lists; extra rent is paid by the payer
+/// and excess rent is refunded to it.
+///
+/// Security limits that differ from the program defaults are mirrored as
+/// `WALLET_FLAG_LIMITS`, so processors cannot skip them by omitting the config.
+///
+/// Only an Owner can change the wallet configuration. While the execution
+/// timelock is enabled it can only be tightened (`TimelockRequired`
+/// otherwise), so a single compromised Owner cannot switch it off. A frozen
+/// wallet can only have its guardian cleared or replaced, with every other
+/// field resubmitted unchanged (`WalletFrozen` otherwise): the unfreeze delay
+/// and a pending unfreeze hold until it is unfrozen, but a misbehaving
+/// guardian cannot keep the wallet frozen.
+///
+/// # Accounts:
+/// 1. `[signer, writable]` Payer
+/// 2. `[writable]` Wallet PDA (`WALLET_FLAG_TIMELOCK` / `WALLET_FLAG_STEP_UP` /
+///    `WALLET_FLAG_LIMITS` updated)
+/// 3. `[writable]` Owner Authority PDA (counter incremented for Secp256r1)
+/// 4. `[writable]` Wallet Config PDA (created if empty)
+/// 5. `[]` System Program
+/// 6. `[optional]` Auth extra (Ed25519: signer keypair | Secp256r1: sysvar_instructions)
+///
+/// # Instruction Data (after discriminator):
+///   [max_deferred_expiry_slots(8)][proposal_threshold(1)][timelock_slots(8)]
+///   [timelock_max_lamports(8)][sensitive_program_count(1)][sensitive_programs(32 × N)]
+///   [timelock_mint_count(1)][timelock_mints((mint(32) + max_amount(8)) × K)]
+///   [step_up_authority(32)][step_up_lamports(8)][step_up_mint_count(1)]
+///   [step_up_mints((mint(32) + max_amount(8)) × M)][max_signature_age_slots(8)]
+///   [max_session_slots(8)][min_deferred_expiry_slots(8)][flags(1)][guardian(32)]
+///   [unfreeze_delay_slots(8)][auth_payload(variable)]
+pub fn process_set_wallet_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() < CONFIG_ARGS_SIZE {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let programs_end = CONFIG_ARGS_SIZE + instruction_data[CONFIG_ARGS_SIZE - 1] as usize * 32;
+    let timelock_mint_count = *instruction_data
+        .get(programs_end)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let timelock_mints_end =
+        programs_end + 1 + timelock_mint_count as usize * STEP_UP_MINT_ENTRY_SIZE;
+    let step_up_args = instruction_data
+        .get(timelock_mints_end..timelock_mints_end + STEP_UP_ARGS_SIZE)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let mints_end = timelock_mints_end
+        + STEP_UP_ARGS_SIZE
+        + step_up_args[STEP_UP_ARGS_SIZE - 1] as usize * STEP_UP_MINT_ENTRY_SIZE;
+    let limits_end = mints_end + LIMITS_ARGS_SIZE;
+    let freeze_end = limits_end + FREEZE_ARGS_SIZE;
+    if instruction_data.len() < freeze_end {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (config_args, authority_payload) = instruction_data.split_at(freeze_end);
+    let sensitive_programs = &config_args[CONFIG_ARGS_SIZE..programs_end];
+    let timelock_mints = &config_args[programs_end + 1..timelock_mints_end];
+    let step_up_mints = &config_args[timelock_mints_end + STEP_UP_ARGS_SIZE..mints_end];
+    let limits_args = &config_args[mints_end..limits_end];
+    let freeze_args = &config_args[limits_end..];
+
+    let payer = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let wallet_pda = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let owner_auth_pda = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let config_pda = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Validate payer is signer
+    if !payer.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !sol_assert_bytes_eq(
+        system_program.key().as_ref(),
+        &crate::utils::SYSTEM_PROGRAM_ID,
+        32,
+    ) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !wallet_pda.is_writable() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let frozen =
+        wallet_flags(unsafe { wallet_pda.borrow_data_unchecked() }) & WALLET_FLAG_FROZEN != 0;
+
+    // Only an Owner (0) can change wallet-level limits
+    {
+        let owner_data = unsafe { owner_auth_pda.borrow_data_unchecked() };
+        if owner_data.len() < std::mem::size_of::<AuthorityAccountHeader>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let owner_header = unsafe {
+            std::ptr::read_unaligned(owner_data.as_ptr() as *const AuthorityAccountHeader)
+        };
+        if owner_header.role != 0 {
+            return Err(AuthError::PermissionDenied.into());
+        }
+    }
+
+    // Bind the signature to the payer (rent source), the config PDA and the values.
+    let mut data_payload = Vec::with_capacity(64 + config_args.len());
+    data_payload.extend_from_slice(payer.key().as_ref());
+    data_payload.extend_from_slice(config_pda.key().as_ref());
+    data_payload.extend_from_slice(config_args);
+
+    // Derive the Wallet Config PDA; its current values are the wallet's limits
+    let (config_key, bump) =
+        find_program_address(&[b"wallet_config", wallet_pda.key().as_ref()], program_id);
+    if !sol_assert_bytes_eq(config_pda.key().as_ref(), config_key.as_ref(), 32) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !config_pda.data_is_empty() && config_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let current = read_wallet_config(
+        unsafe { config_pda.borrow_data_unchecked() },
+        wallet_pda.key(),
+    )?;
+
+    authenticate_admin(
+        program_id,
+        accounts,
+        wallet_pda,
+        owner_auth_pda,
+        &WalletLimits::new(wallet_pda, accounts, program_id).with_config(Some(&current.account)),
+        authority_payload,
+        &data_payload,
+        20,
+    )?;
+
+    let account = WalletConfigAccount {
+        discriminator: AccountDiscriminator::WalletConfig as u8,
+        bump,
+        version: CURRENT_ACCOUNT_VERSION,
+        proposal_threshold: config_args[8],
+        sensitive_program_count: config_args[CONFIG_ARGS_SIZE - 1],
+        step_up_mint_count: step_up_args[STEP_UP_ARGS_SIZE - 1],
+        flags: limits_args[24],
+        timelock_mint_count,
+        wallet: *wallet_pda.key(),
+        max_deferred_expiry_slots: u64::from_le_bytes(config_args[0..8].try_into().unwrap()),
+        timelock_slots: u64::from_le_bytes(config_args[9..17].try_into().unwrap()),
+        timelock_max_lamports: u64::from_le_bytes(config_args[17..25].try_into().unwrap()),
+        step_up_authority: step_up_args[0..32].try_into().unwrap(),
+        step_up_lamports: u64::from_le_bytes(step_up_args[32..40].try_into().unwrap()),
+        max_signature_age_slots: u64::from_le_bytes(limits_args[0..8].try_into().unwrap()),
+        max_session_slots: u64::from_le_bytes(limits_args[8..16].try_into().unwrap()),
+        min_deferred_expiry_slots: u64::from_le_bytes(limits_args[16..24].try_into().unwrap()),
+        guardian: freeze_args[0..32].try_into().unwrap(),
+        unfreeze_delay_slots: u64::from_le_bytes(freeze_args[32..40].try_into().unwrap()),
+        unfreeze_at: 0,
+    };
+    account.validate()?;
+    let mut config = WalletConfigView {
+        account,
+        sensitive_programs,
+        timelock_mints,
+        step_up_mints,
+    };
+
+    let space = WALLET_CONFIG_HEADER_SIZE
+        + sensitive_programs.len()
+        + timelock_mints.len()
+        + step_up_mints.len();
+    let rent = Rent::get()?;
+    let required = rent.minimum_balance(space);
+
+    if config_pda.data_is_empty() {
+        // A frozen wallet without a config has no guardian to change
+        if frozen {
+            return Err(AuthError::WalletFrozen.into());
+        }
+        let bump_arr = [bump];
+        let seeds = [
+            Seed::from(b"wallet_config"),
+            Seed::from(wallet_pda.key().as_ref()),
+            Seed::from(&bump_arr),
+        ];
+        initialize_pda_account(
+            payer,
+            config_pda,
+            system_program,
+            space,
+            required,
+            program_id,
+            &seeds,
+        )?;
+    } else {
+        if frozen {
+            config.account.unfreeze_at = current.account.unfreeze_at;
+            if !only_guardian_changed(&current, &config) {
+                return Err(AuthError::WalletFrozen.into());
+            }
+        }
+        if current.account.timelock_enabled() && !timelock_not_weakened(&current, &config) {
+            return Err(AuthError::TimelockRequired.into());
+        }
+
+        // Resize and rebalance rent
+        let current_lamports = config_pda.lamports();
+        if required > current_lamports {
+            transfer_lamports(
+                payer,
+                config_pda,
+                system_program,
+                required - current_lamports,
+            )?;
+        }
+        config_pda.resize(space)?;
+        if current_lamports > required {
+            let payer_lamports = payer.lamports();
+            unsafe {
+                *payer.borrow_mut_lamports_unchecked() = payer_lamports
+                    .checked_add(current_lamports - required)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+                *config_pda.borrow_mut_lamports_unchecked() = required;
+            }
+        }
+    }
+
+    let data = unsafe { config_pda.borrow_mut_data_unchecked() };
+    unsafe {
+        std::ptr::write_unaligned(
+            data.as_mut_ptr() as *mut WalletConfigAccount,
+            config.account,
+        );
+    }
+    let (programs_data, mints_data) =
+        data[WALLET_CONFIG_HEADER_SIZE..].split_at_mut(sensitive_programs.len());
+    let (timelock_mints_data, step_up_mints_data) = mints_data.split_at_mut(timelock_mints.len());
+    programs_data.copy_from_slice(sensitive_programs);
+    timelock_mints_data.copy_from_slice(timelock_mints);
+    step_up_mints_data.copy_from_slice(step_up_mints);
+
+    // Mirror the execution policies and limits on the wallet, so processors cannot skip the config
+    let wallet_data = unsafe { wallet_pda.borrow_mut_data_unchecked() };
+    let flags = &mut wallet_data[std::mem::offset_of!(WalletAccount, flags)];
+    *flags &= !(WALLET_FLAG_TIMELOCK | WALLET_FLAG_STEP_UP | WALLET_FLAG_LIMITS);
+    if config.account.timelock_enabled() {
+        *flags |= WALLET_FLAG_TIMELOCK;
+    }
+    if config.account.step_up_enabled() {
+        *flags |= WALLET_FLAG_STEP_UP;
+    }
+    if config.account.overrides_limits() {
+        *flags |= WALLET_FLAG_LIMITS;
+    }
+
+    Ok(())
+}
@@ -1,10 +1,23 @@
 //! Session action types for permission enforcement.
 //!
 //! Actions are optional, immutable permission rules attached to sessions at creation time.
-//! They are stored as a flat byte buffer appended after the 80-byte SessionAccount header.
+//! They are stored as a flat byte buffer appended after the 144-byte SessionAccount header.
 //!
 //! Each action has an 11-byte header: [type: u8][data_len: u16 LE][expires_at: u64 LE]
 //! followed by type-specific data bytes.
+//!
+//! Versioning: a buffer may start with `[ACTION_BUFFER_MAGIC][version: u8]`. Buffers
+//! without it are version 0, where every action is critical. From version 1 the
+//! type byte is `[critical: 1 bit][type: 7 bits]`: an action type this binary does
+//! not know is rejected if critical and skipped if advisory, so new rule types can
+//! ship before every deployment understands them.
+//!
+//! `parse_actions` fills an `ActionList` with one bounded heap allocation
+//! (`MAX_ACTIONS` compact entries), keeping the parse off the 4KB BPF stack
+//! frame. Entries of the repeatable list types (`ProgramWhitelist`,
+//! `ProgramBlacklist`, `PayerWhitelist`) may come in any order; clients that
+//! store them strictly ascending by key let `ActionList::has_active_key`
+//! binary-search them.
 
 use pinocchio::program_error::ProgramError;
 
@@ -15,8 +28,33 @@ use crate::error::AuthError;
 /// Size of each action header in bytes.
 pub const ACTION_HEADER_SIZE: usize = 11;
 
+/// First byte of a versioned actions buffer. Never a valid action type byte
+/// in a version 0 buffer.
+pub const ACTION_BUFFER_MAGIC: u8 = 0xFF;
+
+/// Size of the optional `[magic][version]` buffer header.
+pub const ACTION_BUFFER_HEADER_SIZE: usize = 2;
+
+/// Highest actions buffer version this binary understands.
+pub const ACTION_BUFFER_VERSION: u8 = 1;
+
+/// Version 1+ type byte bit: reject the action if its type is unknown.
+pub const ACTION_FLAG_CRITICAL: u8 = 0x80;
+
+/// Version 1+ type byte bits holding the action type.
+pub const ACTION_TYPE_MASK: u8 = 0x7F;
+
 /// Maximum number of actions per session.
-pub const MAX_ACTIONS: usize = 16;
+///
+/// Bounds the `ActionList` allocation (16 bytes per entry, 1KB at most).
+pub const MAX_ACTIONS: usize = 64;
+
+/// Maximum size of a session or template actions buffer.
+///
+/// Larger than fits in one transaction: buffers above ~900 bytes are built with
+/// `CreateSession` (staged) + `AppendSessionActions`. Merged template buffers are
+/// copied to the 32KB BPF heap, so this stays well below it.
+pub const MAX_ACTIONS_BUFFER_SIZE: usize = 4096;
 
 // ─── Action Types ─────────────────────────────────────────────────────
 
@@ -35,10 +73,16 @@ pub enum ActionType {
     TokenRecurringLimit = 5,
     /// Maximum tokens per single execute per mint. Data: {mint: [u8;32], max: u64}
     TokenMaxPerTx = 6,
+    /// Rolling-window SOL cap. Data: {limit, window, last_bucket, buckets: [u64; 9]}
+    SolSlidingLimit = 7,
+    /// Rolling-window token cap per mint. Data: {mint, limit, window, last_bucket, buckets}
+    TokenSlidingLimit = 8,
     /// Allow CPI only to this program. Repeatable. Data: {program_id: [u8;32]}
     ProgramWhitelist = 10,
     /// Block CPI to this program. Repeatable. Data: {program_id: [u8;32]}
     ProgramBlacklist = 11,
+    /// Allow Execute only with this fee payer (`accounts[0]`). Repeatable. Data: {payer: [u8;32]}
+    PayerWhitelist = 20,
 }
 
 impl ActionType {
@@ -50,8 +94,11 @@ impl ActionType {
             4 => Ok(Self::TokenLimit),
             5 => Ok(Self::TokenRecurringLimit),
             6 => Ok(Self::TokenMaxPerTx),
+            7 => Ok(Self::SolSlidingLimit),
+            8 => Ok(Self::TokenSlidingLimit),
             10 => Ok(Self::ProgramWhitelist),
             11 => Ok(Self::ProgramBlacklist),
+            20 => Ok(Self::PayerWhitelist),
             _ => Err(AuthError::ActionBufferInvalid.into()),
         }
     }
@@ -65,8 +112,11 @@ impl ActionType {
             Self::TokenLimit => TOKEN_LIMIT_SIZE,
             Self::TokenRecurringLimit => TOKEN_RECURRING_LIMIT_SIZE,
             Self::TokenMaxPerTx => TOKEN_MAX_PER_TX_SIZE,
+            Self::SolSlidingLimit => SOL_SLIDING_LIMIT_SIZE,
+            Self::TokenSlidingLimit => TOKEN_SLIDING_LIMIT_SIZE,
             Self::ProgramWhitelist => PROGRAM_WHITELIST_SIZE,
             Self::ProgramBlacklist => PROGRAM_BLACKLIST_SIZE,
+            Self::PayerWhitelist => PAYER_WHITELIST_SIZE,
         }
     }
 }
@@ -79,14 +129,28 @@ pub const SOL_MAX_PER_TX_SIZE: usize = 8;
 pub const TOKEN_LIMIT_SIZE: usize = 40;
 pub const TOKEN_RECURRING_LIMIT_SIZE: usize = 64;
 pub const TOKEN_MAX_PER_TX_SIZE: usize = 40;
+pub const SOL_SLIDING_LIMIT_SIZE: usize = SLIDING_WINDOW_SIZE;
+pub const TOKEN_SLIDING_LIMIT_SIZE: usize = 32 + SLIDING_WINDOW_SIZE;
+
+/// Sub-buckets per sliding window. `window` must be a non-zero multiple of this.
+pub const SLIDING_SUB_BUCKETS: u64 = 8;
+
+/// Buckets kept in the ring: the current one plus the `SLIDING_SUB_BUCKETS`
+/// before it. Two spends less than `window` slots apart are at most
+/// `SLIDING_SUB_BUCKETS` buckets apart, so both are always counted.
+pub const SLIDING_RING_LEN: usize = SLIDING_SUB_BUCKETS as usize + 1;
+
+/// `[limit][window][last_bucket][buckets; SLIDING_RING_LEN]`, all u64.
+pub const SLIDING_WINDOW_SIZE: usize = 24 + 8 * SLIDING_RING_LEN;
 pub const PROGRAM_WHITELIST_SIZE: usize = 32;
 pub const PROGRAM_BLACKLIST_SIZE: usize = 32;
+pub const PAYER_WHITELIST_SIZE: usize = 32;
 
 // ─── Action View (zero-copy index into buffer) ───────────────────────
 
 /// A parsed reference to an action within the session data buffer.
 /// Does not own data — just indexes into the buffer.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct ActionView {
     pub action_type: ActionType,
     pub expires_at: u64,
@@ -96,22 +160,165 @@ pub struct ActionView {
     pub data_len: usize,
 }
 
+/// Compact entry backing an `ActionView` (16 bytes instead of 32).
+#[derive(Debug, Clone, Copy)]
+pub struct ActionEntry {
+    expires_at: u64,
+    data_offset: u16,
+    data_len: u16,
+    action_type: ActionType,
+}
+
+impl ActionEntry {
+    #[inline]
+    fn view(&self) -> ActionView {
+        ActionView {
+            action_type: self.action_type,
+            expires_at: self.expires_at,
+            data_offset: self.data_offset as usize,
+            data_len: self.data_len as usize,
+        }
+    }
+}
+
+/// List of parsed actions, allocated once with capacity `MAX_ACTIONS`.
+pub struct ActionList {
+    entries: Vec<ActionEntry>,
+    version: u8,
+}
+
+impl ActionList {
+    /// Actions buffer version (0 = legacy buffer without a version header).
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Number of enforced actions. Skipped advisory actions are not listed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<ActionView> {
+        self.entries.get(index).map(ActionEntry::view)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> ActionIter<'_> {
+        self.entries.iter().map(ActionEntry::view)
+    }
+
+    /// Returns true if an `action_type` list entry whose 32-byte key equals
+    /// `key` is active at `current_slot`.
+    ///
+    /// Strictly ascending lists are binary-searched. Unsorted lists, and lists
+    /// with duplicate keys, are scanned in full: every matching entry is checked,
+    /// so an expired duplicate never hides an active one.
+    pub fn has_active_key(
+        &self,
+        buf: &[u8],
+        action_type: ActionType,
+        key: &[u8],
+        current_slot: u64,
+    ) -> bool {
+        let mut indices = [0u8; MAX_ACTIONS];
+        let mut count = 0;
+        let mut sorted = true;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.action_type != action_type {
+                continue;
+            }
+            if count > 0 {
+                let prev = self.entries[indices[count - 1] as usize].data_offset as usize;
+                let curr = entry.data_offset as usize;
+                if buf[prev..prev + 32] >= buf[curr..curr + 32] {
+                    sorted = false;
+                }
+            }
+            indices[count] = i as u8;
+            count += 1;
+        }
+
+        let key_of = |idx: u8| {
+            let offset = self.entries[idx as usize].data_offset as usize;
+            &buf[offset..offset + 32]
+        };
+        let is_active = |idx: u8| {
+            let expires_at = self.entries[idx as usize].expires_at;
+            expires_at == 0 || current_slot <= expires_at
+        };
+        if sorted {
+            indices[..count]
+                .binary_search_by(|&idx| key_of(idx).cmp(key))
+                .is_ok_and(|pos| is_active(indices[pos]))
+        } else {
+            indices[..count]
+                .iter()
+                .any(|&idx| key_of(idx) == key && is_active(idx))
+        }
+    }
+}
+
+pub type ActionIter<'a> =
+    core::iter::Map<core::slice::Iter<'a, ActionEntry>, fn(&ActionEntry) -> ActionView>;
+
+impl<'a> IntoIterator for &'a ActionList {
+    type Item = ActionView;
+    type IntoIter = ActionIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 // ─── Buffer Parsing ───────────────────────────────────────────────────
 
 /// Parse all actions from a raw actions buffer.
 ///
-/// The buffer starts immediately after the 80-byte session header.
-/// Returns a Vec of ActionViews indexing into the buffer.
-pub fn parse_actions(buf: &[u8]) -> Result<Vec<ActionView>, ProgramError> {
-    let mut actions = Vec::new();
-    let mut cursor = 0;
+/// The buffer starts immediately after the 144-byte session header.
+/// Returns an `ActionList` indexing into the buffer, carrying
+/// the buffer version. Unknown advisory actions (version 1+) are skipped; an
+/// unknown critical action fails with `ActionUnsupported`.
+pub fn parse_actions(buf: &[u8]) -> Result<ActionList, ProgramError> {
+    if buf.len() > MAX_ACTIONS_BUFFER_SIZE {
+        return Err(AuthError::ActionBufferInvalid.into());
+    }
+
+    let (version, mut cursor) = match buf.first() {
+        Some(&ACTION_BUFFER_MAGIC) => {
+            let version = *buf.get(1).ok_or(AuthError::ActionBufferInvalid)?;
+            if version == 0 || version > ACTION_BUFFER_VERSION {
+                return Err(AuthError::ActionUnsupported.into());
+            }
+            (version, ACTION_BUFFER_HEADER_SIZE)
+        },
+        _ => (0, 0),
+    };
+
+    let mut actions = ActionList {
+        entries: Vec::with_capacity(MAX_ACTIONS),
+        version,
+    };
+    // Counts every header, including skipped ones, to bound the parse.
+    let mut parsed = 0;
 
     while cursor < buf.len() {
         if cursor + ACTION_HEADER_SIZE > buf.len() {
             return Err(AuthError::ActionBufferInvalid.into());
         }
+        if parsed == MAX_ACTIONS {
+            return Err(AuthError::ActionBufferInvalid.into());
+        }
+        parsed += 1;
 
-        let action_type = ActionType::from_u8(buf[cursor])?;
+        let type_byte = buf[cursor];
         let data_len = u16::from_le_bytes([buf[cursor + 1], buf[cursor + 2]]) as usize;
         let expires_at = u64::from_le_bytes(
             buf[cursor + 3..cursor + 11]
@@ -123,19 +330,28 @@ pub fn parse_actions(buf: &[u8]) -> Result<Vec<ActionView>, ProgramError> {
         if data_offset + data_len > buf.len() {
             return Err(AuthError::ActionBufferInvalid.into());
         }
+        cursor = data_offset + data_len;
 
-        actions.push(ActionView {
-            action_type,
+        let action_type = if version == 0 {
+            ActionType::from_u8(type_byte)?
+        } else {
+            match ActionType::from_u8(type_byte & ACTION_TYPE_MASK) {
+                Ok(action_type) => action_type,
+                Err(_) if type_byte & ACTION_FLAG_CRITICAL != 0 => {
+                    return Err(AuthError::ActionUnsupported.into())
+                },
+                // Unknown advisory action: not enforced by this binary.
+                Err(_) => continue,
+            }
+        };
+
+        // Offsets fit in u16: the buffer is capped at MAX_ACTIONS_BUFFER_SIZE.
+        actions.entries.push(ActionEntry {
             expires_at,
-            data_offset,
-            data_len,
+            data_offset: data_offset as u16,
+            data_len: data_len as u16,
+            action_type,
         });
-
-        cursor = data_offset + data_len;
-
-        if actions.len() > MAX_ACTIONS {
-            return Err(AuthError::ActionBufferInvalid.into());
-        }
     }
 
     Ok(actions)
@@ -149,9 +365,13 @@ pub fn parse_actions(buf: &[u8]) -> Result<Vec<ActionView>, ProgramError> {
 /// - No simultaneous ProgramWhitelist + ProgramBlacklist
 /// - Buffer is fully consumed (no trailing bytes)
 /// - Not exceeding MAX_ACTIONS
-pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
+/// - Version supported, no unknown critical actions
+///
+/// Returns the buffer version. Only known actions are checked; skipped
+/// advisory actions are opaque to this binary.
+pub fn validate_actions_buffer(buf: &[u8]) -> Result<u8, ProgramError> {
     if buf.is_empty() {
-        return Ok(());
+        return Ok(0);
     }
 
     let actions = parse_actions(buf)?;
@@ -178,8 +398,15 @@ pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
     let mut has_sol_limit = false;
     let mut has_sol_recurring = false;
     let mut has_sol_max_per_tx = false;
+    let mut has_sol_sliding = false;
     for action in &actions {
         match action.action_type {
+            ActionType::SolSlidingLimit => {
+                if has_sol_sliding {
+                    return Err(AuthError::ActionBufferInvalid.into());
+                }
+                has_sol_sliding = true;
+            },
             ActionType::SolLimit => {
                 if has_sol_limit {
                     return Err(AuthError::ActionBufferInvalid.into());
@@ -209,17 +436,18 @@ pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
             ActionType::TokenLimit,
             ActionType::TokenRecurringLimit,
             ActionType::TokenMaxPerTx,
+            ActionType::TokenSlidingLimit,
         ];
         for token_type in &token_types {
-            let token_actions: Vec<&ActionView> = actions
-                .iter()
-                .filter(|a| &a.action_type == token_type)
-                .collect();
-            for i in 0..token_actions.len() {
-                for j in (i + 1)..token_actions.len() {
-                    let mint_a = &buf[token_actions[i].data_offset..token_actions[i].data_offset + 32];
-                    let mint_b = &buf[token_actions[j].data_offset..token_actions[j].data_offset + 32];
-                    if mint_a == mint_b {
+            for (i, a) in actions.iter().enumerate() {
+                if &a.action_type != token_type {
+                    continue;
+                }
+                let mint_a = &buf[a.data_offset..a.data_offset + 32];
+                for b in actions.iter().skip(i + 1) {
+                    if &b.action_type == token_type
+                        && mint_a == &buf[b.data_offset..b.data_offset + 32]
+                    {
                         return Err(AuthError::ActionBufferInvalid.into());
                     }
                 }
@@ -247,6 +475,24 @@ pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
                 return Err(AuthError::ActionBufferInvalid.into());
             }
         }
+        let sliding_offset = match action.action_type {
+            ActionType::SolSlidingLimit => Some(0),
+            ActionType::TokenSlidingLimit => Some(32),
+            _ => None,
+        };
+        if let Some(offset) = sliding_offset {
+            let start = action.data_offset + offset;
+            let data = &buf[start..start + SLIDING_WINDOW_SIZE];
+            // window must be a non-zero multiple of the sub-bucket count
+            let window = read_u64(data, 8);
+            if window == 0 || window % SLIDING_SUB_BUCKETS != 0 {
+                return Err(AuthError::ActionBufferInvalid.into());
+            }
+            // last_bucket and every bucket must start at 0
+            if data[16..].iter().any(|&b| b != 0) {
+                return Err(AuthError::ActionBufferInvalid.into());
+            }
+        }
         if action.action_type == ActionType::TokenRecurringLimit {
             let data = &buf[action.data_offset..action.data_offset + action.data_len];
             // spent must be 0 (bytes 40..48)
@@ -267,7 +513,7 @@ pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
         }
     }
 
-    Ok(())
+    Ok(actions.version())
 }
 
 // ─── Data Layout Helpers ──────────────────────────────────────────────
@@ -290,8 +536,15 @@ pub fn validate_actions_buffer(buf: &[u8]) -> Result<(), ProgramError> {
 // TokenMaxPerTx: [mint: [u8;32]][max: u64] = 40 bytes
 // Offsets: mint = 0..32, max = 32..40
 
+// SolSlidingLimit: [limit: u64][window: u64][last_bucket: u64][buckets: [u64; 9]] = 96 bytes
+// Offsets: limit = 0..8, window = 8..16, last_bucket = 16..24, buckets = 24..96
+
+// TokenSlidingLimit: [mint: [u8;32]] + SolSlidingLimit layout = 128 bytes
+// Offsets: mint = 0..32, limit = 32..40, window = 40..48, last_bucket = 48..56, buckets = 56..128
+
 // ProgramWhitelist: [program_id: [u8;32]] = 32 bytes
 // ProgramBlacklist: [program_id: [u8;32]] = 32 bytes
+// PayerWhitelist: [payer: [u8;32]] = 32 bytes
 
 /// Read a u64 from a byte slice at the given offset (LE).
 #[inline(always)]
@@ -305,6 +558,59 @@ pub fn write_u64(data: &mut [u8], offset: usize, value: u64) {
     data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
 }
 
+// ─── Sliding Window Helpers ───────────────────────────────────────────
+//
+// `data` starts at the sliding window's `limit` field. Buckets are
+// `window / SLIDING_SUB_BUCKETS` slots wide; bucket `b` (absolute index
+// `slot / width`) lives at ring position `b % SLIDING_RING_LEN`.
+
+/// Total spent in the buckets that still fall within the window at `current_slot`.
+pub fn sliding_window_spent(data: &[u8], current_slot: u64) -> u64 {
+    let width = read_u64(data, 8) / SLIDING_SUB_BUCKETS;
+    if width == 0 {
+        return 0;
+    }
+    let current_bucket = current_slot / width;
+    let last_bucket = read_u64(data, 16);
+
+    let mut total: u64 = 0;
+    for k in 0..SLIDING_RING_LEN as u64 {
+        let Some(bucket) = last_bucket.checked_sub(k) else {
+            break;
+        };
+        if current_bucket.saturating_sub(bucket) > SLIDING_SUB_BUCKETS {
+            break;
+        }
+        let pos = (bucket % SLIDING_RING_LEN as u64) as usize;
+        total = total.saturating_add(read_u64(data, 24 + pos * 8));
+    }
+    total
+}
+
+/// Clears buckets that fell out of the window, then adds `amount` to the
+/// bucket for `current_slot`.
+pub fn sliding_window_record(data: &mut [u8], current_slot: u64, amount: u64) {
+    let width = read_u64(data, 8) / SLIDING_SUB_BUCKETS;
+    if width == 0 {
+        return;
+    }
+    let current_bucket = current_slot / width;
+    let last_bucket = read_u64(data, 16);
+
+    if current_bucket > last_bucket {
+        let stale = (current_bucket - last_bucket).min(SLIDING_RING_LEN as u64);
+        for k in 0..stale {
+            let pos = ((current_bucket - k) % SLIDING_RING_LEN as u64) as usize;
+            write_u64(data, 24 + pos * 8, 0);
+        }
+        write_u64(data, 16, current_bucket);
+    }
+
+    let pos = (current_bucket.max(last_bucket) % SLIDING_RING_LEN as u64) as usize;
+    let spent = read_u64(data, 24 + pos * 8);
+    write_u64(data, 24 + pos * 8, spent.saturating_add(amount));
+}
+
 // ─── Tests ────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -332,9 +638,9 @@ mod tests {
         let buf = build_action(1, 0, &data);
         let actions = parse_actions(&buf).unwrap();
         assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, ActionType::SolLimit);
-        assert_eq!(actions[0].expires_at, 0);
-        assert_eq!(actions[0].data_len, 8);
+        assert_eq!(actions.get(0).unwrap().action_type, ActionType::SolLimit);
+        assert_eq!(actions.get(0).unwrap().expires_at, 0);
+        assert_eq!(actions.get(0).unwrap().data_len, 8);
     }
 
     #[test]
@@ -347,8 +653,11 @@ mod tests {
         let buf = build_action(2, 0, &data);
         let actions = parse_actions(&buf).unwrap();
         assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0].action_type, ActionType::SolRecurringLimit);
-        assert_eq!(actions[0].data_len, 32);
+        assert_eq!(
+            actions.get(0).unwrap().action_type,
+            ActionType::SolRecurringLimit
+        );
+        assert_eq!(actions.get(0).unwrap().data_len, 32);
     }
 
     #[test]
@@ -359,8 +668,14 @@ mod tests {
         buf.extend_from_slice(&build_action(10, 0, &prog2));
         let actions = parse_actions(&buf).unwrap();
         assert_eq!(actions.len(), 2);
-        assert_eq!(actions[0].action_type, ActionType::ProgramWhitelist);
-        assert_eq!(actions[1].action_type, ActionType::ProgramWhitelist);
+        assert_eq!(
+            actions.get(0).unwrap().action_type,
+            ActionType::ProgramWhitelist
+        );
+        assert_eq!(
+            actions.get(1).unwrap().action_type,
+            ActionType::ProgramWhitelist
+        );
     }
 
     #[test]
@@ -372,8 +687,11 @@ mod tests {
         buf.extend_from_slice(&build_action(10, 0, &[0xAA; 32]));
         let actions = parse_actions(&buf).unwrap();
         assert_eq!(actions.len(), 2);
-        assert_eq!(actions[0].action_type, ActionType::SolMaxPerTx);
-        assert_eq!(actions[1].action_type, ActionType::ProgramWhitelist);
+        assert_eq!(actions.get(0).unwrap().action_type, ActionType::SolMaxPerTx);
+        assert_eq!(
+            actions.get(1).unwrap().action_type,
+            ActionType::ProgramWhitelist
+        );
     }
 
     #[test]
@@ -381,7 +699,7 @@ mod tests {
         let data = 1_000_000u64.to_le_bytes();
         let buf = build_action(1, 5000, &data);
         let actions = parse_actions(&buf).unwrap();
-        assert_eq!(actions[0].expires_at, 5000);
+        assert_eq!(actions.get(0).unwrap().expires_at, 5000);
     }
 
     #[test]
@@ -478,7 +796,10 @@ mod tests {
         data.extend_from_slice(&1_000_000u64.to_le_bytes()); // max
         let buf = build_action(6, 0, &data);
         let actions = parse_actions(&buf).unwrap();
-        assert_eq!(actions[0].action_type, ActionType::TokenMaxPerTx);
+        assert_eq!(
+            actions.get(0).unwrap().action_type,
+            ActionType::TokenMaxPerTx
+        );
         assert!(validate_actions_buffer(&buf).is_ok());
     }
 
@@ -512,7 +833,7 @@ mod tests {
     #[test]
     fn test_validate_duplicate_token_recurring_same_mint() {
         let mint = [0xBB; 32];
-        let mut make_data = || {
+        let make_data = || {
             let mut data = Vec::new();
             data.extend_from_slice(&mint);
             data.extend_from_slice(&1_000_000u64.to_le_bytes()); // limit
@@ -647,6 +968,12 @@ mod tests {
         assert!(validate_actions_buffer(&buf).is_err());
     }
 
+    #[test]
+    fn test_validate_action_type_19_rejected() {
+        let buf = build_action(19, 0, &[0u8; 32]);
+        assert!(validate_actions_buffer(&buf).is_err());
+    }
+
     #[test]
     fn test_validate_action_type_255_rejected() {
         let buf = build_action(255, 0, &[0u8; 8]);
@@ -669,7 +996,7 @@ mod tests {
             prog[0] = i as u8;
             buf.extend_from_slice(&build_action(10, 0, &prog)); // ProgramWhitelist
         }
-        // 17 actions should fail (MAX_ACTIONS = 16)
+        // MAX_ACTIONS + 1 actions should fail
         assert!(validate_actions_buffer(&buf).is_err());
     }
 
@@ -681,10 +1008,181 @@ mod tests {
             prog[0] = i as u8;
             buf.extend_from_slice(&build_action(10, 0, &prog));
         }
-        // 16 actions should be fine
+        // MAX_ACTIONS actions should be fine
         assert!(validate_actions_buffer(&buf).is_ok());
     }
 
+    #[test]
+    fn test_validate_unsorted_and_duplicate_lists_ok() {
+        // Clients may list keys in any order, interleaved with other actions.
+        let mut buf = build_action(10, 0, &[2u8; 32]);
+        buf.extend_from_slice(&build_action(20, 0, &[9u8; 32]));
+        buf.extend_from_slice(&build_action(3, 0, &500_000u64.to_le_bytes()));
+        buf.extend_from_slice(&build_action(10, 0, &[1u8; 32]));
+        buf.extend_from_slice(&build_action(20, 0, &[9u8; 32]));
+        assert!(validate_actions_buffer(&buf).is_ok());
+    }
+
+    #[test]
+    fn test_has_active_key_sorted_and_unsorted() {
+        let mut sorted = Vec::new();
+        for k in [1u8, 4, 7, 9] {
+            sorted.extend_from_slice(&build_action(10, k as u64 * 100, &[k; 32]));
+        }
+        let actions = parse_actions(&sorted).unwrap();
+        let list = ActionType::ProgramWhitelist;
+        assert!(actions.has_active_key(&sorted, list, &[7u8; 32], 700));
+        assert!(!actions.has_active_key(&sorted, list, &[7u8; 32], 701));
+        assert!(!actions.has_active_key(&sorted, list, &[5u8; 32], 0));
+        assert!(!actions.has_active_key(&sorted, ActionType::ProgramBlacklist, &[7u8; 32], 0));
+
+        // Unsorted buffers resolve through the linear scan.
+        let mut unsorted = build_action(10, 0, &[9u8; 32]);
+        unsorted.extend_from_slice(&build_action(10, 0, &[1u8; 32]));
+        let actions = parse_actions(&unsorted).unwrap();
+        assert!(actions.has_active_key(&unsorted, list, &[1u8; 32], 500));
+    }
+
+    #[test]
+    fn test_has_active_key_checks_every_duplicate() {
+        // An expired entry ahead of an active one for the same key must not
+        // hide it: the active blacklist entry still matches.
+        let mut buf = build_action(11, 50, &[3u8; 32]);
+        buf.extend_from_slice(&build_action(11, 0, &[3u8; 32]));
+        let actions = parse_actions(&buf).unwrap();
+        assert!(actions.has_active_key(&buf, ActionType::ProgramBlacklist, &[3u8; 32], 100));
+
+        // Both expired: no match.
+        let mut buf = build_action(11, 50, &[3u8; 32]);
+        buf.extend_from_slice(&build_action(11, 60, &[3u8; 32]));
+        let actions = parse_actions(&buf).unwrap();
+        assert!(!actions.has_active_key(&buf, ActionType::ProgramBlacklist, &[3u8; 32], 100));
+    }
+
+    #[test]
+    fn test_parse_rejects_oversized_buffer() {
+        let buf = vec![0u8; MAX_ACTIONS_BUFFER_SIZE + 1];
+        assert!(parse_actions(&buf).is_err());
+    }
+
+    // ─── Sliding windows ──────────────────────────────────────────────
+
+    fn sliding(limit: u64, window: u64) -> Vec<u8> {
+        let mut data = vec![0u8; SOL_SLIDING_LIMIT_SIZE];
+        data[0..8].copy_from_slice(&limit.to_le_bytes());
+        data[8..16].copy_from_slice(&window.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_validate_sliding_limits() {
+        assert!(validate_actions_buffer(&build_action(7, 0, &sliding(1_000, 800))).is_ok());
+        // window must be a non-zero multiple of SLIDING_SUB_BUCKETS
+        assert!(validate_actions_buffer(&build_action(7, 0, &sliding(1_000, 0))).is_err());
+        assert!(validate_actions_buffer(&build_action(7, 0, &sliding(1_000, 801))).is_err());
+        // ring must start empty
+        let mut dirty = sliding(1_000, 800);
+        dirty[24] = 1;
+        assert!(validate_actions_buffer(&build_action(7, 0, &dirty)).is_err());
+
+        let mut token = vec![0xAB; 32];
+        token.extend_from_slice(&sliding(1_000, 800));
+        let mut buf = build_action(8, 0, &token);
+        assert!(validate_actions_buffer(&buf).is_ok());
+        buf.extend_from_slice(&build_action(8, 0, &token)); // same mint twice
+        assert!(validate_actions_buffer(&buf).is_err());
+    }
+
+    #[test]
+    fn test_sliding_window_record_and_expire() {
+        let mut data = sliding(1_000, 80); // buckets of 10 slots
+        sliding_window_record(&mut data, 105, 300); // bucket 10
+        sliding_window_record(&mut data, 139, 200); // bucket 13
+        assert_eq!(sliding_window_spent(&data, 140), 500);
+        // bucket 10 is still counted 8 buckets later (any W-slot span reaching back)
+        assert_eq!(sliding_window_spent(&data, 189), 500);
+        // ...and dropped after that
+        assert_eq!(sliding_window_spent(&data, 190), 200);
+        assert_eq!(sliding_window_spent(&data, 219), 200);
+        assert_eq!(sliding_window_spent(&data, 220), 0);
+
+        // Recording after a long gap clears the whole ring
+        sliding_window_record(&mut data, 10_000, 50);
+        assert_eq!(sliding_window_spent(&data, 10_000), 50);
+        assert_eq!(read_u64(&data, 16), 1_000);
+    }
+
+    // ─── Versioning ───────────────────────────────────────────────────
+
+    fn versioned(actions: &[u8]) -> Vec<u8> {
+        let mut buf = vec![ACTION_BUFFER_MAGIC, ACTION_BUFFER_VERSION];
+        buf.extend_from_slice(actions);
+        buf
+    }
+
+    #[test]
+    fn test_parse_legacy_buffer_is_version_0() {
+        let buf = build_action(3, 0, &500_000u64.to_le_bytes());
+        assert_eq!(parse_actions(&buf).unwrap().version(), 0);
+        assert_eq!(validate_actions_buffer(&buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_versioned_buffer() {
+        let buf = versioned(&build_action(
+            3 | ACTION_FLAG_CRITICAL,
+            0,
+            &500_000u64.to_le_bytes(),
+        ));
+        let actions = parse_actions(&buf).unwrap();
+        assert_eq!(actions.version(), 1);
+        assert_eq!(actions.len(), 1);
+        let action = actions.get(0).unwrap();
+        assert_eq!(action.action_type, ActionType::SolMaxPerTx);
+        assert_eq!(
+            action.data_offset,
+            ACTION_BUFFER_HEADER_SIZE + ACTION_HEADER_SIZE
+        );
+        assert_eq!(validate_actions_buffer(&buf).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_unknown_advisory_action_skipped() {
+        let mut inner = build_action(99, 0, &[0xEE; 5]); // unknown, advisory
+        inner.extend_from_slice(&build_action(1, 0, &1_000u64.to_le_bytes()));
+        let buf = versioned(&inner);
+        let actions = parse_actions(&buf).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions.get(0).unwrap().action_type, ActionType::SolLimit);
+        assert!(validate_actions_buffer(&buf).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_critical_action_rejected() {
+        let buf = versioned(&build_action(99 | ACTION_FLAG_CRITICAL, 0, &[0xEE; 5]));
+        assert_eq!(
+            parse_actions(&buf).err().unwrap(),
+            ProgramError::Custom(AuthError::ActionUnsupported as u32)
+        );
+    }
+
+    #[test]
+    fn test_unknown_action_in_legacy_buffer_rejected() {
+        // Version 0 buffers have no flag bit: everything is critical.
+        let buf = build_action(99, 0, &[0xEE; 5]);
+        assert!(parse_actions(&buf).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_buffer_version_rejected() {
+        let buf = vec![ACTION_BUFFER_MAGIC, ACTION_BUFFER_VERSION + 1];
+        assert_eq!(
+            parse_actions(&buf).err().unwrap(),
+            ProgramError::Custom(AuthError::ActionUnsupported as u32)
+        );
+        assert!(parse_actions(&[ACTION_BUFFER_MAGIC]).is_err());
+    }
+
     // ─── Security: Trailing bytes ─────────────────────────────────────
 
     #[test]
@@ -694,4 +1192,36 @@ mod tests {
         // parse_actions should fail because the trailing byte doesn't form a valid header
         assert!(validate_actions_buffer(&buf).is_err());
     }
+
+    // ─── PayerWhitelist ───────────────────────────────────────────────
+
+    #[test]
+    fn test_parse_payer_whitelist_multiple() {
+        let mut buf = build_action(20, 0, &[1u8; 32]);
+        buf.extend_from_slice(&build_action(20, 0, &[2u8; 32]));
+        let actions = parse_actions(&buf).unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(
+            actions.get(0).unwrap().action_type,
+            ActionType::PayerWhitelist
+        );
+        assert_eq!(
+            actions.get(1).unwrap().action_type,
+            ActionType::PayerWhitelist
+        );
+        assert!(validate_actions_buffer(&buf).is_ok());
+    }
+
+    #[test]
+    fn test_validate_payer_whitelist_wrong_size() {
+        let buf = build_action(20, 0, &[1u8; 8]);
+        assert!(validate_actions_buffer(&buf).is_err());
+    }
+
+    #[test]
+    fn test_validate_payer_whitelist_with_program_whitelist_ok() {
+        let mut buf = build_action(20, 0, &[1u8; 32]);
+        buf.extend_from_slice(&build_action(10, 0, &[2u8; 32]));
+        assert!(validate_actions_buffer(&buf).is_ok());
+    }
 }
@@ -1,5 +1,7 @@
 use no_padding::NoPadding;
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::error::AuthError;
 
 /// Header for all Authority accounts.
 ///
@@ -17,14 +19,118 @@ pub struct AuthorityAccountHeader {
     pub bump: u8,
     /// Account Version (for future upgrades).
     pub version: u8,
+    /// Authority flags (`AUTHORITY_FLAG_*`).
+    pub flags: u8,
     /// Padding for 8-byte alignment.
-    pub _padding1: [u8; 3],
+    pub _padding1: [u8; 2],
     /// Monotonically increasing counter to prevent replay attacks (Secp256r1 only).
+    /// Ed25519 authorities only advance it in `Authorize`, where it seeds the
+    /// DeferredExec PDA.
     /// u32 supports ~4 billion operations per authority — more than sufficient.
     pub counter: u32,
-    /// Alignment padding after u32 counter.
-    pub _padding2: [u8; 4],
+    /// Slot of the last successful `Execute` authenticated by this authority
+    /// (0 = never), saturating at `u32::MAX`. Former alignment padding.
+    pub last_used_slot: u32,
     /// The wallet this authority belongs to.
     pub wallet: Pubkey,
 }
-// 1+1+1+1+1+3+4+4+32 = 48. Divisible by 8. wallet stays at offset 16.
+// 1+1+1+1+1+1+2+4+4+32 = 48. Divisible by 8. wallet stays at offset 16.
+
+/// `AuthorityAccountHeader::flags`: the authority proved possession of its
+/// key when it was registered.
+pub const AUTHORITY_FLAG_VERIFIED: u8 = 1 << 0;
+
+/// `AuthorityAccountHeader::flags`: the authority expires. An
+/// `AuthorityExpiry` follows its key data.
+pub const AUTHORITY_FLAG_EXPIRES: u8 = 1 << 1;
+
+/// Size of an `AuthorityExpiry`.
+pub const AUTHORITY_EXPIRY_SIZE: usize = 40;
+
+/// Expiry of a temporary authority, stored after its key data.
+#[repr(C, align(8))]
+#[derive(NoPadding, Debug, Clone, Copy)]
+pub struct AuthorityExpiry {
+    /// Last slot at which the authority is valid.
+    pub expires_at: u64,
+    /// Payer that funded the account; receives its rent when it is closed
+    /// after expiry.
+    pub funder: Pubkey,
+}
+
+/// Size of the key data stored after the header for `authority_type`: the
+/// Ed25519 pubkey (32 bytes), or the Secp256r1 credential id hash, pubkey
+/// and rpId hash (32 + 33 + 32 bytes).
+pub fn authority_data_len(authority_type: u8) -> Result<usize, ProgramError> {
+    match authority_type {
+        0 => Ok(32),
+        1 => Ok(32 + 33 + 32),
+        _ => Err(AuthError::InvalidAuthenticationKind.into()),
+    }
+}
+
+/// Reads the expiry of an Authority account's data, if it has one.
+pub fn read_authority_expiry(data: &[u8]) -> Result<Option<AuthorityExpiry>, ProgramError> {
+    let header_size = std::mem::size_of::<AuthorityAccountHeader>();
+    if data.len() < header_size {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let header =
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const AuthorityAccountHeader) };
+    if header.flags & AUTHORITY_FLAG_EXPIRES == 0 {
+        return Ok(None);
+    }
+    let offset = header_size + authority_data_len(header.authority_type)?;
+    let expiry = data
+        .get(offset..offset + AUTHORITY_EXPIRY_SIZE)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    Ok(Some(unsafe {
+        std::ptr::read_unaligned(expiry.as_ptr() as *const AuthorityExpiry)
+    }))
+}
+
+/// Whether an Authority account's data has expired at `current_slot`.
+pub fn authority_expired(data: &[u8], current_slot: u64) -> Result<bool, ProgramError> {
+    Ok(read_authority_expiry(data)?.is_some_and(|expiry| current_slot > expiry.expires_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_authority_expiry() {
+        let header_size = std::mem::size_of::<AuthorityAccountHeader>();
+        assert_eq!(header_size, 48);
+        assert_eq!(
+            std::mem::size_of::<AuthorityExpiry>(),
+            AUTHORITY_EXPIRY_SIZE
+        );
+
+        let mut data = vec![0u8; header_size + 32 + AUTHORITY_EXPIRY_SIZE];
+        // Without the flag, trailing bytes are not an expiry
+        assert!(read_authority_expiry(&data).unwrap().is_none());
+        assert!(!authority_expired(&data, u64::MAX).unwrap());
+
+        data[std::mem::offset_of!(AuthorityAccountHeader, flags)] = AUTHORITY_FLAG_EXPIRES;
+        let expiry = AuthorityExpiry {
+            expires_at: 1_000,
+            funder: [7u8; 32],
+        };
+        unsafe {
+            std::ptr::write_unaligned(
+                data[header_size + 32..].as_mut_ptr() as *mut AuthorityExpiry,
+                expiry,
+            );
+        }
+        assert_eq!(
+            read_authority_expiry(&data).unwrap().unwrap().funder,
+            [7u8; 32]
+        );
+        assert!(!authority_expired(&data, 1_000).unwrap());
+        assert!(authority_expired(&data, 1_001).unwrap());
+
+        // A flagged authority must carry its expiry
+        assert!(read_authority_expiry(&data[..header_size + 32]).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use no_padding::NoPadding;
-use pinocchio::pubkey::Pubkey;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::error::AuthError;
 
 /// Deferred Execution Authorization Account.
 ///
@@ -7,6 +9,17 @@ use pinocchio::pubkey::Pubkey;
 /// set of instructions for later execution. The `ExecuteDeferred` instruction (tx2)
 /// verifies the hashes and executes the instructions, then closes this account.
 ///
+/// A multi-use authorization (`AuthorizeRecurring`) can be executed up to
+/// `max_executions` times, at least `min_interval_slots` apart; it is closed
+/// by its last run, or reclaimed once expired.
+///
+/// A conditional authorization (`AuthorizeConditional`) stores a predicate
+/// list after the header; every run requires all predicates to hold.
+///
+/// A proposal (`CreateProposal`) stores an approval section after the header
+/// instead, and can only be executed once enough approvers have approved. A
+/// queued execution (`QueueExecute`) stores the slot from which it can run.
+///
 /// This enables large payloads (e.g., Jupiter swaps) that exceed the ~574 bytes
 /// available in a single Secp256r1 Execute transaction.
 #[repr(C, align(8))]
@@ -18,19 +31,167 @@ pub struct DeferredExecAccount {
     pub version: u8,
     /// Bump seed for this PDA.
     pub bump: u8,
-    /// Padding for alignment.
-    pub _padding: [u8; 5],
+    /// What `authority` is and how it runs: `DEFERRED_AUTHORITY_KIND_*`.
+    pub authority_kind: u8,
+    /// Number of runs allowed (1 for a single-use authorization).
+    pub max_executions: u16,
+    /// Number of successful runs so far.
+    pub executions_done: u16,
     /// SHA256 of the serialized compact instructions bytes.
     pub instructions_hash: [u8; 32],
     /// SHA256 of all account pubkeys referenced by compact instructions.
     pub accounts_hash: [u8; 32],
     /// The wallet this authorization is for.
     pub wallet: Pubkey,
-    /// The authority that created this authorization.
+    /// The Authority or Session PDA that created this authorization.
     pub authority: Pubkey,
     /// The payer who funded this account (receives rent refund on close).
     pub payer: Pubkey,
     /// Absolute slot at which this authorization expires.
     pub expires_at: u64,
+    /// Minimum number of slots between two runs.
+    pub min_interval_slots: u64,
+    /// Slot of the last successful run (0 = never run).
+    pub last_executed_slot: u64,
+}
+// Layout: 1+1+1+1+2+2+32+32+32+32+32+8+8+8 = 192 bytes
+
+/// Size of the fixed DeferredExec header. A conditional authorization's
+/// predicate list (see `state::predicate`) follows it.
+pub const DEFERRED_EXEC_HEADER_SIZE: usize = 192;
+
+/// Maximum `max_executions` of a multi-use authorization.
+pub const MAX_DEFERRED_EXECUTIONS: u16 = 1_000;
+
+impl DeferredExecAccount {
+    /// Records a run at `current_slot` and returns whether it was the last one.
+    ///
+    /// Fails with `DeferredIntervalNotElapsed` if less than `min_interval_slots`
+    /// have passed since the previous run.
+    pub fn record_execution(&mut self, current_slot: u64) -> Result<bool, AuthError> {
+        if self.executions_done > 0
+            && current_slot
+                < self
+                    .last_executed_slot
+                    .saturating_add(self.min_interval_slots)
+        {
+            return Err(AuthError::DeferredIntervalNotElapsed);
+        }
+        self.executions_done = self.executions_done.saturating_add(1);
+        self.last_executed_slot = current_slot;
+        Ok(self.executions_done >= self.max_executions)
+    }
+}
+
+/// `DeferredExecAccount::authority_kind`: authorized by an Owner/Admin Authority PDA.
+pub const DEFERRED_AUTHORITY_KIND_AUTHORITY: u8 = 0;
+
+/// `DeferredExecAccount::authority_kind`: authorized by a Session PDA.
+/// `ExecuteDeferred` runs the session's action enforcement around the CPIs.
+pub const DEFERRED_AUTHORITY_KIND_SESSION: u8 = 1;
+
+/// `DeferredExecAccount::authority_kind`: a multi-approver proposal created by
+/// the Owner/Admin Authority PDA in `authority`. Its approval section (see
+/// `state::proposal`) follows the header instead of predicates.
+pub const DEFERRED_AUTHORITY_KIND_PROPOSAL: u8 = 2;
+
+/// `DeferredExecAccount::authority_kind`: a queued execution of a timelocked
+/// wallet, proposed by the Owner/Admin Authority PDA in `authority`. The slot
+/// from which it can run (`not_before: u64 LE`) follows the header.
+pub const DEFERRED_AUTHORITY_KIND_TIMELOCK: u8 = 3;
+
+/// Returns the `not_before` slot stored after a queued execution's header.
+pub fn timelock_not_before(trailer: &[u8]) -> Result<u64, ProgramError> {
+    let bytes = trailer.get(..8).ok_or(ProgramError::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Maximum size of a deferred buffer's payload (the serialized compact instructions).
+pub const MAX_DEFERRED_BUFFER_SIZE: usize = 10 * 1024;
+
+/// Deferred Buffer Account.
+///
+/// Holds the serialized compact instructions of a DeferredExec authorization
+/// when they do not fit in the `ExecuteDeferred` transaction. Written in chunks
+/// by the DeferredExec payer (`WriteDeferredBuffer`), then hashed against
+/// `instructions_hash`, executed from and closed by `ExecuteDeferred`.
+///
+/// PDA seeds: `["deferred_buffer", deferred_exec_pda]`. The payload follows
+/// the 72-byte header.
+#[repr(C, align(8))]
+#[derive(NoPadding, Debug, Clone, Copy)]
+pub struct DeferredBufferAccount {
+    /// Account discriminator (must be `7` for DeferredBuffer).
+    pub discriminator: u8,
+    /// Account version.
+    pub version: u8,
+    /// Bump seed for this PDA.
+    pub bump: u8,
+    /// Padding for alignment.
+    pub _padding: [u8; 5],
+    /// The DeferredExec authorization this buffer belongs to.
+    pub deferred: Pubkey,
+    /// The payer who funded this account (receives rent refund on close).
+    pub payer: Pubkey,
+}
+// Layout: 1+1+1+5+32+32 = 72 bytes
+
+/// Size of the fixed deferred buffer header (excluding the payload).
+pub const DEFERRED_BUFFER_HEADER_SIZE: usize = 72;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deferred_account_sizes() {
+        assert_eq!(
+            std::mem::size_of::<DeferredExecAccount>(),
+            DEFERRED_EXEC_HEADER_SIZE
+        );
+        assert_eq!(
+            std::mem::size_of::<DeferredBufferAccount>(),
+            DEFERRED_BUFFER_HEADER_SIZE
+        );
+    }
+
+    fn recurring(max_executions: u16, min_interval_slots: u64) -> DeferredExecAccount {
+        DeferredExecAccount {
+            discriminator: 4,
+            version: 1,
+            bump: 255,
+            authority_kind: DEFERRED_AUTHORITY_KIND_AUTHORITY,
+            max_executions,
+            executions_done: 0,
+            instructions_hash: [0; 32],
+            accounts_hash: [0; 32],
+            wallet: [0; 32],
+            authority: [0; 32],
+            payer: [0; 32],
+            expires_at: u64::MAX,
+            min_interval_slots,
+            last_executed_slot: 0,
+        }
+    }
+
+    #[test]
+    fn test_single_use_closes_on_first_run() {
+        let mut deferred = recurring(1, 0);
+        assert!(matches!(deferred.record_execution(100), Ok(true)));
+    }
+
+    #[test]
+    fn test_recurring_enforces_interval_and_count() {
+        let mut deferred = recurring(3, 50);
+        assert!(matches!(deferred.record_execution(100), Ok(false)));
+        assert!(matches!(
+            deferred.record_execution(149),
+            Err(AuthError::DeferredIntervalNotElapsed)
+        ));
+        assert_eq!(deferred.executions_done, 1);
+        assert!(matches!(deferred.record_execution(150), Ok(false)));
+        assert!(matches!(deferred.record_execution(200), Ok(true)));
+        assert_eq!(deferred.executions_done, 3);
+        assert_eq!(deferred.last_executed_slot, 200);
+    }
 }
-// Layout: 1+1+1+5+32+32+32+32+32+8 = 176 bytes
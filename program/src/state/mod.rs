@@ -1,8 +1,13 @@
 pub mod action;
 pub mod authority;
 pub mod deferred;
+pub mod pending_owner;
+pub mod policy_template;
+pub mod predicate;
+pub mod proposal;
 pub mod session;
 pub mod wallet;
+pub mod wallet_config;
 
 /// Discriminators for account types to ensure type safety.
 #[repr(u8)]
@@ -15,6 +20,14 @@ pub enum AccountDiscriminator {
     Session = 3,
     /// A Deferred Execution authorization account.
     DeferredExec = 4,
+    /// A Policy Template shared by template-bound sessions.
+    PolicyTemplate = 5,
+    /// A chunked payload buffer for a Deferred Execution authorization.
+    DeferredBuffer = 7,
+    /// Wallet-level configuration.
+    WalletConfig = 8,
+    /// A proposed, not yet accepted, ownership transfer.
+    PendingOwner = 9,
 }
 
 /// Helper constant for versioning.
@@ -0,0 +1,99 @@
+//! Pending two-step ownership transfers.
+//!
+//! `ProposeOwnership` records the next Owner in the wallet's Pending Owner
+//! PDA `["pending_owner", wallet]`; the transfer only completes when that
+//! authority proves possession of its key in `AcceptOwnership`. The header is
+//! followed by the new Owner's authority data, exactly as it will be stored
+//! after its `AuthorityAccountHeader`: the Ed25519 pubkey (32 bytes), or the
+//! Secp256r1 credential id hash, pubkey and rpId hash (32 + 33 + 32 bytes).
+
+use no_padding::NoPadding;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::{authority::authority_data_len, AccountDiscriminator};
+
+/// Size of the pending owner header (excluding the authority data).
+pub const PENDING_OWNER_HEADER_SIZE: usize = 104;
+
+#[repr(C, align(8))]
+#[derive(NoPadding, Debug, Clone, Copy)]
+/// Pending Owner Account.
+pub struct PendingOwnerAccount {
+    /// Account discriminator (must be `9` for PendingOwner).
+    pub discriminator: u8, // 1
+    /// Bump seed for this PDA.
+    pub bump: u8, // 1
+    /// Account Version.
+    pub version: u8, // 1
+    /// Authority type of the new Owner (0=Ed25519, 1=Secp256r1).
+    pub auth_type: u8, // 1
+    /// Padding for alignment.
+    pub _padding: [u8; 4], // 4
+    /// The wallet whose ownership is being transferred.
+    pub wallet: Pubkey, // 32
+    /// The Owner Authority PDA that proposed the transfer (closed on accept).
+    pub proposer: Pubkey, // 32
+    /// Receives the rent of the proposer and of this account.
+    pub refund_destination: Pubkey, // 32
+}
+
+/// Reads a Pending Owner PDA of `wallet`, returning its header and the new
+/// Owner's authority data.
+pub fn read_pending_owner<'a>(
+    data: &'a [u8],
+    wallet: &Pubkey,
+) -> Result<(PendingOwnerAccount, &'a [u8]), ProgramError> {
+    if data.len() < PENDING_OWNER_HEADER_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let pending = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const PendingOwnerAccount) };
+    if pending.discriminator != AccountDiscriminator::PendingOwner as u8
+        || pending.wallet != *wallet
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let authority_data = &data[PENDING_OWNER_HEADER_SIZE..];
+    if authority_data.len() != authority_data_len(pending.auth_type)? {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok((pending, authority_data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_owner_size() {
+        assert_eq!(
+            std::mem::size_of::<PendingOwnerAccount>(),
+            PENDING_OWNER_HEADER_SIZE
+        );
+    }
+
+    #[test]
+    fn test_read_pending_owner() {
+        let wallet = [1u8; 32];
+        let pending = PendingOwnerAccount {
+            discriminator: AccountDiscriminator::PendingOwner as u8,
+            bump: 255,
+            version: 1,
+            auth_type: 1,
+            _padding: [0; 4],
+            wallet,
+            proposer: [2u8; 32],
+            refund_destination: [3u8; 32],
+        };
+        let mut data = vec![0u8; PENDING_OWNER_HEADER_SIZE + 97];
+        unsafe {
+            std::ptr::write_unaligned(data.as_mut_ptr() as *mut PendingOwnerAccount, pending);
+        }
+        let (read, authority_data) = read_pending_owner(&data, &wallet).unwrap();
+        assert_eq!(read.proposer, [2u8; 32]);
+        assert_eq!(authority_data.len(), 97);
+
+        assert!(read_pending_owner(&data, &[9u8; 32]).is_err());
+        // Authority data must match the type
+        assert!(read_pending_owner(&data[..PENDING_OWNER_HEADER_SIZE + 32], &wallet).is_err());
+    }
+}
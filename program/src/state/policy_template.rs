@@ -0,0 +1,315 @@
+//! Policy templates: a validated action buffer shared by many sessions.
+//!
+//! A template-bound session does not embed the rules. Its trailing data is
+//! `[template: Pubkey(32)][state(N)]`, where `state` holds only the mutable
+//! fields of the template's stateful actions (lifetime remaining budgets and
+//! recurring / sliding-window counters), in template order. At `Execute` the template's
+//! action buffer is copied, the session state is scattered over it, the normal
+//! enforcement engine runs on the merged buffer, and the updated mutable fields
+//! are gathered back into the session.
+//!
+//! Static rules (whitelists, blacklists, per-tx caps, recurring and sliding
+//! caps, action expiries) therefore change fleet-wide when the template is
+//! updated, while every session keeps its own counters. `SolLimit` and
+//! `TokenLimit` amounts live in each session's `remaining` field, so a
+//! template update does not change them for existing sessions. Windows cannot
+//! change: the sessions' `last_reset` / `last_bucket` are in units of the
+//! window they were recorded with.
+
+use no_padding::NoPadding;
+use pinocchio::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    error::AuthError,
+    state::action::{parse_actions, read_u64, ActionType, ActionView, SLIDING_RING_LEN},
+};
+
+/// Size of the fixed policy template header (excluding actions).
+pub const POLICY_TEMPLATE_HEADER_SIZE: usize = 48;
+
+#[repr(C, align(8))]
+#[derive(NoPadding, Debug, Clone, Copy)]
+/// Policy Template Account.
+///
+/// Holds a validated action buffer (appended after the 48-byte header) that
+/// sessions can reference instead of embedding their own copy.
+pub struct PolicyTemplateAccount {
+    /// Account discriminator (must be `5` for PolicyTemplate).
+    pub discriminator: u8, // 1
+    /// Bump seed for this PDA.
+    pub bump: u8, // 1
+    /// Account Version.
+    pub version: u8, // 1
+    /// Padding for alignment.
+    pub _padding: [u8; 1], // 1
+    /// Caller-chosen template id (PDA seed).
+    pub template_id: u32, // 4
+    /// The wallet this template belongs to.
+    pub wallet: Pubkey, // 32
+    /// Size in bytes of the per-session mutable state this template requires.
+    pub state_len: u32, // 4
+    /// Incremented on every `UpdatePolicyTemplate`.
+    pub revision: u32, // 4
+}
+
+/// Mutable `(offset, len)` fields within an action's data, in storage order.
+///
+/// These are the only bytes a session keeps for a template-bound action.
+#[inline]
+pub fn mutable_fields(action_type: ActionType) -> &'static [(usize, usize)] {
+    match action_type {
+        // remaining
+        ActionType::SolLimit => &[(0, 8)],
+        // spent, last_reset (limit and window are static)
+        ActionType::SolRecurringLimit => &[(8, 8), (24, 8)],
+        // remaining
+        ActionType::TokenLimit => &[(32, 8)],
+        // spent, last_reset (mint, limit and window are static)
+        ActionType::TokenRecurringLimit => &[(40, 8), (56, 8)],
+        // last_bucket + bucket ring
+        ActionType::SolSlidingLimit => &[(16, 8 + 8 * SLIDING_RING_LEN)],
+        // last_bucket + bucket ring (after the mint)
+        ActionType::TokenSlidingLimit => &[(48, 8 + 8 * SLIDING_RING_LEN)],
+        _ => &[],
+    }
+}
+
+/// Offset of the `window` field of recurring and sliding-window actions.
+#[inline]
+fn window_offset(action_type: ActionType) -> Option<usize> {
+    match action_type {
+        ActionType::SolRecurringLimit => Some(16),
+        ActionType::TokenRecurringLimit => Some(48),
+        ActionType::SolSlidingLimit => Some(8),
+        ActionType::TokenSlidingLimit => Some(40),
+        _ => None,
+    }
+}
+
+/// Total per-session state size required by an action buffer.
+pub fn state_len(actions_buf: &[u8]) -> Result<usize, ProgramError> {
+    let actions = parse_actions(actions_buf)?;
+    Ok(actions
+        .iter()
+        .map(|a| {
+            mutable_fields(a.action_type)
+                .iter()
+                .map(|(_, len)| len)
+                .sum::<usize>()
+        })
+        .sum())
+}
+
+/// Copies the mutable fields of every stateful action in `actions_buf` into `state`.
+///
+/// Used to seed a new session from the template's initial values, and to write
+/// counters back after enforcement ran on a merged buffer.
+pub fn gather_state(actions_buf: &[u8], state: &mut [u8]) -> Result<(), ProgramError> {
+    let actions = parse_actions(actions_buf)?;
+    let mut cursor = 0;
+    for action in &actions {
+        for &(offset, len) in mutable_fields(action.action_type) {
+            let src = action.data_offset + offset;
+            let dst = state
+                .get_mut(cursor..cursor + len)
+                .ok_or(AuthError::ActionBufferInvalid)?;
+            dst.copy_from_slice(&actions_buf[src..src + len]);
+            cursor += len;
+        }
+    }
+    if cursor != state.len() {
+        return Err(AuthError::ActionBufferInvalid.into());
+    }
+    Ok(())
+}
+
+/// Overwrites the mutable fields of every stateful action in `actions_buf` with `state`.
+pub fn scatter_state(actions_buf: &mut [u8], state: &[u8]) -> Result<(), ProgramError> {
+    let actions = parse_actions(actions_buf)?;
+    let mut cursor = 0;
+    for action in &actions {
+        for &(offset, len) in mutable_fields(action.action_type) {
+            let dst = action.data_offset + offset;
+            let src = state
+                .get(cursor..cursor + len)
+                .ok_or(AuthError::ActionBufferInvalid)?;
+            actions_buf[dst..dst + len].copy_from_slice(src);
+            cursor += len;
+        }
+    }
+    if cursor != state.len() {
+        return Err(AuthError::ActionBufferInvalid.into());
+    }
+    Ok(())
+}
+
+/// Returns true if both buffers have the same stateful actions, in the same
+/// order, bound to the same mints and windows. A template update must preserve
+/// this so existing sessions' state still lines up with the template's actions.
+pub fn same_state_layout(old_buf: &[u8], new_buf: &[u8]) -> Result<bool, ProgramError> {
+    let old_actions = parse_actions(old_buf)?;
+    let new_actions = parse_actions(new_buf)?;
+    let stateful = |a: &ActionView| !mutable_fields(a.action_type).is_empty();
+
+    let mut old_iter = old_actions.iter().filter(stateful);
+    let mut new_iter = new_actions.iter().filter(stateful);
+    loop {
+        match (old_iter.next(), new_iter.next()) {
+            (None, None) => return Ok(true),
+            (Some(a), Some(b)) => {
+                if a.action_type != b.action_type {
+                    return Ok(false);
+                }
+                let is_token = matches!(
+                    a.action_type,
+                    ActionType::TokenLimit
+                        | ActionType::TokenRecurringLimit
+                        | ActionType::TokenSlidingLimit
+                );
+                if is_token
+                    && old_buf[a.data_offset..a.data_offset + 32]
+                        != new_buf[b.data_offset..b.data_offset + 32]
+                {
+                    return Ok(false);
+                }
+                if let Some(offset) = window_offset(a.action_type) {
+                    if read_u64(&old_buf[a.data_offset..], offset)
+                        != read_u64(&new_buf[b.data_offset..], offset)
+                    {
+                        return Ok(false);
+                    }
+                }
+            },
+            _ => return Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_action(action_type: u8, expires_at: u64, data: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.push(action_type);
+        buf.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&expires_at.to_le_bytes());
+        buf.extend_from_slice(data);
+        buf
+    }
+
+    fn sol_recurring(limit: u64, spent: u64, window: u64, last_reset: u64) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&limit.to_le_bytes());
+        data.extend_from_slice(&spent.to_le_bytes());
+        data.extend_from_slice(&window.to_le_bytes());
+        data.extend_from_slice(&last_reset.to_le_bytes());
+        data
+    }
+
+    fn token_limit(mint: u8, remaining: u64) -> Vec<u8> {
+        let mut data = vec![mint; 32];
+        data.extend_from_slice(&remaining.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_header_size_matches_struct() {
+        assert_eq!(
+            std::mem::size_of::<PolicyTemplateAccount>(),
+            POLICY_TEMPLATE_HEADER_SIZE
+        );
+    }
+
+    #[test]
+    fn test_state_len_counts_only_mutable_fields() {
+        let mut buf = build_action(10, 0, &[1u8; 32]); // whitelist: 0
+        buf.extend_from_slice(&build_action(1, 0, &1_000u64.to_le_bytes())); // SolLimit: 8
+        buf.extend_from_slice(&build_action(2, 0, &sol_recurring(100, 0, 10, 0))); // 16
+        buf.extend_from_slice(&build_action(3, 0, &50u64.to_le_bytes())); // SolMaxPerTx: 0
+        buf.extend_from_slice(&build_action(4, 0, &token_limit(7, 9))); // TokenLimit: 8
+        assert_eq!(state_len(&buf).unwrap(), 32);
+    }
+
+    #[test]
+    fn test_gather_scatter_roundtrip() {
+        let mut buf = build_action(1, 0, &1_000u64.to_le_bytes());
+        buf.extend_from_slice(&build_action(2, 0, &sol_recurring(100, 0, 10, 0)));
+        let mut state = vec![0u8; state_len(&buf).unwrap()];
+        gather_state(&buf, &mut state).unwrap();
+        assert_eq!(read_u64(&state, 0), 1_000);
+
+        // Session has spent: remaining 400, recurring spent 30 at slot 20
+        state[0..8].copy_from_slice(&400u64.to_le_bytes());
+        state[8..16].copy_from_slice(&30u64.to_le_bytes());
+        state[16..24].copy_from_slice(&20u64.to_le_bytes());
+
+        let mut merged = buf.clone();
+        scatter_state(&mut merged, &state).unwrap();
+        let sol_limit_data = 11;
+        let recurring_data = 11 + 8 + 11;
+        assert_eq!(read_u64(&merged, sol_limit_data), 400);
+        assert_eq!(read_u64(&merged, recurring_data), 100); // limit from template
+        assert_eq!(read_u64(&merged, recurring_data + 8), 30); // spent from session
+        assert_eq!(read_u64(&merged, recurring_data + 16), 10); // window from template
+        assert_eq!(read_u64(&merged, recurring_data + 24), 20); // last_reset from session
+
+        let mut back = vec![0u8; state.len()];
+        gather_state(&merged, &mut back).unwrap();
+        assert_eq!(back, state);
+    }
+
+    #[test]
+    fn test_state_length_mismatch_rejected() {
+        let buf = build_action(1, 0, &1_000u64.to_le_bytes());
+        let mut too_long = vec![0u8; 16];
+        assert!(gather_state(&buf, &mut too_long).is_err());
+        let mut merged = buf.clone();
+        assert!(scatter_state(&mut merged, &[0u8; 4]).is_err());
+    }
+
+    #[test]
+    fn test_same_state_layout_allows_static_changes() {
+        let mut old = build_action(10, 0, &[1u8; 32]);
+        old.extend_from_slice(&build_action(1, 0, &1_000u64.to_le_bytes()));
+        let mut new = build_action(10, 0, &[1u8; 32]);
+        new.extend_from_slice(&build_action(10, 0, &[2u8; 32])); // extra whitelist entry
+        new.extend_from_slice(&build_action(3, 0, &5u64.to_le_bytes())); // new per-tx cap
+        new.extend_from_slice(&build_action(1, 0, &9_999u64.to_le_bytes()));
+        assert!(same_state_layout(&old, &new).unwrap());
+    }
+
+    #[test]
+    fn test_same_state_layout_rejects_reordered_or_new_stateful() {
+        let old = build_action(1, 0, &1_000u64.to_le_bytes());
+        let mut new = build_action(1, 0, &1_000u64.to_le_bytes());
+        new.extend_from_slice(&build_action(4, 0, &token_limit(7, 9)));
+        assert!(!same_state_layout(&old, &new).unwrap());
+        assert!(!same_state_layout(&new, &old).unwrap());
+    }
+
+    #[test]
+    fn test_same_state_layout_rejects_mint_change() {
+        let old = build_action(4, 0, &token_limit(7, 9));
+        let new = build_action(4, 0, &token_limit(8, 9));
+        assert!(!same_state_layout(&old, &new).unwrap());
+    }
+
+    #[test]
+    fn test_same_state_layout_rejects_window_change() {
+        let old = build_action(2, 0, &sol_recurring(100, 0, 10, 0));
+        // A new cap keeps the layout, a new window does not
+        let raised = build_action(2, 0, &sol_recurring(500, 0, 10, 0));
+        assert!(same_state_layout(&old, &raised).unwrap());
+        let longer = build_action(2, 0, &sol_recurring(100, 0, 20, 0));
+        assert!(!same_state_layout(&old, &longer).unwrap());
+
+        let mut sliding = vec![0u8; 96];
+        sliding[0..8].copy_from_slice(&100u64.to_le_bytes());
+        sliding[8..16].copy_from_slice(&80u64.to_le_bytes());
+        let old = build_action(7, 0, &sliding);
+        sliding[8..16].copy_from_slice(&160u64.to_le_bytes());
+        let new = build_action(7, 0, &sliding);
+        assert!(!same_state_layout(&old, &new).unwrap());
+    }
+}
@@ -0,0 +1,225 @@
+//! Predicates for conditional deferred execution.
+//!
+//! A DeferredExec authorization may carry a predicate list after its 192-byte
+//! header. `ExecuteDeferred` evaluates every predicate before any CPI and
+//! fails unless all of them hold, which enables limit-order and stop-loss
+//! style automation ("swap only once the oracle price is ≥ N").
+//!
+//! Each predicate reads `len` bytes at `offset` of a named account and compares
+//! them with an operand of the same length:
+//!
+//! `[account: 32][offset: u16 LE][len: u8][op: u8][operand: len]`
+//!
+//! Values are little-endian integers of 1–32 bytes. `Eq` / `Ne` compare the
+//! raw bytes; ordered comparisons are unsigned, or two's-complement signed when
+//! `PREDICATE_FLAG_SIGNED` is set on `op`.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::error::AuthError;
+
+/// Size of a predicate's fixed part (before the operand).
+pub const PREDICATE_HEADER_SIZE: usize = 36;
+
+/// Maximum compared width in bytes.
+pub const MAX_PREDICATE_LEN: usize = 32;
+
+/// Maximum number of predicates per authorization.
+pub const MAX_PREDICATES: usize = 8;
+
+/// `op` bit: compare ordered values as signed integers.
+pub const PREDICATE_FLAG_SIGNED: u8 = 0x80;
+
+/// Comparison operators (`op & !PREDICATE_FLAG_SIGNED`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PredicateOp {
+    Eq = 0,
+    Ne = 1,
+    Lt = 2,
+    Le = 3,
+    Gt = 4,
+    Ge = 5,
+}
+
+impl TryFrom<u8> for PredicateOp {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PredicateOp::Eq),
+            1 => Ok(PredicateOp::Ne),
+            2 => Ok(PredicateOp::Lt),
+            3 => Ok(PredicateOp::Le),
+            4 => Ok(PredicateOp::Gt),
+            5 => Ok(PredicateOp::Ge),
+            _ => Err(AuthError::InvalidDeferredPredicate.into()),
+        }
+    }
+}
+
+/// A parsed predicate borrowing from the predicate list.
+#[derive(Debug, Clone, Copy)]
+pub struct Predicate<'a> {
+    /// Pubkey of the account whose data is compared.
+    pub account: &'a [u8],
+    /// Byte offset of the compared value in the account data.
+    pub offset: usize,
+    pub op: PredicateOp,
+    pub signed: bool,
+    /// Little-endian operand, as wide as the compared value.
+    pub operand: &'a [u8],
+}
+
+impl Predicate<'_> {
+    /// Returns whether the predicate holds for `account_data`. A value that
+    /// lies outside the data never satisfies the predicate.
+    pub fn holds(&self, account_data: &[u8]) -> bool {
+        let end = self.offset + self.operand.len();
+        let Some(value) = account_data.get(self.offset..end) else {
+            return false;
+        };
+        let ordering = compare_le(value, self.operand, self.signed);
+        match self.op {
+            PredicateOp::Eq => value == self.operand,
+            PredicateOp::Ne => value != self.operand,
+            PredicateOp::Lt => ordering.is_lt(),
+            PredicateOp::Le => ordering.is_le(),
+            PredicateOp::Gt => ordering.is_gt(),
+            PredicateOp::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// Compares two little-endian integers of equal width.
+fn compare_le(a: &[u8], b: &[u8], signed: bool) -> core::cmp::Ordering {
+    let mut bytes = a.iter().zip(b.iter()).rev();
+    if signed {
+        // The most significant byte carries the sign: flipping its top bit
+        // maps two's-complement order onto unsigned order.
+        if let Some((x, y)) = bytes.next() {
+            match (x ^ 0x80).cmp(&(y ^ 0x80)) {
+                core::cmp::Ordering::Equal => {},
+                other => return other,
+            }
+        }
+    }
+    for (x, y) in bytes {
+        match x.cmp(y) {
+            core::cmp::Ordering::Equal => {},
+            other => return other,
+        }
+    }
+    core::cmp::Ordering::Equal
+}
+
+/// Parses and validates a predicate list. The buffer must consist of whole
+/// predicates only, at most `MAX_PREDICATES` of them.
+pub fn parse_predicates(buf: &[u8]) -> Result<Vec<Predicate<'_>>, ProgramError> {
+    let mut predicates = Vec::new();
+    let mut rest = buf;
+    while !rest.is_empty() {
+        if predicates.len() == MAX_PREDICATES || rest.len() < PREDICATE_HEADER_SIZE {
+            return Err(AuthError::InvalidDeferredPredicate.into());
+        }
+        let offset = u16::from_le_bytes([rest[32], rest[33]]) as usize;
+        let len = rest[34] as usize;
+        let op_byte = rest[35];
+        if len == 0 || len > MAX_PREDICATE_LEN || rest.len() < PREDICATE_HEADER_SIZE + len {
+            return Err(AuthError::InvalidDeferredPredicate.into());
+        }
+        let op = PredicateOp::try_from(op_byte & !PREDICATE_FLAG_SIGNED)?;
+        let signed = op_byte & PREDICATE_FLAG_SIGNED != 0;
+        if signed && matches!(op, PredicateOp::Eq | PredicateOp::Ne) {
+            return Err(AuthError::InvalidDeferredPredicate.into());
+        }
+        predicates.push(Predicate {
+            account: &rest[..32],
+            offset,
+            op,
+            signed,
+            operand: &rest[PREDICATE_HEADER_SIZE..PREDICATE_HEADER_SIZE + len],
+        });
+        rest = &rest[PREDICATE_HEADER_SIZE + len..];
+    }
+    Ok(predicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(account: [u8; 32], offset: u16, op: u8, operand: &[u8]) -> Vec<u8> {
+        let mut buf = account.to_vec();
+        buf.extend_from_slice(&offset.to_le_bytes());
+        buf.push(operand.len() as u8);
+        buf.push(op);
+        buf.extend_from_slice(operand);
+        buf
+    }
+
+    #[test]
+    fn test_u64_price_at_least() {
+        let buf = encode([1; 32], 8, PredicateOp::Ge as u8, &1_000u64.to_le_bytes());
+        let predicates = parse_predicates(&buf).unwrap();
+        assert_eq!(predicates.len(), 1);
+
+        let mut data = vec![0u8; 16];
+        data[8..16].copy_from_slice(&999u64.to_le_bytes());
+        assert!(!predicates[0].holds(&data));
+        data[8..16].copy_from_slice(&1_000u64.to_le_bytes());
+        assert!(predicates[0].holds(&data));
+        data[8..16].copy_from_slice(&0x0100_0000_0000_0000u64.to_le_bytes());
+        assert!(predicates[0].holds(&data));
+
+        // Value beyond the account data
+        assert!(!predicates[0].holds(&data[..12]));
+    }
+
+    #[test]
+    fn test_signed_comparison() {
+        let op = PredicateOp::Lt as u8 | PREDICATE_FLAG_SIGNED;
+        let buf = encode([1; 32], 0, op, &0i64.to_le_bytes());
+        let predicates = parse_predicates(&buf).unwrap();
+        assert!(predicates[0].holds(&(-5i64).to_le_bytes()));
+        assert!(!predicates[0].holds(&5i64.to_le_bytes()));
+
+        // Unsigned, -5 is a huge value
+        let buf = encode([1; 32], 0, PredicateOp::Lt as u8, &0i64.to_le_bytes());
+        let predicates = parse_predicates(&buf).unwrap();
+        assert!(!predicates[0].holds(&(-5i64).to_le_bytes()));
+    }
+
+    #[test]
+    fn test_multiple_predicates_and_equality() {
+        let mut buf = encode([1; 32], 0, PredicateOp::Eq as u8, &[7u8; 32]);
+        buf.extend(encode(
+            [2; 32],
+            4,
+            PredicateOp::Gt as u8,
+            &100u32.to_le_bytes(),
+        ));
+        let predicates = parse_predicates(&buf).unwrap();
+        assert_eq!(predicates.len(), 2);
+        assert_eq!(predicates[1].account, &[2u8; 32]);
+        assert!(predicates[0].holds(&[7u8; 32]));
+        assert!(!predicates[0].holds(&[8u8; 32]));
+    }
+
+    #[test]
+    fn test_rejects_malformed_lists() {
+        // Truncated operand
+        let buf = encode([1; 32], 0, PredicateOp::Eq as u8, &[1u8; 8]);
+        assert!(parse_predicates(&buf[..buf.len() - 1]).is_err());
+        // Zero width, unknown op, signed equality
+        assert!(parse_predicates(&encode([1; 32], 0, PredicateOp::Eq as u8, &[])).is_err());
+        assert!(parse_predicates(&encode([1; 32], 0, 6, &[1])).is_err());
+        let signed_eq = PredicateOp::Eq as u8 | PREDICATE_FLAG_SIGNED;
+        assert!(parse_predicates(&encode([1; 32], 0, signed_eq, &[1])).is_err());
+        // Too many predicates
+        let buf: Vec<u8> = (0..=MAX_PREDICATES)
+            .flat_map(|_| encode([1; 32], 0, PredicateOp::Eq as u8, &[1]))
+            .collect();
+        assert!(parse_predicates(&buf).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Multi-approver proposals.
+//!
+//! A proposal is a DeferredExec authorization of kind
+//! `DEFERRED_AUTHORITY_KIND_PROPOSAL`. After the 192-byte DeferredExec header
+//! it stores an approval section:
+//!
+//! `[threshold: u8][approver_count: u8][approvals: u16 LE][_padding: 4][approvers: 32 × approver_count]`
+//!
+//! `approvers` are the Owner/Admin Authority PDAs allowed to approve, and bit
+//! `i` of `approvals` is set once `approvers[i]` has approved. `ExecuteDeferred`
+//! runs the proposal once at least `threshold` approvers, still Owner/Admin
+//! Authorities of the wallet, have approved.
+
+use pinocchio::program_error::ProgramError;
+
+use crate::error::AuthError;
+
+/// Size of the approval section's fixed part (before the approver list).
+pub const PROPOSAL_HEADER_SIZE: usize = 8;
+
+/// Maximum number of approvers per proposal (bits in `approvals`).
+pub const MAX_PROPOSAL_APPROVERS: usize = 16;
+
+/// Builds the approval section of a new proposal, with no approvals yet.
+///
+/// `approvers` must be 1–16 distinct pubkeys and `threshold` must be
+/// reachable (`1 ≤ threshold ≤ approver_count`).
+pub fn build_approval_section(threshold: u8, approvers: &[u8]) -> Result<Vec<u8>, ProgramError> {
+    if approvers.is_empty() || approvers.len() % 32 != 0 {
+        return Err(AuthError::InvalidProposal.into());
+    }
+    let count = approvers.len() / 32;
+    if count > MAX_PROPOSAL_APPROVERS || threshold == 0 || threshold as usize > count {
+        return Err(AuthError::InvalidProposal.into());
+    }
+    for (i, approver) in approvers.chunks_exact(32).enumerate() {
+        if approvers[(i + 1) * 32..]
+            .chunks_exact(32)
+            .any(|other| other == approver)
+        {
+            return Err(AuthError::InvalidProposal.into());
+        }
+    }
+
+    let mut section = Vec::with_capacity(PROPOSAL_HEADER_SIZE + approvers.len());
+    section.push(threshold);
+    section.push(count as u8);
+    section.extend_from_slice(&[0u8; 6]);
+    section.extend_from_slice(approvers);
+    Ok(section)
+}
+
+/// Returns `(threshold, approvals, approvers)` of a stored approval section.
+pub fn read_approval_section(section: &[u8]) -> Result<(u8, u16, &[u8]), ProgramError> {
+    if section.len() < PROPOSAL_HEADER_SIZE {
+        return Err(AuthError::InvalidProposal.into());
+    }
+    let count = section[1] as usize;
+    let approvers = section
+        .get(PROPOSAL_HEADER_SIZE..PROPOSAL_HEADER_SIZE + count * 32)
+        .ok_or(AuthError::InvalidProposal)?;
+    Ok((
+        section[0],
+        u16::from_le_bytes([section[2], section[3]]),
+        approvers,
+    ))
+}
+
+/// Records `approver`'s approval. Fails with `PermissionDenied` if it is not
+/// an approver of the proposal and `ProposalAlreadyApproved` if it already
+/// approved.
+pub fn record_approval(section: &mut [u8], approver: &[u8]) -> Result<(), ProgramError> {
+    let (_, approvals, approvers) = read_approval_section(section)?;
+    let index = approvers
+        .chunks_exact(32)
+        .position(|key| key == approver)
+        .ok_or(AuthError::PermissionDenied)?;
+    let bit = 1u16 << index;
+    if approvals & bit != 0 {
+        return Err(AuthError::ProposalAlreadyApproved.into());
+    }
+    section[2..4].copy_from_slice(&(approvals | bit).to_le_bytes());
+    Ok(())
+}
+
+/// Iterates over the approvers that have approved.
+pub fn approved_approvers(approvals: u16, approvers: &[u8]) -> impl Iterator<Item = &[u8]> {
+    approvers
+        .chunks_exact(32)
+        .enumerate()
+        .filter(move |(i, _)| approvals & (1u16 << i) != 0)
+        .map(|(_, key)| key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approvers(n: u8) -> Vec<u8> {
+        (1..=n).flat_map(|i| [i; 32]).collect()
+    }
+
+    #[test]
+    fn test_approvals_are_recorded_once() {
+        let mut section = build_approval_section(2, &approvers(3)).unwrap();
+        assert_eq!(section.len(), PROPOSAL_HEADER_SIZE + 96);
+
+        record_approval(&mut section, &[3u8; 32]).unwrap();
+        record_approval(&mut section, &[1u8; 32]).unwrap();
+        assert!(matches!(
+            record_approval(&mut section, &[1u8; 32]),
+            Err(ProgramError::Custom(c)) if c == AuthError::ProposalAlreadyApproved as u32
+        ));
+        assert!(record_approval(&mut section, &[9u8; 32]).is_err());
+
+        let (threshold, approvals, list) = read_approval_section(&section).unwrap();
+        assert_eq!(threshold, 2);
+        assert_eq!(approvals, 0b101);
+        let approved: Vec<&[u8]> = approved_approvers(approvals, list).collect();
+        assert_eq!(approved, vec![&[1u8; 32][..], &[3u8; 32][..]]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_approver_sets() {
+        // Unreachable or zero threshold
+        assert!(build_approval_section(4, &approvers(3)).is_err());
+        assert!(build_approval_section(0, &approvers(3)).is_err());
+        // Empty, partial key, too many, duplicate
+        assert!(build_approval_section(1, &[]).is_err());
+        assert!(build_approval_section(1, &[1u8; 33]).is_err());
+        assert!(build_approval_section(1, &approvers(MAX_PROPOSAL_APPROVERS as u8 + 1)).is_err());
+        let mut duplicate = approvers(2);
+        duplicate.extend_from_slice(&[1u8; 32]);
+        assert!(build_approval_section(1, &duplicate).is_err());
+        assert!(build_approval_section(16, &approvers(16)).is_ok());
+    }
+}
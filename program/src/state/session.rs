@@ -2,14 +2,45 @@ use no_padding::NoPadding;
 use pinocchio::pubkey::Pubkey;
 
 /// Size of the fixed session header (excluding actions).
-pub const SESSION_HEADER_SIZE: usize = 80;
+pub const SESSION_HEADER_SIZE: usize = 144;
+
+/// Layout version of the sessions created by this program (`SessionAccount::version`).
+pub const SESSION_VERSION: u8 = 2;
+
+/// Layout version of sessions created before the header grew. Their header is
+/// the first 80 bytes of `SessionAccount` (no flags, nonce, creator or
+/// statistics; those bytes were zero padding) and their actions start at
+/// offset 80.
+pub const LEGACY_SESSION_VERSION: u8 = 1;
+
+/// Size of a legacy (version 1) session header.
+pub const LEGACY_SESSION_HEADER_SIZE: usize = 80;
+
+/// Session flag: `Execute` requires the creator Authority PDA to still exist
+/// and belong to the wallet. Lets offboarding an Admin via `RemoveAuthority`
+/// also kill every session that Admin handed out.
+pub const SESSION_FLAG_REQUIRE_CREATOR: u8 = 1 << 0;
+
+/// Session flag: the session references a `PolicyTemplate` instead of embedding
+/// actions. Trailing data is `[template: Pubkey(32)][state(N)]` — see
+/// `state::policy_template`.
+pub const SESSION_FLAG_TEMPLATE: u8 = 1 << 1;
+
+/// Session flag: the action buffer is still being built with
+/// `AppendSessionActions`. The session cannot `Execute` until the final chunk
+/// validates the whole buffer and clears this flag.
+pub const SESSION_FLAG_STAGED: u8 = 1 << 2;
+
+/// All session flag bits understood by this program version.
+pub const SESSION_FLAGS_MASK: u8 =
+    SESSION_FLAG_REQUIRE_CREATOR | SESSION_FLAG_TEMPLATE | SESSION_FLAG_STAGED;
 
 #[repr(C, align(8))]
 #[derive(NoPadding)]
 /// Ephemeral Session Account.
 ///
 /// Represents a temporary delegated authority with an expiration time.
-/// Optional actions may follow the 80-byte header as a flat byte buffer.
+/// Optional actions may follow the 144-byte header as a flat byte buffer.
 pub struct SessionAccount {
     /// Account discriminator (must be `3` for Session).
     pub discriminator: u8, // 1
@@ -17,29 +48,233 @@ pub struct SessionAccount {
     pub bump: u8, // 1
     /// Account Version.
     pub version: u8, // 1
-    /// Padding for alignment.
-    pub _padding: [u8; 5], // 5
+    /// Session flags (`SESSION_FLAG_*`), fixed at creation.
+    pub flags: u8, // 1
+    /// Number of `Authorize` calls made by this session; seeds its DeferredExec PDAs.
+    pub deferred_nonce: u32, // 4
     /// The wallet this session belongs to.
     pub wallet: Pubkey, // 32
     /// The ephemeral public key authorized to sign.
     pub session_key: Pubkey, // 32
     /// Absolute slot height when this session expires.
     pub expires_at: u64, // 8
+    /// The Authority PDA that created this session.
+    pub creator: Pubkey, // 32
+    /// Slot of the last successful `Execute` (0 = never used).
+    pub last_used_slot: u64, // 8
+    /// Number of successful `Execute` calls.
+    pub use_count: u64, // 8
+    /// Lifetime net SOL outflow from the vault, in lamports.
+    pub total_sol_out: u64, // 8
+    /// Lifetime net token outflow, in raw units, summed over the mints tracked
+    /// by this session's token actions (0 for sessions without token actions).
+    pub total_token_out: u64, // 8
+}
+
+/// Size of the header of this session account, from its layout version.
+#[inline]
+pub fn session_header_size(session_data: &[u8]) -> usize {
+    if session_data.get(2) == Some(&LEGACY_SESSION_VERSION) {
+        LEGACY_SESSION_HEADER_SIZE
+    } else {
+        SESSION_HEADER_SIZE
+    }
+}
+
+/// Reads the session header of either layout. A legacy header is widened
+/// with zeroed flags, nonce, creator and statistics. Returns `None` if the
+/// data is shorter than its header.
+pub fn read_session(session_data: &[u8]) -> Option<SessionAccount> {
+    let header_size = session_header_size(session_data);
+    if session_data.len() < header_size {
+        return None;
+    }
+    let mut header = [0u8; SESSION_HEADER_SIZE];
+    header[..header_size].copy_from_slice(&session_data[..header_size]);
+    Some(unsafe { std::ptr::read_unaligned(header.as_ptr() as *const SessionAccount) })
+}
+
+/// Writes `session` back into a header read with `read_session`. A legacy
+/// header only keeps its first 80 bytes, so its action buffer is untouched.
+pub fn write_session(session_data: &mut [u8], session: &SessionAccount) {
+    let header_size = session_header_size(session_data);
+    if session_data.len() < header_size {
+        return;
+    }
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            session as *const SessionAccount as *const u8,
+            SESSION_HEADER_SIZE,
+        )
+    };
+    session_data[..header_size].copy_from_slice(&bytes[..header_size]);
 }
 
 /// Returns true if the session account data contains actions after the header.
 #[inline]
 pub fn has_actions(session_data: &[u8]) -> bool {
-    session_data.len() > SESSION_HEADER_SIZE
+    session_data.len() > session_header_size(session_data)
+}
+
+/// Returns true if the session is bound to a policy template (`SESSION_FLAG_TEMPLATE`).
+#[inline]
+pub fn uses_template(session_data: &[u8]) -> bool {
+    session_data.len() > 3 && session_data[3] & SESSION_FLAG_TEMPLATE != 0
 }
 
-/// Returns the actions buffer slice (bytes after the 80-byte header).
+/// Returns the actions buffer slice (bytes after the header of either layout).
 /// Returns empty slice if no actions.
 #[inline]
 pub fn actions_slice(session_data: &[u8]) -> &[u8] {
-    if session_data.len() > SESSION_HEADER_SIZE {
-        &session_data[SESSION_HEADER_SIZE..]
-    } else {
-        &[]
+    session_data
+        .get(session_header_size(session_data)..)
+        .unwrap_or(&[])
+}
+
+/// Records a successful `Execute` in the session header: stamps
+/// `last_used_slot`, bumps `use_count` and accumulates the outflow totals.
+///
+/// All counters saturate — statistics must never fail an otherwise valid Execute.
+/// Legacy sessions have no room for statistics and are left untouched.
+pub fn record_session_usage(
+    session_data: &mut [u8],
+    current_slot: u64,
+    sol_out: u64,
+    token_out: u64,
+) {
+    if session_header_size(session_data) != SESSION_HEADER_SIZE
+        || session_data.len() < SESSION_HEADER_SIZE
+    {
+        return;
+    }
+    let mut session =
+        unsafe { std::ptr::read_unaligned(session_data.as_ptr() as *const SessionAccount) };
+    session.last_used_slot = current_slot;
+    session.use_count = session.use_count.saturating_add(1);
+    session.total_sol_out = session.total_sol_out.saturating_add(sol_out);
+    session.total_token_out = session.total_token_out.saturating_add(token_out);
+    unsafe {
+        std::ptr::write_unaligned(session_data.as_mut_ptr() as *mut SessionAccount, session);
+    }
+}
+
+/// Rebinds a copied session header to a new session key and PDA bump.
+///
+/// Used by `RotateSession`: everything else — expiry, flags, creator, usage
+/// statistics and the trailing action buffer with its spent counters — is kept.
+pub fn rekey_session(session_data: &mut [u8], new_session_key: &Pubkey, new_bump: u8) {
+    let Some(mut session) = read_session(session_data) else {
+        return;
+    };
+    session.session_key = *new_session_key;
+    session.bump = new_bump;
+    write_session(session_data, &session);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_header(data: &[u8]) -> SessionAccount {
+        unsafe { std::ptr::read_unaligned(data.as_ptr() as *const SessionAccount) }
+    }
+
+    #[test]
+    fn test_header_size_matches_struct() {
+        assert_eq!(std::mem::size_of::<SessionAccount>(), SESSION_HEADER_SIZE);
+    }
+
+    #[test]
+    fn test_record_session_usage_accumulates() {
+        let mut data = vec![0u8; SESSION_HEADER_SIZE];
+        record_session_usage(&mut data, 100, 5_000, 0);
+        record_session_usage(&mut data, 250, 1_000, 42);
+
+        let session = read_header(&data);
+        assert_eq!(session.last_used_slot, 250);
+        assert_eq!(session.use_count, 2);
+        assert_eq!(session.total_sol_out, 6_000);
+        assert_eq!(session.total_token_out, 42);
+    }
+
+    #[test]
+    fn test_record_session_usage_leaves_actions_untouched() {
+        let mut data = vec![0u8; SESSION_HEADER_SIZE];
+        data.extend_from_slice(&[0xAB; 19]);
+        record_session_usage(&mut data, 7, 0, 0);
+
+        assert_eq!(read_header(&data).use_count, 1);
+        assert_eq!(actions_slice(&data), &[0xAB; 19]);
+    }
+
+    #[test]
+    fn test_record_session_usage_saturates() {
+        let mut data = vec![0u8; SESSION_HEADER_SIZE];
+        record_session_usage(&mut data, 1, u64::MAX, u64::MAX);
+        record_session_usage(&mut data, 2, 1, 1);
+
+        let session = read_header(&data);
+        assert_eq!(session.total_sol_out, u64::MAX);
+        assert_eq!(session.total_token_out, u64::MAX);
+    }
+
+    /// A version 1 session: 80-byte header, actions at offset 80.
+    fn legacy_session(actions: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; LEGACY_SESSION_HEADER_SIZE];
+        data[0] = 3;
+        data[2] = LEGACY_SESSION_VERSION;
+        data[8..40].copy_from_slice(&[1u8; 32]); // wallet
+        data[40..72].copy_from_slice(&[2u8; 32]); // session_key
+        data[72..80].copy_from_slice(&900u64.to_le_bytes()); // expires_at
+        data.extend_from_slice(actions);
+        data
+    }
+
+    #[test]
+    fn test_legacy_session_layout() {
+        let mut data = legacy_session(&[0xEE; 27]);
+        assert_eq!(session_header_size(&data), LEGACY_SESSION_HEADER_SIZE);
+        assert!(has_actions(&data));
+        assert_eq!(actions_slice(&data), &[0xEE; 27]);
+
+        let session = read_session(&data).unwrap();
+        assert_eq!(session.wallet, [1u8; 32]);
+        assert_eq!(session.session_key, [2u8; 32]);
+        assert_eq!(session.expires_at, 900);
+        assert_eq!(session.flags, 0);
+        assert_eq!(session.creator, [0u8; 32]);
+
+        // Statistics are skipped, rekeying keeps the action buffer in place
+        record_session_usage(&mut data, 5, 10, 10);
+        assert_eq!(data, legacy_session(&[0xEE; 27]));
+        rekey_session(&mut data, &[9u8; 32], 7);
+        assert_eq!(read_session(&data).unwrap().session_key, [9u8; 32]);
+        assert_eq!(actions_slice(&data), &[0xEE; 27]);
+
+        // A legacy session without actions is just its header
+        let bare = legacy_session(&[]);
+        assert!(!has_actions(&bare));
+        assert!(read_session(&bare[..79]).is_none());
+    }
+
+    #[test]
+    fn test_rekey_session_keeps_state() {
+        let mut data = vec![0u8; SESSION_HEADER_SIZE];
+        data[0] = 3;
+        data[2] = SESSION_VERSION;
+        data.extend_from_slice(&[0xCD; 43]);
+        record_session_usage(&mut data, 500, 10, 0);
+        let before = read_header(&data);
+
+        rekey_session(&mut data, &[9u8; 32], 251);
+
+        let after = read_header(&data);
+        assert_eq!(after.session_key, [9u8; 32]);
+        assert_eq!(after.bump, 251);
+        assert_eq!(after.discriminator, before.discriminator);
+        assert_eq!(after.expires_at, before.expires_at);
+        assert_eq!(after.use_count, before.use_count);
+        assert_eq!(after.total_sol_out, before.total_sol_out);
+        assert_eq!(actions_slice(&data), &[0xCD; 43]);
     }
 }
@@ -11,6 +11,35 @@ pub struct WalletAccount {
     pub bump: u8,
     /// Account Version.
     pub version: u8,
+    /// Wallet flags (`WALLET_FLAG_*`).
+    pub flags: u8,
     /// Padding for alignment.
-    pub _padding: [u8; 5],
+    pub _padding: [u8; 4],
+}
+
+/// `WalletAccount::flags`: the wallet's config enables the execution timelock.
+/// `Execute` and `ExecuteDeferred` must then be given the Wallet Config PDA.
+pub const WALLET_FLAG_TIMELOCK: u8 = 1 << 0;
+
+/// `WalletAccount::flags`: the wallet's config sets a step-up authority.
+/// `Execute` and `ExecuteDeferred` must then be given the Wallet Config PDA.
+pub const WALLET_FLAG_STEP_UP: u8 = 1 << 1;
+
+/// `WalletAccount::flags`: the wallet's config changes a security limit
+/// (signature age, session length, deferred lifetime, Admin permissions,
+/// proposal threshold, freeze settings).
+/// Processors that apply one must then be given the Wallet Config PDA.
+pub const WALLET_FLAG_LIMITS: u8 = 1 << 2;
+
+/// `WalletAccount::flags`: the wallet is frozen (`Freeze`). `Execute`,
+/// `ExecuteDeferred`, `CreateSession` and `SetWalletConfig` fail until an
+/// Owner unfreezes it.
+pub const WALLET_FLAG_FROZEN: u8 = 1 << 3;
+
+/// Reads `WalletAccount::flags` from a wallet's data (0 if it is too short).
+pub fn wallet_flags(wallet_data: &[u8]) -> u8 {
+    wallet_data
+        .get(std::mem::offset_of!(WalletAccount, flags))
+        .copied()
+        .unwrap_or(0)
 }
@@ -0,0 +1,703 @@
+//! Wallet-level configuration.
+//!
+//! A wallet may have a Wallet Config PDA `["wallet_config", wallet]`, written
+//! by an Owner with `SetWalletConfig`. Wallets without one use the program
+//! defaults. Every field is bounded by a program-wide limit.
+//!
+//! Besides the execution policies (proposals, timelock, step-up), the config
+//! holds the wallet's security limits: the Secp256r1 signature freshness
+//! window, the longest session, the deferred authorization lifetime and
+//! whether Admins may add Spenders, and the emergency freeze settings (a
+//! guardian key allowed to freeze the wallet, and the delay before an Owner
+//! can unfreeze it). A wallet whose limits differ from the defaults has
+//! `WALLET_FLAG_LIMITS`, and its processors must be given the Wallet Config
+//! PDA.
+//!
+//! The loaders at the end of this module read the config for processors and
+//! authenticators, following the wallet flags.
+//!
+//! The 176-byte header is followed by `sensitive_program_count` program ids
+//! (32 bytes each) whose invocation requires a queued, timelocked execution,
+//! then `timelock_mint_count` timelock and `step_up_mint_count` step-up token
+//! thresholds (`[mint: 32][max_amount: u64 LE]`, 40 bytes each).
+
+use no_padding::NoPadding;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{find_program_address, Pubkey},
+};
+use std::cell::Cell;
+
+use crate::{
+    error::AuthError,
+    state::{
+        proposal::MAX_PROPOSAL_APPROVERS,
+        wallet::{wallet_flags, WALLET_FLAG_LIMITS, WALLET_FLAG_STEP_UP, WALLET_FLAG_TIMELOCK},
+        AccountDiscriminator,
+    },
+};
+
+/// Size of the wallet config header (excluding the program and mint lists).
+pub const WALLET_CONFIG_HEADER_SIZE: usize = 176;
+
+/// Size of a timelock or step-up token threshold entry.
+pub const STEP_UP_MINT_ENTRY_SIZE: usize = 40;
+
+/// Default maximum lifetime of a deferred authorization (~1 hour at 400ms/slot).
+pub const DEFAULT_MAX_DEFERRED_EXPIRY_SLOTS: u64 = 9_000;
+
+/// Program-wide bound on the configurable deferred expiry (~30 days, the
+/// same as the longest session).
+pub const MAX_DEFERRED_EXPIRY_SLOTS_LIMIT: u64 = 6_480_000;
+
+/// Default minimum lifetime of a deferred authorization (~4 seconds).
+pub const DEFAULT_MIN_DEFERRED_EXPIRY_SLOTS: u64 = 10;
+
+/// Default freshness window of a Secp256r1 signature's slot (~1 minute).
+pub const DEFAULT_MAX_SIGNATURE_AGE_SLOTS: u64 = 150;
+
+/// Program-wide bounds on the configurable signature freshness window.
+pub const MIN_SIGNATURE_AGE_SLOTS_LIMIT: u64 = 10;
+pub const MAX_SIGNATURE_AGE_SLOTS_LIMIT: u64 = 300;
+
+/// Default and program-wide bound of the longest session (~30 days). Wallets
+/// can only shorten it.
+pub const MAX_SESSION_SLOTS_LIMIT: u64 = 6_480_000;
+
+/// `WalletConfigAccount::flags`: Admins may add Spenders (on by default).
+pub const WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS: u8 = 1 << 0;
+
+/// Default number of approvals needed to execute a proposal.
+pub const DEFAULT_PROPOSAL_THRESHOLD: u8 = 2;
+
+/// Program-wide bound on the execution timelock delay (~7 days).
+pub const MAX_TIMELOCK_SLOTS: u64 = 1_512_000;
+
+/// Program-wide bound on the unfreeze delay (~7 days, as the timelock).
+pub const MAX_UNFREEZE_DELAY_SLOTS: u64 = 1_512_000;
+
+/// Maximum number of sensitive programs.
+pub const MAX_SENSITIVE_PROGRAMS: usize = 8;
+
+/// Maximum number of step-up token thresholds.
+pub const MAX_STEP_UP_MINTS: usize = 4;
+
+/// Maximum number of timelock token thresholds.
+pub const MAX_TIMELOCK_MINTS: usize = 4;
+
+#[repr(C, align(8))]
+#[derive(NoPadding, Debug, Clone, Copy)]
+/// Wallet Config Account.
+pub struct WalletConfigAccount {
+    /// Account discriminator (must be `8` for WalletConfig).
+    pub discriminator: u8, // 1
+    /// Bump seed for this PDA.
+    pub bump: u8, // 1
+    /// Account Version.
+    pub version: u8, // 1
+    /// Approvals needed to execute a proposal.
+    pub proposal_threshold: u8, // 1
+    /// Number of sensitive program ids following the header.
+    pub sensitive_program_count: u8, // 1
+    /// Number of step-up token thresholds following the timelock thresholds.
+    pub step_up_mint_count: u8, // 1
+    /// Permission flags (`WALLET_CONFIG_FLAG_*`).
+    pub flags: u8, // 1
+    /// Number of timelock token thresholds following the sensitive programs.
+    pub timelock_mint_count: u8, // 1
+    /// The wallet this config belongs to.
+    pub wallet: Pubkey, // 32
+    /// Longest allowed lifetime of a deferred authorization, in slots.
+    pub max_deferred_expiry_slots: u64, // 8
+    /// Delay before a queued execution can run (0 = no timelock).
+    pub timelock_slots: u64, // 8
+    /// With the timelock enabled, a direct execution may not move more than
+    /// this many lamports out of the vault.
+    pub timelock_max_lamports: u64, // 8
+    /// Authority PDA that must approve executions above the step-up
+    /// thresholds (all zeros = no step-up).
+    pub step_up_authority: Pubkey, // 32
+    /// Vault SOL outflow (gross, per execution) above which the step-up
+    /// authority must approve.
+    pub step_up_lamports: u64, // 8
+    /// Oldest slot a Secp256r1 signature may be bound to, relative to now.
+    pub max_signature_age_slots: u64, // 8
+    /// Longest allowed session, in slots.
+    pub max_session_slots: u64, // 8
+    /// Shortest allowed lifetime of a deferred authorization, in slots.
+    pub min_deferred_expiry_slots: u64, // 8
+    /// Key that may `Freeze` the wallet besides its Owners (all zeros = none).
+    pub guardian: Pubkey, // 32
+    /// Delay between an Owner requesting `Unfreeze` and the wallet unfreezing
+    /// (0 = immediate).
+    pub unfreeze_delay_slots: u64, // 8
+    /// Slot from which a requested unfreeze can complete (0 = none pending).
+    /// Reset by `Freeze` and `SetWalletConfig`.
+    pub unfreeze_at: u64, // 8
+}
+
+impl WalletConfigAccount {
+    /// The configuration of a wallet without a Wallet Config PDA.
+    pub fn defaults(wallet: &Pubkey) -> Self {
+        Self {
+            discriminator: AccountDiscriminator::WalletConfig as u8,
+            bump: 0,
+            version: crate::state::CURRENT_ACCOUNT_VERSION,
+            proposal_threshold: DEFAULT_PROPOSAL_THRESHOLD,
+            sensitive_program_count: 0,
+            step_up_mint_count: 0,
+            flags: WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS,
+            timelock_mint_count: 0,
+            wallet: *wallet,
+            max_deferred_expiry_slots: DEFAULT_MAX_DEFERRED_EXPIRY_SLOTS,
+            timelock_slots: 0,
+            timelock_max_lamports: 0,
+            step_up_authority: [0; 32],
+            step_up_lamports: 0,
+            max_signature_age_slots: DEFAULT_MAX_SIGNATURE_AGE_SLOTS,
+            max_session_slots: MAX_SESSION_SLOTS_LIMIT,
+            min_deferred_expiry_slots: DEFAULT_MIN_DEFERRED_EXPIRY_SLOTS,
+            guardian: [0; 32],
+            unfreeze_delay_slots: 0,
+            unfreeze_at: 0,
+        }
+    }
+
+    /// Whether high-value executions must be queued.
+    pub fn timelock_enabled(&self) -> bool {
+        self.timelock_slots > 0
+    }
+
+    /// Whether high-value executions need the step-up authority's approval.
+    pub fn step_up_enabled(&self) -> bool {
+        self.step_up_authority != [0; 32]
+    }
+
+    /// Whether Admins may add Spenders.
+    pub fn admin_adds_spenders(&self) -> bool {
+        self.flags & WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS != 0
+    }
+
+    /// Whether a security limit differs from the program default, so the
+    /// wallet's processors must read this config (`WALLET_FLAG_LIMITS`).
+    pub fn overrides_limits(&self) -> bool {
+        let defaults = Self::defaults(&self.wallet);
+        self.flags != defaults.flags
+            || self.max_signature_age_slots != defaults.max_signature_age_slots
+            || self.max_session_slots != defaults.max_session_slots
+            || self.min_deferred_expiry_slots != defaults.min_deferred_expiry_slots
+            || self.max_deferred_expiry_slots != defaults.max_deferred_expiry_slots
+            || self.guardian != defaults.guardian
+            || self.unfreeze_delay_slots != defaults.unfreeze_delay_slots
+            || self.proposal_threshold != defaults.proposal_threshold
+    }
+
+    /// Checks every field against its program-wide bound.
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if self.min_deferred_expiry_slots == 0
+            || self.max_deferred_expiry_slots <= self.min_deferred_expiry_slots
+            || self.max_deferred_expiry_slots > MAX_DEFERRED_EXPIRY_SLOTS_LIMIT
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        if !(MIN_SIGNATURE_AGE_SLOTS_LIMIT..=MAX_SIGNATURE_AGE_SLOTS_LIMIT)
+            .contains(&self.max_signature_age_slots)
+            || self.max_session_slots == 0
+            || self.max_session_slots > MAX_SESSION_SLOTS_LIMIT
+            || self.flags & !WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS != 0
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        if self.proposal_threshold == 0 || self.proposal_threshold as usize > MAX_PROPOSAL_APPROVERS
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        if self.timelock_slots > MAX_TIMELOCK_SLOTS
+            || self.unfreeze_delay_slots > MAX_UNFREEZE_DELAY_SLOTS
+            || self.sensitive_program_count as usize > MAX_SENSITIVE_PROGRAMS
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        // Token thresholds only make sense with their timelock / step-up authority
+        if self.timelock_mint_count as usize > MAX_TIMELOCK_MINTS
+            || (self.timelock_mint_count > 0 && !self.timelock_enabled())
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        if self.step_up_mint_count as usize > MAX_STEP_UP_MINTS
+            || (self.step_up_mint_count > 0 && !self.step_up_enabled())
+        {
+            return Err(AuthError::InvalidWalletConfig.into());
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the timelock of `new` is at least as strict as that of
+/// `current`: no shorter delay, no higher direct outflow, every current
+/// sensitive program still listed and every current token threshold kept at
+/// the same or a lower amount. Only meaningful when `current` has the
+/// timelock enabled.
+pub fn timelock_not_weakened(current: &WalletConfigView, new: &WalletConfigView) -> bool {
+    new.account.timelock_slots >= current.account.timelock_slots
+        && new.account.timelock_max_lamports <= current.account.timelock_max_lamports
+        && current.sensitive_programs.chunks_exact(32).all(|program| {
+            new.sensitive_programs
+                .chunks_exact(32)
+                .any(|p| p == program)
+        })
+        && current.timelock_thresholds().all(|(mint, max)| {
+            new.timelock_thresholds()
+                .any(|(m, new_max)| m == mint && new_max <= max)
+        })
+}
+
+/// Returns whether `new` (with its lists) differs from `current` in the
+/// guardian at most. This is the only change an Owner can make to a frozen
+/// wallet's config: clearing or replacing a guardian that keeps re-freezing it.
+pub fn only_guardian_changed(current: &WalletConfigView, new: &WalletConfigView) -> bool {
+    let mut expected = current.account;
+    expected.guardian = new.account.guardian;
+    header_bytes(&expected) == header_bytes(&new.account)
+        && current.sensitive_programs == new.sensitive_programs
+        && current.timelock_mints == new.timelock_mints
+        && current.step_up_mints == new.step_up_mints
+}
+
+fn header_bytes(config: &WalletConfigAccount) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(
+            config as *const WalletConfigAccount as *const u8,
+            WALLET_CONFIG_HEADER_SIZE,
+        )
+    }
+}
+
+/// A wallet's configuration with its variable-length lists.
+#[derive(Clone, Copy)]
+pub struct WalletConfigView<'a> {
+    pub account: WalletConfigAccount,
+    /// Sensitive program ids, 32 bytes each.
+    pub sensitive_programs: &'a [u8],
+    /// Timelock token thresholds, `STEP_UP_MINT_ENTRY_SIZE` bytes each.
+    pub timelock_mints: &'a [u8],
+    /// Step-up token thresholds, `STEP_UP_MINT_ENTRY_SIZE` bytes each.
+    pub step_up_mints: &'a [u8],
+}
+
+impl WalletConfigView<'_> {
+    /// Iterates over the `(mint, max_amount)` timelock token thresholds.
+    pub fn timelock_thresholds(&self) -> impl Iterator<Item = ([u8; 32], u64)> + '_ {
+        token_thresholds(self.timelock_mints)
+    }
+
+    /// Iterates over the `(mint, max_amount)` step-up token thresholds.
+    pub fn step_up_thresholds(&self) -> impl Iterator<Item = ([u8; 32], u64)> + '_ {
+        token_thresholds(self.step_up_mints)
+    }
+}
+
+fn token_thresholds(entries: &[u8]) -> impl Iterator<Item = ([u8; 32], u64)> + '_ {
+    entries.chunks_exact(STEP_UP_MINT_ENTRY_SIZE).map(|entry| {
+        (
+            entry[..32].try_into().unwrap(),
+            u64::from_le_bytes(entry[32..].try_into().unwrap()),
+        )
+    })
+}
+
+/// Reads a wallet's configuration from its Wallet Config PDA data, or the
+/// defaults if the account has not been created.
+pub fn read_wallet_config<'a>(
+    data: &'a [u8],
+    wallet: &Pubkey,
+) -> Result<WalletConfigView<'a>, ProgramError> {
+    if data.is_empty() {
+        return Ok(WalletConfigView {
+            account: WalletConfigAccount::defaults(wallet),
+            sensitive_programs: &[],
+            timelock_mints: &[],
+            step_up_mints: &[],
+        });
+    }
+    if data.len() < WALLET_CONFIG_HEADER_SIZE {
+        return Err(AuthError::InvalidWalletConfig.into());
+    }
+    let config = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const WalletConfigAccount) };
+    if config.discriminator != AccountDiscriminator::WalletConfig as u8 || config.wallet != *wallet
+    {
+        return Err(AuthError::InvalidWalletConfig.into());
+    }
+    let lists = &data[WALLET_CONFIG_HEADER_SIZE..];
+    let programs_len = config.sensitive_program_count as usize * 32;
+    let timelock_mints_len = config.timelock_mint_count as usize * STEP_UP_MINT_ENTRY_SIZE;
+    if lists.len()
+        != programs_len
+            + timelock_mints_len
+            + config.step_up_mint_count as usize * STEP_UP_MINT_ENTRY_SIZE
+    {
+        return Err(AuthError::InvalidWalletConfig.into());
+    }
+    let (sensitive_programs, mints) = lists.split_at(programs_len);
+    let (timelock_mints, step_up_mints) = mints.split_at(timelock_mints_len);
+    Ok(WalletConfigView {
+        account: config,
+        sensitive_programs,
+        timelock_mints,
+        step_up_mints,
+    })
+}
+
+/// Loads the configuration of a wallet with an execution policy
+/// (`WALLET_FLAG_TIMELOCK` or `WALLET_FLAG_STEP_UP`) from its Wallet Config
+/// PDA, which must then be among `accounts`. Returns `None` for a wallet
+/// without one.
+pub fn load_execution_policy<'a>(
+    wallet_data: &[u8],
+    wallet: &Pubkey,
+    accounts: &'a [AccountInfo],
+    program_id: &Pubkey,
+) -> Result<Option<WalletConfigView<'a>>, ProgramError> {
+    if wallet_flags(wallet_data) & (WALLET_FLAG_TIMELOCK | WALLET_FLAG_STEP_UP) == 0 {
+        return Ok(None);
+    }
+    find_wallet_config(wallet, accounts, program_id).map(Some)
+}
+
+/// The security limits of a wallet for one instruction: from its Wallet
+/// Config PDA, which must then be among `accounts`, if the wallet has
+/// `WALLET_FLAG_LIMITS`, or the program defaults otherwise.
+///
+/// A processor creates one and passes it down to its authenticators. The
+/// config is only read when first needed (Ed25519 authorities never need the
+/// signature age), and its PDA is derived at most once per instruction.
+pub struct WalletLimits<'a> {
+    wallet_pda: &'a AccountInfo,
+    accounts: &'a [AccountInfo],
+    program_id: &'a Pubkey,
+    config_pda: Cell<Option<&'a AccountInfo>>,
+    limits: Cell<Option<WalletConfigAccount>>,
+}
+
+impl<'a> WalletLimits<'a> {
+    pub fn new(
+        wallet_pda: &'a AccountInfo,
+        accounts: &'a [AccountInfo],
+        program_id: &'a Pubkey,
+    ) -> Self {
+        Self {
+            wallet_pda,
+            accounts,
+            program_id,
+            config_pda: Cell::new(None),
+            limits: Cell::new(None),
+        }
+    }
+
+    /// Reuses a config the processor already read (its execution policy),
+    /// which holds the wallet's limits with or without `WALLET_FLAG_LIMITS`.
+    pub fn with_config(self, config: Option<&WalletConfigAccount>) -> Self {
+        self.limits.set(config.copied());
+        self
+    }
+
+    /// The wallet's security limits.
+    pub fn get(&self) -> Result<WalletConfigAccount, ProgramError> {
+        if let Some(limits) = self.limits.get() {
+            return Ok(limits);
+        }
+        let wallet = self.wallet_pda.key();
+        let limits = if wallet_flags(unsafe { self.wallet_pda.borrow_data_unchecked() })
+            & WALLET_FLAG_LIMITS
+            == 0
+        {
+            WalletConfigAccount::defaults(wallet)
+        } else {
+            let config_pda = self.config_pda()?;
+            read_wallet_config(unsafe { config_pda.borrow_data_unchecked() }, wallet)?.account
+        };
+        self.limits.set(Some(limits));
+        Ok(limits)
+    }
+
+    /// The wallet's Wallet Config PDA among `accounts` (see
+    /// `find_wallet_config_pda`).
+    pub fn config_pda(&self) -> Result<&'a AccountInfo, ProgramError> {
+        if let Some(config_pda) = self.config_pda.get() {
+            return Ok(config_pda);
+        }
+        let config_pda =
+            find_wallet_config_pda(self.wallet_pda.key(), self.accounts, self.program_id)?;
+        self.config_pda.set(Some(config_pda));
+        Ok(config_pda)
+    }
+}
+
+/// Finds the derived Wallet Config PDA of `wallet` among `accounts`.
+fn find_wallet_config<'a>(
+    wallet: &Pubkey,
+    accounts: &'a [AccountInfo],
+    program_id: &Pubkey,
+) -> Result<WalletConfigView<'a>, ProgramError> {
+    let config_pda = find_wallet_config_pda(wallet, accounts, program_id)?;
+    read_wallet_config(unsafe { config_pda.borrow_data_unchecked() }, wallet)
+}
+
+/// Finds the derived, program-owned Wallet Config PDA of `wallet` among
+/// `accounts`, for processors that update it.
+pub fn find_wallet_config_pda<'a>(
+    wallet: &Pubkey,
+    accounts: &'a [AccountInfo],
+    program_id: &Pubkey,
+) -> Result<&'a AccountInfo, ProgramError> {
+    let (config_key, _) = find_program_address(&[b"wallet_config", wallet.as_ref()], program_id);
+    let config_pda = accounts
+        .iter()
+        .find(|acc| *acc.key() == config_key)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if config_pda.owner() != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(config_pda)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_config_size() {
+        assert_eq!(
+            std::mem::size_of::<WalletConfigAccount>(),
+            WALLET_CONFIG_HEADER_SIZE
+        );
+    }
+
+    #[test]
+    fn test_read_wallet_config_defaults_and_binding() {
+        let wallet = [3u8; 32];
+        let view = read_wallet_config(&[], &wallet).unwrap();
+        let config = view.account;
+        assert_eq!(
+            config.max_deferred_expiry_slots,
+            DEFAULT_MAX_DEFERRED_EXPIRY_SLOTS
+        );
+        assert!(!config.timelock_enabled());
+        assert!(!config.step_up_enabled());
+        assert!(config.admin_adds_spenders());
+        assert!(!config.overrides_limits());
+        assert!(view.sensitive_programs.is_empty());
+        assert!(config.validate().is_ok());
+
+        let mut stored = config;
+        stored.max_deferred_expiry_slots = 216_000;
+        stored.sensitive_program_count = 1;
+        stored.timelock_mint_count = 1;
+        stored.step_up_mint_count = 1;
+        let lists = [
+            [7u8; 32].as_slice(),
+            &[9u8; 32],
+            &300u64.to_le_bytes(),
+            &[8u8; 32],
+            &500u64.to_le_bytes(),
+        ]
+        .concat();
+        let mut data = vec![0u8; WALLET_CONFIG_HEADER_SIZE];
+        unsafe {
+            std::ptr::write_unaligned(data.as_mut_ptr() as *mut WalletConfigAccount, stored);
+        }
+        data.extend_from_slice(&lists);
+        let read = read_wallet_config(&data, &wallet).unwrap();
+        assert_eq!(read.account.max_deferred_expiry_slots, 216_000);
+        assert!(read.account.overrides_limits());
+        assert_eq!(read.sensitive_programs, &[7u8; 32]);
+        assert_eq!(
+            read.timelock_thresholds().collect::<Vec<_>>(),
+            vec![([9u8; 32], 300)]
+        );
+        assert_eq!(
+            read.step_up_thresholds().collect::<Vec<_>>(),
+            vec![([8u8; 32], 500)]
+        );
+        assert!(read_wallet_config(&data, &[4u8; 32]).is_err());
+        // List counts must match the account length
+        assert!(read_wallet_config(&data[..WALLET_CONFIG_HEADER_SIZE + 32], &wallet).is_err());
+    }
+
+    #[test]
+    fn test_validate_bounds() {
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.max_deferred_expiry_slots = MAX_DEFERRED_EXPIRY_SLOTS_LIMIT;
+        assert!(config.validate().is_ok());
+        config.max_deferred_expiry_slots = MAX_DEFERRED_EXPIRY_SLOTS_LIMIT + 1;
+        assert!(config.validate().is_err());
+        // The lifetime can be shortened, down to just above the minimum
+        config.max_deferred_expiry_slots = DEFAULT_MIN_DEFERRED_EXPIRY_SLOTS + 1;
+        assert!(config.validate().is_ok());
+        config.max_deferred_expiry_slots = DEFAULT_MIN_DEFERRED_EXPIRY_SLOTS;
+        assert!(config.validate().is_err());
+        config.max_deferred_expiry_slots = DEFAULT_MAX_DEFERRED_EXPIRY_SLOTS;
+        config.min_deferred_expiry_slots = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.max_signature_age_slots = MIN_SIGNATURE_AGE_SLOTS_LIMIT;
+        assert!(config.validate().is_ok());
+        assert!(config.overrides_limits());
+        config.max_signature_age_slots = MAX_SIGNATURE_AGE_SLOTS_LIMIT + 1;
+        assert!(config.validate().is_err());
+        config.max_signature_age_slots = MIN_SIGNATURE_AGE_SLOTS_LIMIT - 1;
+        assert!(config.validate().is_err());
+
+        // Sessions can only be shortened
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.max_session_slots = 216_000;
+        assert!(config.validate().is_ok());
+        config.max_session_slots = MAX_SESSION_SLOTS_LIMIT + 1;
+        assert!(config.validate().is_err());
+        config.max_session_slots = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.flags = 0;
+        assert!(config.validate().is_ok());
+        assert!(!config.admin_adds_spenders());
+        assert!(config.overrides_limits());
+        config.flags = 1 << 7;
+        assert!(config.validate().is_err());
+
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.proposal_threshold = 3;
+        assert!(config.overrides_limits());
+        config.proposal_threshold = 0;
+        assert!(config.validate().is_err());
+        config.proposal_threshold = MAX_PROPOSAL_APPROVERS as u8 + 1;
+        assert!(config.validate().is_err());
+
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.timelock_slots = MAX_TIMELOCK_SLOTS + 1;
+        assert!(config.validate().is_err());
+        config.timelock_slots = MAX_TIMELOCK_SLOTS;
+        config.sensitive_program_count = MAX_SENSITIVE_PROGRAMS as u8 + 1;
+        assert!(config.validate().is_err());
+
+        // Timelock token thresholds need the timelock
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.timelock_mint_count = 1;
+        assert!(config.validate().is_err());
+        config.timelock_slots = 1_000;
+        assert!(config.validate().is_ok());
+        config.timelock_mint_count = MAX_TIMELOCK_MINTS as u8 + 1;
+        assert!(config.validate().is_err());
+
+        // A guardian or an unfreeze delay must be applied by Freeze/Unfreeze
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.guardian = [6u8; 32];
+        assert!(config.validate().is_ok());
+        assert!(config.overrides_limits());
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.unfreeze_delay_slots = MAX_UNFREEZE_DELAY_SLOTS;
+        assert!(config.validate().is_ok());
+        assert!(config.overrides_limits());
+        config.unfreeze_delay_slots = MAX_UNFREEZE_DELAY_SLOTS + 1;
+        assert!(config.validate().is_err());
+
+        // Token thresholds need a step-up authority
+        let mut config = WalletConfigAccount::defaults(&[0u8; 32]);
+        config.step_up_mint_count = 1;
+        assert!(config.validate().is_err());
+        config.step_up_authority = [5u8; 32];
+        assert!(config.validate().is_ok());
+        config.step_up_mint_count = MAX_STEP_UP_MINTS as u8 + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_only_guardian_changed() {
+        let mut account = WalletConfigAccount::defaults(&[0u8; 32]);
+        account.guardian = [6u8; 32];
+        account.unfreeze_delay_slots = 1_000;
+        account.unfreeze_at = 5_000;
+        let current = WalletConfigView {
+            account,
+            sensitive_programs: &[7u8; 32],
+            timelock_mints: &[],
+            step_up_mints: &[],
+        };
+
+        // Clearing or replacing the guardian, everything else resubmitted
+        let mut new = current;
+        new.account.guardian = [0u8; 32];
+        assert!(only_guardian_changed(&current, &new));
+        new.account.guardian = [9u8; 32];
+        assert!(only_guardian_changed(&current, &new));
+
+        // Any other change, including a shorter unfreeze delay, is rejected
+        let mut delay = new;
+        delay.account.unfreeze_delay_slots = 0;
+        assert!(!only_guardian_changed(&current, &delay));
+        let mut pending = new;
+        pending.account.unfreeze_at = 0;
+        assert!(!only_guardian_changed(&current, &pending));
+        new.sensitive_programs = &[];
+        assert!(!only_guardian_changed(&current, &new));
+    }
+
+    #[test]
+    fn test_timelock_can_only_be_tightened() {
+        let mut account = WalletConfigAccount::defaults(&[0u8; 32]);
+        account.timelock_slots = 1_000;
+        account.timelock_max_lamports = 5_000;
+        let programs = [[1u8; 32], [2u8; 32]].concat();
+        let mints = [[4u8; 32].as_slice(), &700u64.to_le_bytes()].concat();
+        let current = WalletConfigView {
+            account,
+            sensitive_programs: &programs,
+            timelock_mints: &mints,
+            step_up_mints: &[],
+        };
+
+        let more_programs = [[2u8; 32], [3u8; 32], [1u8; 32]].concat();
+        let lower_mints = [
+            [5u8; 32].as_slice(),
+            &0u64.to_le_bytes(),
+            &[4u8; 32],
+            &600u64.to_le_bytes(),
+        ]
+        .concat();
+        let mut stricter = WalletConfigView {
+            sensitive_programs: &more_programs,
+            timelock_mints: &lower_mints,
+            ..current
+        };
+        stricter.account.timelock_slots = 2_000;
+        stricter.account.timelock_max_lamports = 0;
+        assert!(timelock_not_weakened(&current, &stricter));
+
+        let mut shorter = current;
+        shorter.account.timelock_slots = 999;
+        assert!(!timelock_not_weakened(&current, &shorter));
+        let mut looser = current;
+        looser.account.timelock_max_lamports = 5_001;
+        assert!(!timelock_not_weakened(&current, &looser));
+        let fewer_programs = WalletConfigView {
+            sensitive_programs: &[1u8; 32],
+            ..current
+        };
+        assert!(!timelock_not_weakened(&current, &fewer_programs));
+
+        // Token thresholds can be lowered or added, but not raised or dropped
+        let higher_mints = [[4u8; 32].as_slice(), &701u64.to_le_bytes()].concat();
+        let higher = WalletConfigView {
+            timelock_mints: &higher_mints,
+            ..current
+        };
+        assert!(!timelock_not_weakened(&current, &higher));
+        let dropped = WalletConfigView {
+            timelock_mints: &[],
+            ..current
+        };
+        assert!(!timelock_not_weakened(&current, &dropped));
+    }
+}
@@ -20,6 +20,42 @@ pub fn get_stack_height() -> u64 {
     0
 }
 
+/// Transfers `amount` lamports from `payer` to `target` via the System Program.
+///
+/// `payer` must be a signer of the outer transaction.
+pub fn transfer_lamports(
+    payer: &AccountInfo,
+    target: &AccountInfo,
+    system_program: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    // System Program Transfer instruction (discriminator: 2)
+    let mut transfer_data = Vec::with_capacity(12);
+    transfer_data.extend_from_slice(&2u32.to_le_bytes());
+    transfer_data.extend_from_slice(&amount.to_le_bytes());
+
+    let transfer_accounts = [
+        AccountMeta {
+            pubkey: payer.key(),
+            is_signer: true,
+            is_writable: true,
+        },
+        AccountMeta {
+            pubkey: target.key(),
+            is_signer: false,
+            is_writable: true,
+        },
+    ];
+
+    let transfer_ix = Instruction {
+        program_id: &Pubkey::from(SYSTEM_PROGRAM_ID),
+        accounts: &transfer_accounts,
+        data: &transfer_data,
+    };
+
+    pinocchio::program::invoke(&transfer_ix, &[payer, target, system_program])
+}
+
 /// Safely initializes a PDA account using transfer-allocate-assign pattern.
 ///
 /// This prevents DoS attacks where malicious actors pre-fund target accounts
@@ -73,31 +109,7 @@ pub fn initialize_pda_account(
             .checked_sub(current_balance)
             .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        // System Program Transfer instruction (discriminator: 2)
-        let mut transfer_data = Vec::with_capacity(12);
-        transfer_data.extend_from_slice(&2u32.to_le_bytes());
-        transfer_data.extend_from_slice(&transfer_amount.to_le_bytes());
-
-        let transfer_accounts = [
-            AccountMeta {
-                pubkey: payer.key(),
-                is_signer: true,
-                is_writable: true,
-            },
-            AccountMeta {
-                pubkey: target_pda.key(),
-                is_signer: false,
-                is_writable: true,
-            },
-        ];
-
-        let transfer_ix = Instruction {
-            program_id: &Pubkey::from(SYSTEM_PROGRAM_ID),
-            accounts: &transfer_accounts,
-            data: &transfer_data,
-        };
-
-        pinocchio::program::invoke(&transfer_ix, &[&payer, &target_pda, &system_program])?;
+        transfer_lamports(payer, target_pda, system_program, transfer_amount)?;
     }
 
     // Step 2: Allocate space
@@ -119,7 +131,7 @@ pub fn initialize_pda_account(
     };
 
     let signer: Signer = pda_seeds.into();
-    invoke_signed(&allocate_ix, &[&target_pda, &system_program], &[signer])?;
+    invoke_signed(&allocate_ix, &[target_pda, system_program], &[signer])?;
 
     // Step 3: Assign ownership to target program
     // System Program Assign instruction (discriminator: 1)
@@ -140,7 +152,45 @@ pub fn initialize_pda_account(
     };
 
     let signer: Signer = pda_seeds.into();
-    invoke_signed(&assign_ix, &[&target_pda, &system_program], &[signer])?;
+    invoke_signed(&assign_ix, &[target_pda, system_program], &[signer])?;
 
     Ok(())
 }
+
+/// Splits a payload carrying a second, length-suffixed payload,
+/// `[first][second][second_len(2)]`, into `(first, second)`. Used where one
+/// instruction authenticates two authorities, e.g. a step-up co-signer or a
+/// new authority proving possession of its key.
+pub fn split_trailing_payload(payload: &[u8]) -> Result<(&[u8], &[u8]), ProgramError> {
+    let len_offset = payload
+        .len()
+        .checked_sub(2)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let second_len = u16::from_le_bytes([payload[len_offset], payload[len_offset + 1]]) as usize;
+    let split = len_offset
+        .checked_sub(second_len)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    Ok((&payload[..split], &payload[split..len_offset]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_trailing_payload() {
+        let mut data = vec![1u8, 2, 3, 9, 9];
+        data.extend_from_slice(&2u16.to_le_bytes());
+        let (first, second) = split_trailing_payload(&data).unwrap();
+        assert_eq!(first, &[1, 2, 3]);
+        assert_eq!(second, &[9, 9]);
+
+        // Ed25519 second authority: empty payload
+        let (first, second) = split_trailing_payload(&[5, 0, 0]).unwrap();
+        assert_eq!(first, &[5]);
+        assert!(second.is_empty());
+
+        assert!(split_trailing_payload(&[1]).is_err());
+        assert!(split_trailing_payload(&[1, 4, 0]).is_err());
+    }
+}
@@ -159,7 +159,7 @@ describe('Deferred Execution', () => {
       const deferredAccount =
         await ctx.connection.getAccountInfo(deferredExecPda);
       expect(deferredAccount).not.toBeNull();
-      expect(deferredAccount!.data.length).toBe(176);
+      expect(deferredAccount!.data.length).toBe(192);
       expect(deferredAccount!.data[0]).toBe(4); // DeferredExec discriminator
 
       // === TX2: ExecuteDeferred ===
@@ -178,6 +178,7 @@ describe('Deferred Execution', () => {
             isWritable: false,
           },
           { pubkey: recipient, isSigner: false, isWritable: true },
+          { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
         ],
       programId: PROGRAM_ID,
       });
@@ -299,6 +300,7 @@ describe('Deferred Execution', () => {
             { pubkey: recipient1, isSigner: false, isWritable: true },
             { pubkey: recipient2, isSigner: false, isWritable: true },
             { pubkey: recipient3, isSigner: false, isWritable: true },
+            { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
           ],
       programId: PROGRAM_ID,
         }),
@@ -452,6 +454,7 @@ describe('Deferred Execution', () => {
                 isWritable: false,
               },
               { pubkey: recipient, isSigner: false, isWritable: true },
+              { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
             ],
       programId: PROGRAM_ID,
           }),
@@ -477,6 +480,7 @@ describe('Deferred Execution', () => {
               isWritable: false,
             },
             { pubkey: recipient, isSigner: false, isWritable: true },
+            { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
           ],
       programId: PROGRAM_ID,
         }),
@@ -563,6 +567,7 @@ describe('Deferred Execution', () => {
             isWritable: false,
           },
           { pubkey: recipient, isSigner: false, isWritable: true },
+          { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
         ],
       programId: PROGRAM_ID,
       });
@@ -585,6 +590,7 @@ describe('Deferred Execution', () => {
               isWritable: false,
             },
             { pubkey: recipient, isSigner: false, isWritable: true },
+            { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
           ],
       programId: PROGRAM_ID,
         }),
@@ -687,6 +693,7 @@ describe('Deferred Execution', () => {
               isWritable: false,
             },
             { pubkey: recipient, isSigner: false, isWritable: true },
+            { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
           ],
       programId: PROGRAM_ID,
         }),
@@ -805,6 +812,7 @@ describe('Deferred Execution', () => {
                 isWritable: false,
               },
               { pubkey: recipient, isSigner: false, isWritable: true },
+              { pubkey: ownerAuthorityPda, isSigner: false, isWritable: false },
             ],
       programId: PROGRAM_ID,
           }),
@@ -7,6 +7,8 @@
  * - Admin removing owner
  * - Admin adding admin (admin can only add spender)
  * - Self-removal
+ *
+ * and that an owner can remove a co-owner.
  */
 import { describe, it, expect, beforeAll } from 'vitest';
 import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
@@ -142,7 +144,7 @@ describe('Permission Boundaries', () => {
       targetAuthorityPda: ownerAuthPda,
     });
 
-    // Error 3002 = PermissionDenied (owner cannot be removed)
+    // Error 3002 = PermissionDenied (only an owner can remove an owner)
     await sendTxExpectError(ctx, instructions, [adminKp], 3002);
   });
 
@@ -190,6 +192,38 @@ describe('Permission Boundaries', () => {
     await sendTxExpectError(ctx, instructions, [adminKp], 3002);
   });
 
+  it('owner can remove a co-owner, never itself', async () => {
+    // Add a second owner
+    const coOwnerKp = Keypair.generate();
+    const addResult = await client.addAuthority({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(ownerKp.publicKey, ownerAuthPda),
+      newAuthority: { type: 'ed25519', publicKey: coOwnerKp.publicKey },
+      role: 0, // Owner
+    });
+    await sendTx(ctx, addResult.instructions, [ownerKp]);
+
+    // The co-owner removes the original owner
+    const removeOwner = await client.removeAuthority({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(coOwnerKp.publicKey, addResult.newAuthorityPda),
+      targetAuthorityPda: ownerAuthPda,
+    });
+    await sendTx(ctx, removeOwner.instructions, [coOwnerKp]);
+    expect(await ctx.connection.getAccountInfo(ownerAuthPda)).toBeNull();
+
+    // The remaining owner is the last one and cannot remove itself
+    const selfRemove = await client.removeAuthority({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(coOwnerKp.publicKey, addResult.newAuthorityPda),
+      targetAuthorityPda: addResult.newAuthorityPda,
+    });
+    await sendTxExpectError(ctx, selfRemove.instructions, [coOwnerKp], 3002);
+  });
+
   // ─── CreateSession permission boundaries ────────────────────────
 
   it('spender cannot create session', async () => {
@@ -0,0 +1,122 @@
+/**
+ * CreateSession instruction-data forms (E2E).
+ *
+ * Session flags are opt-in: the flags byte after the actions buffer is only
+ * read when bit 15 of `actions_len` is set. Clients that predate flags send
+ * `[actions_len][actions][auth_payload]` and must keep working.
+ *
+ * Coverage:
+ * - Unflagged form with actions: flags = 0, session at the current layout
+ * - Flagged form: flags byte recorded in the session header
+ * - Flagged form with an undefined flag bit is rejected
+ * - Versioned action buffers: advisory unknown actions skipped, critical rejected
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, findSessionPda } from '@lazorkit/sdk-legacy';
+import {
+  createCreateSessionIx,
+  SESSION_FLAG_REQUIRE_CREATOR,
+  SESSION_VERSION,
+  versionActionsBuffer,
+} from './instructions';
+
+/** SolMaxPerTx (type 3): header(11) + max(8). */
+function solMaxPerTx(max: bigint): Uint8Array {
+  const buf = Buffer.alloc(19);
+  buf.writeUInt8(3, 0);
+  buf.writeUInt16LE(8, 1);
+  buf.writeBigUInt64LE(0n, 3);
+  buf.writeBigUInt64LE(max, 11);
+  return buf;
+}
+
+describe('CreateSession flags', () => {
+  let ctx: TestContext;
+  let client: LazorKitClient;
+  let walletPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+  });
+
+  async function buildIx(opts: { flags?: number; actions?: Uint8Array }) {
+    const sessionKp = Keypair.generate();
+    const [sessionPda] = findSessionPda(walletPda, sessionKp.publicKey.toBytes(), PROGRAM_ID);
+    const ix = createCreateSessionIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorityPda: ownerAuthPda,
+      sessionPda,
+      sessionKey: sessionKp.publicKey,
+      expiresAt: (await getSlot(ctx)) + 9000n,
+      actions: opts.actions,
+      flags: opts.flags,
+      authorizerSigner: ownerKp.publicKey,
+    });
+    return { ix, sessionPda };
+  }
+
+  it('accepts the unflagged form with actions (pre-flags clients)', async () => {
+    const { ix, sessionPda } = await buildIx({ actions: solMaxPerTx(1_000_000n) });
+    await sendTx(ctx, [ix], [ownerKp]);
+
+    const info = await ctx.connection.getAccountInfo(sessionPda);
+    expect(info).not.toBeNull();
+    expect(info!.data[2]).toBe(SESSION_VERSION);
+    expect(info!.data[3]).toBe(0);
+  });
+
+  it('records flags sent in the flagged form', async () => {
+    const { ix, sessionPda } = await buildIx({
+      actions: solMaxPerTx(1_000_000n),
+      flags: SESSION_FLAG_REQUIRE_CREATOR,
+    });
+    await sendTx(ctx, [ix], [ownerKp]);
+
+    const info = await ctx.connection.getAccountInfo(sessionPda);
+    expect(info!.data[3]).toBe(SESSION_FLAG_REQUIRE_CREATOR);
+  });
+
+  it('rejects undefined flag bits', async () => {
+    const { ix } = await buildIx({ flags: 0x80 });
+    await sendTxExpectError(ctx, [ix], [ownerKp]);
+  });
+
+  it('accepts versioned buffers and skips unknown advisory actions', async () => {
+    // Type 99 is unknown to the program: advisory is skipped, critical is rejected.
+    const unknown = Buffer.alloc(11 + 4);
+    unknown.writeUInt8(99, 0);
+    unknown.writeUInt16LE(4, 1);
+    const actions = Buffer.concat([solMaxPerTx(1_000_000n), unknown]);
+
+    const { ix, sessionPda } = await buildIx({ actions: versionActionsBuffer(actions, [99]) });
+    await sendTx(ctx, [ix], [ownerKp]);
+    expect(await ctx.connection.getAccountInfo(sessionPda)).not.toBeNull();
+
+    const { ix: rejected } = await buildIx({ actions: versionActionsBuffer(actions) });
+    await sendTxExpectError(ctx, [rejected], [ownerKp], 3037);
+  });
+});
@@ -0,0 +1,156 @@
+/**
+ * RotateSession payer binding (E2E).
+ *
+ * The admin's signature covers `old_session ‖ new_session_key ‖ payer`, so a
+ * relayer cannot submit it with its own payer to collect the old session's
+ * rent refund.
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { generateMockSecp256r1Key, signSecp256r1, type MockSecp256r1Key } from './secp256r1Utils';
+import {
+  findWalletPda,
+  findVaultPda,
+  findAuthorityPda,
+  findSessionPda,
+  createCreateWalletIx,
+  AUTH_TYPE_SECP256R1,
+} from '@lazorkit/sdk-legacy';
+import {
+  createCreateSessionIx,
+  createRotateSessionIx,
+  encodeCreateSessionArgs,
+  rotateSessionSignedPayload,
+  DISC_CREATE_SESSION,
+  DISC_ROTATE_SESSION,
+} from './instructions';
+
+const SYSVAR_IX = { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false };
+
+describe('RotateSession', () => {
+  let ctx: TestContext;
+  let ownerKey: MockSecp256r1Key;
+  let walletPda: PublicKey;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    ownerKey = await generateMockSecp256r1Key();
+    const userSeed = crypto.randomBytes(32);
+    [walletPda] = findWalletPda(userSeed, PROGRAM_ID);
+    const [vaultPda] = findVaultPda(walletPda, PROGRAM_ID);
+    let authBump: number;
+    [ownerAuthPda, authBump] = findAuthorityPda(walletPda, ownerKey.credentialIdHash, PROGRAM_ID);
+
+    await sendTx(ctx, [
+      createCreateWalletIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        authorityPda: ownerAuthPda,
+        userSeed,
+        authType: AUTH_TYPE_SECP256R1,
+        authBump,
+        credentialOrPubkey: ownerKey.credentialIdHash,
+        secp256r1Pubkey: ownerKey.publicKeyBytes,
+        rpId: ownerKey.rpId,
+        programId: PROGRAM_ID,
+      }),
+    ]);
+  });
+
+  async function createSession(): Promise<PublicKey> {
+    const sessionKey = Keypair.generate().publicKey;
+    const [sessionPda] = findSessionPda(walletPda, sessionKey.toBytes(), PROGRAM_ID);
+    const expiresAt = (await getSlot(ctx)) + 9000n;
+    const args = encodeCreateSessionArgs({ sessionKey, expiresAt });
+    const { authPayload, precompileIx } = await signSecp256r1({
+      key: ownerKey,
+      discriminator: new Uint8Array([DISC_CREATE_SESSION]),
+      signedPayload: Buffer.concat([args, ctx.payer.publicKey.toBuffer()]),
+      slot: await getSlot(ctx),
+      counter: ++counter,
+      payer: ctx.payer.publicKey,
+      sysvarIxIndex: 6,
+    });
+    await sendTx(ctx, [
+      precompileIx,
+      createCreateSessionIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        authorityPda: ownerAuthPda,
+        sessionPda,
+        sessionKey,
+        expiresAt,
+        authPayload,
+        extraAccounts: [SYSVAR_IX],
+      }),
+    ]);
+    return sessionPda;
+  }
+
+  async function rotateIxs(oldSessionPda: PublicKey, signedFor: PublicKey, payer: PublicKey) {
+    const newSessionKey = Keypair.generate().publicKey;
+    const [newSessionPda] = findSessionPda(walletPda, newSessionKey.toBytes(), PROGRAM_ID);
+    const { authPayload, precompileIx } = await signSecp256r1({
+      key: ownerKey,
+      discriminator: new Uint8Array([DISC_ROTATE_SESSION]),
+      signedPayload: rotateSessionSignedPayload(oldSessionPda, newSessionKey, signedFor),
+      slot: await getSlot(ctx),
+      counter: counter + 1,
+      payer: signedFor,
+      sysvarIxIndex: 6,
+    });
+    const ix = createRotateSessionIx({
+      payer,
+      walletPda,
+      authorityPda: ownerAuthPda,
+      oldSessionPda,
+      newSessionPda,
+      newSessionKey,
+      authPayload,
+      extraAccounts: [SYSVAR_IX],
+    });
+    return { ixs: [precompileIx, ix], newSessionPda };
+  }
+
+  it('rejects a signature relayed with another payer', async () => {
+    const oldSessionPda = await createSession();
+
+    const relayer = Keypair.generate();
+    const sig = await ctx.connection.requestAirdrop(relayer.publicKey, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+
+    // The owner signed for ctx.payer; the relayer submits as the payer.
+    const { ixs } = await rotateIxs(oldSessionPda, ctx.payer.publicKey, relayer.publicKey);
+    await sendTxExpectError(ctx, ixs, [relayer]);
+
+    // The session is untouched and can still be rotated by the intended payer.
+    const { ixs: okIxs, newSessionPda } = await rotateIxs(
+      oldSessionPda,
+      ctx.payer.publicKey,
+      ctx.payer.publicKey,
+    );
+    await sendTx(ctx, okIxs);
+    counter++;
+
+    expect(await ctx.connection.getAccountInfo(newSessionPda)).not.toBeNull();
+    const old = await ctx.connection.getAccountInfo(oldSessionPda);
+    expect(old === null || old.lamports === 0).toBe(true);
+  });
+});
@@ -0,0 +1,291 @@
+/**
+ * Authority expiry and permissionless CloseExpiredAuthority (E2E).
+ *
+ * An Admin or Spender added with `REGISTER_FLAG_EXPIRES` carries a 40-byte
+ * expiry trailer (`[expires_at][funder]`) after its authority data; the
+ * 48-byte header is unchanged. Once the slot passes, anyone can close it and
+ * the rent goes back to the funder that paid for it.
+ *
+ * Coverage:
+ * - The expiry trailer is written after the Ed25519 authority data
+ * - Closing before expiry is rejected
+ * - A stranger closes it after expiry, rent to the funder
+ * - Authorities without an expiry cannot be closed
+ * - An expiring Admin cannot authorize or create a session past its expiry
+ * - An Admin's DeferredExec needs its Authority PDA, and fails once it is removed
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  Transaction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import {
+  LazorKitClient,
+  ROLE_ADMIN,
+  ROLE_SPENDER,
+  createExecuteDeferredIx,
+  ed25519,
+  findAuthorityPda,
+  findDeferredExecPda,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAddEd25519AuthorityIx,
+  createAuthorizeVariantIx,
+  createCloseExpiredAuthorityIx,
+  deferredTransfer,
+  encodeAuthorizeArgs,
+  AUTHORITY_EXPIRY_EXTENSION_SIZE,
+  DISC_AUTHORIZE,
+} from './instructions';
+
+/** Ed25519 authority: header(48) + pubkey(32). */
+const ED25519_AUTHORITY_SIZE = 80;
+const PERMISSION_DENIED = 3002;
+const INVALID_SESSION_DURATION = 3008;
+const INVALID_EXPIRY_WINDOW = 3016;
+
+async function waitForSlot(ctx: TestContext, slot: bigint): Promise<void> {
+  while ((await getSlot(ctx)) <= slot) {
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+}
+
+describe('CloseExpiredAuthority', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let stranger: Keypair;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    stranger = Keypair.generate();
+    const sig = await ctx.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** Adds an Ed25519 Spender, paid by ctx.payer, expiring at `expiresAt`. */
+  async function addSpender(expiresAt?: bigint): Promise<PublicKey> {
+    const spender = Keypair.generate().publicKey;
+    const [authorityPda] = findAuthorityPda(walletPda, spender.toBytes(), PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAddEd25519AuthorityIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          adminAuthorityPda: ownerAuthPda,
+          adminSigner: ownerKp.publicKey,
+          newAuthorityPda: authorityPda,
+          newPubkey: spender,
+          role: ROLE_SPENDER,
+          expiresAt,
+        }),
+      ],
+      [ownerKp],
+    );
+    return authorityPda;
+  }
+
+  function closeIx(authorityPda: PublicKey) {
+    return createCloseExpiredAuthorityIx({
+      walletPda,
+      authorityPda,
+      funder: ctx.payer.publicKey,
+    });
+  }
+
+  it('stores the expiry trailer after the authority data', async () => {
+    const expiresAt = (await getSlot(ctx)) + 9000n;
+    const authorityPda = await addSpender(expiresAt);
+
+    const info = await ctx.connection.getAccountInfo(authorityPda);
+    expect(info!.data.length).toBe(ED25519_AUTHORITY_SIZE + AUTHORITY_EXPIRY_EXTENSION_SIZE);
+    const trailer = info!.data.subarray(ED25519_AUTHORITY_SIZE);
+    expect(trailer.readBigUInt64LE(0)).toBe(expiresAt);
+    expect(new PublicKey(trailer.subarray(8, 40)).equals(ctx.payer.publicKey)).toBe(true);
+
+    await sendTxExpectError(ctx, [closeIx(authorityPda)], [], PERMISSION_DENIED);
+  });
+
+  it('lets anyone close an expired authority, rent to the funder', async () => {
+    const expiresAt = (await getSlot(ctx)) + 3n;
+    const authorityPda = await addSpender(expiresAt);
+    const rent = (await ctx.connection.getAccountInfo(authorityPda))!.lamports;
+    await waitForSlot(ctx, expiresAt);
+
+    const funderBefore = await ctx.connection.getBalance(ctx.payer.publicKey);
+    // A stranger submits and pays the fee; the funder only receives the rent.
+    await sendAndConfirmTransaction(
+      ctx.connection,
+      new Transaction().add(closeIx(authorityPda)),
+      [stranger],
+      { commitment: 'confirmed' },
+    );
+
+    const closed = await ctx.connection.getAccountInfo(authorityPda);
+    expect(closed === null || closed.lamports === 0).toBe(true);
+    expect(await ctx.connection.getBalance(ctx.payer.publicKey)).toBe(funderBefore + rent);
+  });
+
+  it('rejects authorities registered without an expiry', async () => {
+    const authorityPda = await addSpender();
+    await sendTxExpectError(ctx, [closeIx(authorityPda)], [], PERMISSION_DENIED);
+    await sendTxExpectError(ctx, [closeIx(ownerAuthPda)], [], PERMISSION_DENIED);
+  });
+});
+
+describe('Expiring authorizers', () => {
+  let ctx: TestContext;
+  let client: LazorKitClient;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** Adds an Ed25519 Admin expiring at `expiresAt`. */
+  async function addAdmin(expiresAt: bigint) {
+    const adminKp = Keypair.generate();
+    const [adminAuthPda] = findAuthorityPda(walletPda, adminKp.publicKey.toBytes(), PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAddEd25519AuthorityIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          adminAuthorityPda: ownerAuthPda,
+          adminSigner: ownerKp.publicKey,
+          newAuthorityPda: adminAuthPda,
+          newPubkey: adminKp.publicKey,
+          role: ROLE_ADMIN,
+          expiresAt,
+        }),
+      ],
+      [ownerKp],
+    );
+    return { adminKp, adminAuthPda };
+  }
+
+  /** The Admin's first authorization of a 0.01 SOL transfer, valid for `expiryOffset` slots. */
+  function authorizeIx(adminKp: Keypair, adminAuthPda: PublicKey, expiryOffset: number) {
+    const recipient = Keypair.generate().publicKey;
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+    });
+    const [deferredExecPda] = findDeferredExecPda(walletPda, adminAuthPda, 1, PROGRAM_ID);
+    const ix = createAuthorizeVariantIx({
+      discriminator: DISC_AUTHORIZE,
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorizerPda: adminAuthPda,
+      deferredExecPda,
+      args: encodeAuthorizeArgs(transfer.instructionsHash, transfer.accountsHash, expiryOffset),
+      authorizerSigner: adminKp.publicKey,
+    });
+    return { ix, transfer, deferredExecPda };
+  }
+
+  it('rejects an authorization outliving the Admin', async () => {
+    const { adminKp, adminAuthPda } = await addAdmin((await getSlot(ctx)) + 100n);
+    const { ix } = authorizeIx(adminKp, adminAuthPda, 300);
+    await sendTxExpectError(ctx, [ix], [adminKp], INVALID_EXPIRY_WINDOW);
+  });
+
+  it('rejects a session outliving the Admin that creates it', async () => {
+    const expiresAt = (await getSlot(ctx)) + 100n;
+    const { adminKp, adminAuthPda } = await addAdmin(expiresAt);
+    const create = (sessionExpiresAt: bigint) =>
+      client.createSession({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        adminSigner: ed25519(adminKp.publicKey, adminAuthPda),
+        sessionKey: Keypair.generate().publicKey,
+        expiresAt: sessionExpiresAt,
+      });
+
+    const tooLate = await create(expiresAt + 1n);
+    await sendTxExpectError(ctx, tooLate.instructions, [adminKp], INVALID_SESSION_DURATION);
+    const inTime = await create(expiresAt);
+    await sendTx(ctx, inTime.instructions, [adminKp]);
+  });
+
+  it('needs a live authorizer to run an Admin authorization', async () => {
+    const { adminKp, adminAuthPda } = await addAdmin((await getSlot(ctx)) + 9000n);
+    const { ix, transfer, deferredExecPda } = authorizeIx(adminKp, adminAuthPda, 300);
+    await sendTx(ctx, [ix], [adminKp]);
+
+    const execute = (remainingAccounts: typeof transfer.remainingAccounts) =>
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts,
+        programId: PROGRAM_ID,
+      });
+    const adminAccount = { pubkey: adminAuthPda, isSigner: false, isWritable: false };
+
+    // Without the authorizer's Authority PDA its liveness cannot be checked
+    await sendTxExpectError(ctx, [execute(transfer.remainingAccounts)]);
+
+    const { instructions } = await client.removeAuthority({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(ownerKp.publicKey, ownerAuthPda),
+      targetAuthorityPda: adminAuthPda,
+    });
+    await sendTx(ctx, instructions, [ownerKp]);
+
+    await sendTxExpectError(ctx, [execute([...transfer.remainingAccounts, adminAccount])]);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).not.toBeNull();
+  });
+});
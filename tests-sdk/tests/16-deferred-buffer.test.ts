@@ -0,0 +1,228 @@
+/**
+ * Chunked Deferred Buffer payloads (E2E).
+ *
+ * A payload too large for the ExecuteDeferred transaction is written to the
+ * Deferred Buffer PDA in chunks after Authorize, then executed with empty
+ * instruction data. The buffer must hash to the signed `instructions_hash`.
+ *
+ * Coverage:
+ * - A ~1.3 KB payload (memo + transfer) written in two chunks and executed
+ * - Out-of-order chunks are rejected
+ * - Only the DeferredExec payer can write
+ * - The buffer is closed after execution, rent to the refund destination
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  type AccountMeta,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { generateMockSecp256r1Key, signSecp256r1, type MockSecp256r1Key } from './secp256r1Utils';
+import {
+  findWalletPda,
+  findVaultPda,
+  findAuthorityPda,
+  findDeferredExecPda,
+  createCreateWalletIx,
+  createAuthorizeIx,
+  packCompactInstructions,
+  computeAccountsHash,
+  computeInstructionsHash,
+  AUTH_TYPE_SECP256R1,
+  DISC_AUTHORIZE,
+} from '@lazorkit/sdk-legacy';
+import {
+  createExecuteDeferredFromBufferIx,
+  createWriteDeferredBufferIx,
+  createWriteDeferredBufferIxs,
+  findDeferredBufferPda,
+  DEFERRED_BUFFER_HEADER_SIZE,
+} from './instructions';
+
+const MEMO_PROGRAM_ID = new PublicKey('MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr');
+const INVALID_DEFERRED_BUFFER = 3039;
+const PERMISSION_DENIED = 3002;
+
+describe('Deferred Buffer', () => {
+  let ctx: TestContext;
+  let ownerKey: MockSecp256r1Key;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    ownerKey = await generateMockSecp256r1Key();
+    const userSeed = crypto.randomBytes(32);
+    [walletPda] = findWalletPda(userSeed, PROGRAM_ID);
+    [vaultPda] = findVaultPda(walletPda, PROGRAM_ID);
+    let authBump: number;
+    [ownerAuthPda, authBump] = findAuthorityPda(walletPda, ownerKey.credentialIdHash, PROGRAM_ID);
+
+    await sendTx(ctx, [
+      createCreateWalletIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        authorityPda: ownerAuthPda,
+        userSeed,
+        authType: AUTH_TYPE_SECP256R1,
+        authBump,
+        credentialOrPubkey: ownerKey.credentialIdHash,
+        secp256r1Pubkey: ownerKey.publicKeyBytes,
+        rpId: ownerKey.rpId,
+        programId: PROGRAM_ID,
+      }),
+    ]);
+    const sig = await ctx.connection.requestAirdrop(vaultPda, 2 * LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /**
+   * Authorizes a memo of `memoLen` bytes followed by a 0.1 SOL transfer to
+   * `recipient`. ExecuteDeferred layout: 0 payer, 1 wallet, 2 vault,
+   * 3 deferred, 4 refund, 5 buffer, 6 memo, 7 system, 8 recipient.
+   */
+  async function authorizeLargePayload(recipient: PublicKey, memoLen: number) {
+    const transfer = Buffer.alloc(12);
+    transfer.writeUInt32LE(2, 0);
+    transfer.writeBigUInt64LE(BigInt(LAMPORTS_PER_SOL / 10), 4);
+    const compactIxs = [
+      {
+        programIdIndex: 6,
+        accountIndexes: [] as number[],
+        data: new Uint8Array(Buffer.alloc(memoLen, 'x')),
+      },
+      { programIdIndex: 7, accountIndexes: [2, 8], data: new Uint8Array(transfer) },
+    ];
+
+    counter++;
+    const [deferredExecPda] = findDeferredExecPda(walletPda, ownerAuthPda, counter, PROGRAM_ID);
+    const [bufferPda] = findDeferredBufferPda(deferredExecPda);
+    const remainingAccounts: AccountMeta[] = [
+      { pubkey: MEMO_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: recipient, isSigner: false, isWritable: true },
+      { pubkey: ownerAuthPda, isSigner: false, isWritable: false },
+    ];
+    const tx2AccountMetas: AccountMeta[] = [
+      { pubkey: ctx.payer.publicKey, isSigner: true, isWritable: true },
+      { pubkey: walletPda, isSigner: false, isWritable: false },
+      { pubkey: vaultPda, isSigner: false, isWritable: true },
+      { pubkey: deferredExecPda, isSigner: false, isWritable: true },
+      { pubkey: ctx.payer.publicKey, isSigner: false, isWritable: true },
+      { pubkey: bufferPda, isSigner: false, isWritable: true },
+      ...remainingAccounts,
+    ];
+    const instructionsHash = computeInstructionsHash(compactIxs);
+    const accountsHash = computeAccountsHash(tx2AccountMetas, compactIxs);
+
+    const expiry = Buffer.alloc(2);
+    expiry.writeUInt16LE(300);
+    const { authPayload, precompileIx } = await signSecp256r1({
+      key: ownerKey,
+      discriminator: new Uint8Array([DISC_AUTHORIZE]),
+      signedPayload: Buffer.concat([instructionsHash, accountsHash, expiry]),
+      slot: await getSlot(ctx),
+      counter,
+      payer: ctx.payer.publicKey,
+      sysvarIxIndex: 6,
+    });
+    await sendTx(ctx, [
+      precompileIx,
+      createAuthorizeIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        authorityPda: ownerAuthPda,
+        deferredExecPda,
+        instructionsHash,
+        accountsHash,
+        expiryOffset: 300,
+        authPayload,
+        programId: PROGRAM_ID,
+      }),
+    ]);
+
+    return {
+      deferredExecPda,
+      bufferPda,
+      payload: packCompactInstructions(compactIxs),
+      remainingAccounts,
+    };
+  }
+
+  it('executes a payload written in chunks', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const { deferredExecPda, bufferPda, payload, remainingAccounts } =
+      await authorizeLargePayload(recipient, 1_200);
+    expect(payload.length).toBeGreaterThan(1_100);
+
+    const writes = createWriteDeferredBufferIxs({
+      payer: ctx.payer.publicKey,
+      deferredExecPda,
+      payload,
+      chunkSize: 700,
+    });
+    expect(writes.length).toBe(2);
+
+    // The second chunk cannot land first
+    await sendTxExpectError(ctx, [writes[1]], [], INVALID_DEFERRED_BUFFER);
+    for (const ix of writes) await sendTx(ctx, [ix]);
+
+    const buffer = await ctx.connection.getAccountInfo(bufferPda);
+    expect(buffer!.data.length).toBe(DEFERRED_BUFFER_HEADER_SIZE + payload.length);
+
+    // Replaying the first chunk is rejected too
+    await sendTxExpectError(ctx, [writes[0]], [], INVALID_DEFERRED_BUFFER);
+
+    await sendTx(ctx, [
+      createExecuteDeferredFromBufferIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        remainingAccounts,
+      }),
+    ]);
+
+    expect(await ctx.connection.getBalance(recipient)).toBe(LAMPORTS_PER_SOL / 10);
+    const closed = await ctx.connection.getAccountInfo(bufferPda);
+    expect(closed === null || closed.lamports === 0).toBe(true);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  });
+
+  it('only lets the DeferredExec payer write', async () => {
+    const { deferredExecPda } = await authorizeLargePayload(Keypair.generate().publicKey, 16);
+
+    const stranger = Keypair.generate();
+    const sig = await ctx.connection.requestAirdrop(stranger.publicKey, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+
+    await sendTxExpectError(
+      ctx,
+      [
+        createWriteDeferredBufferIx({
+          payer: stranger.publicKey,
+          deferredExecPda,
+          offset: 0,
+          chunk: new Uint8Array(32),
+        }),
+      ],
+      [stranger],
+      PERMISSION_DENIED,
+    );
+  });
+});
@@ -0,0 +1,178 @@
+/**
+ * Deferred authorization by Ed25519 authorities and sessions (E2E).
+ *
+ * An Ed25519 Owner/Admin authorizes by signing the transaction; its counter
+ * seeds the DeferredExec PDA, and the execution is passed its Authority PDA.
+ * A session authorizes with its session key and its `deferred_nonce`; the
+ * execution then runs under the session's actions, with the Session PDA
+ * passed among the inner accounts.
+ *
+ * Coverage:
+ * - Ed25519 Owner: Authorize + ExecuteDeferred, counter-seeded PDA
+ * - Session: Authorize + ExecuteDeferred within the session's limits
+ * - Session: ExecuteDeferred above the session's SolMaxPerTx is rejected
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import {
+  Actions,
+  LazorKitClient,
+  createExecuteDeferredIx,
+  ed25519,
+  findDeferredExecPda,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  deferredTransfer,
+  encodeAuthorizeArgs,
+  DISC_AUTHORIZE,
+} from './instructions';
+
+const ACTION_SOL_MAX_PER_TX_EXCEEDED = 3023;
+
+describe('Deferred authorizers', () => {
+  let ctx: TestContext;
+  let client: LazorKitClient;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, 2 * LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /**
+   * Authorizes a transfer of `lamports` to a fresh recipient by
+   * `authorizerPda` (nonce `nonce`), signed by `signer`, then executes it.
+   */
+  async function authorizeAndExecute(opts: {
+    authorizerPda: PublicKey;
+    signer: Keypair;
+    nonce: number;
+    lamports: bigint;
+    sessionPda?: PublicKey;
+    expectedError?: number;
+  }) {
+    const recipient = Keypair.generate().publicKey;
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: opts.lamports,
+      extraAccounts: opts.sessionPda
+        ? [{ pubkey: opts.sessionPda, isSigner: false, isWritable: true }]
+        : [{ pubkey: opts.authorizerPda, isSigner: false, isWritable: false }],
+    });
+    const [deferredExecPda] = findDeferredExecPda(
+      walletPda,
+      opts.authorizerPda,
+      opts.nonce,
+      PROGRAM_ID,
+    );
+
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_AUTHORIZE,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda: opts.authorizerPda,
+          deferredExecPda,
+          args: encodeAuthorizeArgs(transfer.instructionsHash, transfer.accountsHash, 300),
+          authorizerSigner: opts.signer.publicKey,
+        }),
+      ],
+      [opts.signer],
+    );
+
+    const executeIx = createExecuteDeferredIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      deferredExecPda,
+      refundDestination: ctx.payer.publicKey,
+      packedInstructions: transfer.packedInstructions,
+      remainingAccounts: transfer.remainingAccounts,
+      programId: PROGRAM_ID,
+    });
+    if (opts.expectedError !== undefined) {
+      await sendTxExpectError(ctx, [executeIx], [], opts.expectedError);
+      return;
+    }
+    await sendTx(ctx, [executeIx]);
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(opts.lamports);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  }
+
+  it('Ed25519 Owner authorizes by signing the transaction', async () => {
+    await authorizeAndExecute({
+      authorizerPda: ownerAuthPda,
+      signer: ownerKp,
+      nonce: 1,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+    });
+    // The counter advanced, so the next authorization gets a fresh PDA
+    await authorizeAndExecute({
+      authorizerPda: ownerAuthPda,
+      signer: ownerKp,
+      nonce: 2,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+    });
+  });
+
+  it('a session authorizes under its own actions', async () => {
+    const sessionKp = Keypair.generate();
+    const { instructions, sessionPda } = await client.createSession({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(ownerKp.publicKey, ownerAuthPda),
+      sessionKey: sessionKp.publicKey,
+      expiresAt: (await getSlot(ctx)) + 9000n,
+      actions: [Actions.solMaxPerTx(BigInt(LAMPORTS_PER_SOL / 20))],
+    });
+    await sendTx(ctx, instructions, [ownerKp]);
+
+    await authorizeAndExecute({
+      authorizerPda: sessionPda,
+      signer: sessionKp,
+      nonce: 1,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+      sessionPda,
+    });
+    await authorizeAndExecute({
+      authorizerPda: sessionPda,
+      signer: sessionKp,
+      nonce: 2,
+      lamports: BigInt(LAMPORTS_PER_SOL / 10),
+      sessionPda,
+      expectedError: ACTION_SOL_MAX_PER_TX_EXCEEDED,
+    });
+  });
+});
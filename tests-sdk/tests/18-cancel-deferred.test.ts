@@ -0,0 +1,202 @@
+/**
+ * CancelDeferred (E2E).
+ *
+ * A pending DeferredExec can be closed before it runs by any Owner/Admin of
+ * the wallet, or by the Session that authorized it. Rent goes back to the
+ * payer stored in the account.
+ *
+ * Coverage:
+ * - Owner cancels its own authorization; it can no longer be executed
+ * - A session cancels its own authorization
+ * - Another session cannot cancel it
+ * - The refund destination must be the stored payer
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import {
+  LazorKitClient,
+  createExecuteDeferredIx,
+  ed25519,
+  findDeferredExecPda,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  createCancelDeferredIx,
+  deferredTransfer,
+  encodeAuthorizeArgs,
+  DISC_AUTHORIZE,
+} from './instructions';
+
+const PERMISSION_DENIED = 3002;
+
+describe('CancelDeferred', () => {
+  let ctx: TestContext;
+  let client: LazorKitClient;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let ownerCounter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  async function createSession(): Promise<{ sessionPda: PublicKey; sessionKp: Keypair }> {
+    const sessionKp = Keypair.generate();
+    const { instructions, sessionPda } = await client.createSession({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(ownerKp.publicKey, ownerAuthPda),
+      sessionKey: sessionKp.publicKey,
+      expiresAt: (await getSlot(ctx)) + 9000n,
+    });
+    await sendTx(ctx, instructions, [ownerKp]);
+    return { sessionPda, sessionKp };
+  }
+
+  /** Authorizes a 0.01 SOL transfer; returns the DeferredExec PDA and transfer. */
+  async function authorize(authorizerPda: PublicKey, signer: Keypair, nonce: number) {
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient: Keypair.generate().publicKey,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+      extraAccounts: [{ pubkey: authorizerPda, isSigner: false, isWritable: false }],
+    });
+    const [deferredExecPda] = findDeferredExecPda(walletPda, authorizerPda, nonce, PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_AUTHORIZE,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda,
+          deferredExecPda,
+          args: encodeAuthorizeArgs(transfer.instructionsHash, transfer.accountsHash, 300),
+          authorizerSigner: signer.publicKey,
+        }),
+      ],
+      [signer],
+    );
+    return { deferredExecPda, transfer };
+  }
+
+  it('Owner cancels a pending authorization', async () => {
+    const { deferredExecPda, transfer } = await authorize(ownerAuthPda, ownerKp, ++ownerCounter);
+    const rent = (await ctx.connection.getAccountInfo(deferredExecPda))!.lamports;
+    expect(rent).toBeGreaterThan(0);
+
+    await sendTx(
+      ctx,
+      [
+        createCancelDeferredIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          cancellerPda: ownerAuthPda,
+          deferredExecPda,
+          refundDestination: ctx.payer.publicKey,
+          cancellerSigner: ownerKp.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+
+    await sendTxExpectError(ctx, [
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts: transfer.remainingAccounts,
+        programId: PROGRAM_ID,
+      }),
+    ]);
+  });
+
+  it('rejects a refund destination other than the stored payer', async () => {
+    const { deferredExecPda } = await authorize(ownerAuthPda, ownerKp, ++ownerCounter);
+    await sendTxExpectError(
+      ctx,
+      [
+        createCancelDeferredIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          cancellerPda: ownerAuthPda,
+          deferredExecPda,
+          refundDestination: ownerKp.publicKey,
+          cancellerSigner: ownerKp.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).not.toBeNull();
+  });
+
+  it('only the authorizing session can cancel', async () => {
+    const authorizing = await createSession();
+    const other = await createSession();
+    const { deferredExecPda } = await authorize(authorizing.sessionPda, authorizing.sessionKp, 1);
+
+    await sendTxExpectError(
+      ctx,
+      [
+        createCancelDeferredIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          cancellerPda: other.sessionPda,
+          deferredExecPda,
+          refundDestination: ctx.payer.publicKey,
+          cancellerSigner: other.sessionKp.publicKey,
+        }),
+      ],
+      [other.sessionKp],
+      PERMISSION_DENIED,
+    );
+
+    await sendTx(
+      ctx,
+      [
+        createCancelDeferredIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          cancellerPda: authorizing.sessionPda,
+          deferredExecPda,
+          refundDestination: ctx.payer.publicKey,
+          cancellerSigner: authorizing.sessionKp.publicKey,
+        }),
+      ],
+      [authorizing.sessionKp],
+    );
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  });
+});
@@ -0,0 +1,168 @@
+/**
+ * AuthorizeRecurring (E2E).
+ *
+ * A multi-use DeferredExec runs up to `max_executions` times, at least
+ * `min_interval_slots` apart, and is closed by its last run.
+ *
+ * Coverage:
+ * - Runs again only after the interval has elapsed
+ * - Stays open until the last run, which closes it
+ * - The schedule is bounded (max_executions = 0 is rejected)
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, createExecuteDeferredIx, findDeferredExecPda } from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  deferredTransfer,
+  encodeRecurringArgs,
+  DISC_AUTHORIZE_RECURRING,
+} from './instructions';
+
+const DEFERRED_INTERVAL_NOT_ELAPSED = 3040;
+const MIN_INTERVAL_SLOTS = 10;
+
+async function waitForSlot(ctx: TestContext, slot: bigint): Promise<void> {
+  while ((await getSlot(ctx)) <= slot) {
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+}
+
+describe('AuthorizeRecurring', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  it('runs on schedule and closes after the last run', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const lamports = BigInt(LAMPORTS_PER_SOL / 100);
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports,
+      extraAccounts: [{ pubkey: ownerAuthPda, isSigner: false, isWritable: false }],
+    });
+    counter++;
+    const [deferredExecPda] = findDeferredExecPda(walletPda, ownerAuthPda, counter, PROGRAM_ID);
+
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_AUTHORIZE_RECURRING,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda: ownerAuthPda,
+          deferredExecPda,
+          args: encodeRecurringArgs(
+            transfer.instructionsHash,
+            transfer.accountsHash,
+            600,
+            2,
+            MIN_INTERVAL_SLOTS,
+          ),
+          authorizerSigner: ownerKp.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+
+    // A distinct compute limit per attempt keeps the transactions unique
+    const run = (attempt: number) => [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 + attempt }),
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts: transfer.remainingAccounts,
+        programId: PROGRAM_ID,
+      }),
+    ];
+
+    await sendTx(ctx, run(0));
+    const firstRunSlot = await getSlot(ctx);
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(lamports);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).not.toBeNull();
+
+    await sendTxExpectError(ctx, run(1), [], DEFERRED_INTERVAL_NOT_ELAPSED);
+
+    await waitForSlot(ctx, firstRunSlot + BigInt(MIN_INTERVAL_SLOTS));
+    await sendTx(ctx, run(2));
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(2n * lamports);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+
+    await sendTxExpectError(ctx, run(3));
+  });
+
+  it('rejects max_executions = 0', async () => {
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient: Keypair.generate().publicKey,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+    });
+    const [deferredExecPda] = findDeferredExecPda(
+      walletPda,
+      ownerAuthPda,
+      counter + 1,
+      PROGRAM_ID,
+    );
+    await sendTxExpectError(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_AUTHORIZE_RECURRING,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda: ownerAuthPda,
+          deferredExecPda,
+          args: encodeRecurringArgs(transfer.instructionsHash, transfer.accountsHash, 600, 0, 0),
+          authorizerSigner: ownerKp.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+  });
+});
@@ -0,0 +1,167 @@
+/**
+ * AuthorizeConditional (E2E).
+ *
+ * A conditional DeferredExec stores a predicate list; ExecuteDeferred runs it
+ * only when every predicate holds on the accounts passed in. These tests use
+ * the Clock sysvar (`slot` at offset 0) as the watched account, so the
+ * condition becomes true on its own after a few slots.
+ *
+ * Coverage:
+ * - Execution fails until the condition holds, then succeeds
+ * - A missing predicate account is rejected
+ * - Malformed predicates are rejected at authorization
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_CLOCK_PUBKEY,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, createExecuteDeferredIx, findDeferredExecPda } from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  deferredTransfer,
+  encodeConditionalArgs,
+  encodePredicate,
+  DISC_AUTHORIZE_CONDITIONAL,
+  PREDICATE_OP_GE,
+} from './instructions';
+
+const DEFERRED_CONDITION_NOT_MET = 3041;
+const INVALID_DEFERRED_PREDICATE = 3042;
+
+async function waitForSlot(ctx: TestContext, slot: bigint): Promise<void> {
+  while ((await getSlot(ctx)) <= slot) {
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+}
+
+/** `Clock.slot >= slot`. */
+function slotAtLeast(slot: bigint): Buffer {
+  const operand = Buffer.alloc(8);
+  operand.writeBigUInt64LE(slot);
+  return encodePredicate({ account: SYSVAR_CLOCK_PUBKEY, offset: 0, op: PREDICATE_OP_GE, operand });
+}
+
+describe('AuthorizeConditional', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** Authorizes a 0.01 SOL transfer guarded by `predicates`. */
+  async function authorizeConditional(recipient: PublicKey, predicates: Uint8Array[]) {
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+      extraAccounts: [
+        { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: ownerAuthPda, isSigner: false, isWritable: false },
+      ],
+    });
+    const [deferredExecPda] = findDeferredExecPda(
+      walletPda,
+      ownerAuthPda,
+      counter + 1,
+      PROGRAM_ID,
+    );
+    const ix = createAuthorizeVariantIx({
+      discriminator: DISC_AUTHORIZE_CONDITIONAL,
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorizerPda: ownerAuthPda,
+      deferredExecPda,
+      args: encodeConditionalArgs(
+        transfer.instructionsHash,
+        transfer.accountsHash,
+        600,
+        1,
+        0,
+        predicates,
+      ),
+      authorizerSigner: ownerKp.publicKey,
+    });
+    return { ix, transfer, deferredExecPda };
+  }
+
+  it('executes only once the condition holds', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const target = (await getSlot(ctx)) + 15n;
+    const { ix, transfer, deferredExecPda } = await authorizeConditional(recipient, [
+      slotAtLeast(target),
+    ]);
+    await sendTx(ctx, [ix], [ownerKp]);
+    counter++;
+
+    // A distinct compute limit per attempt keeps the transactions unique
+    const run = (attempt: number, remainingAccounts = transfer.remainingAccounts) => [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 + attempt }),
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts,
+        programId: PROGRAM_ID,
+      }),
+    ];
+
+    await sendTxExpectError(ctx, run(0), [], DEFERRED_CONDITION_NOT_MET);
+    // Without the watched account the predicate cannot be evaluated
+    await sendTxExpectError(ctx, run(1, transfer.remainingAccounts.slice(0, 2)));
+
+    await waitForSlot(ctx, target);
+    await sendTx(ctx, run(2));
+    expect(await ctx.connection.getBalance(recipient)).toBe(LAMPORTS_PER_SOL / 100);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  });
+
+  it('rejects malformed predicates', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const badOp = encodePredicate({
+      account: SYSVAR_CLOCK_PUBKEY,
+      offset: 0,
+      op: 9,
+      operand: new Uint8Array(8),
+    });
+    const { ix } = await authorizeConditional(recipient, [badOp]);
+    await sendTxExpectError(ctx, [ix], [ownerKp], INVALID_DEFERRED_PREDICATE);
+  });
+});
@@ -0,0 +1,166 @@
+/**
+ * AuthorizeUntil and the deferred lifetime bound (E2E).
+ *
+ * AuthorizeUntil takes an absolute `expires_at` slot, bounded by the wallet's
+ * `max_deferred_expiry_slots`: the ~1 hour default while the wallet has no
+ * Wallet Config PDA, or whatever an Owner set with SetWalletConfig.
+ *
+ * Coverage:
+ * - Without a Wallet Config, expiries beyond the default bound are rejected
+ * - An Owner raises the bound; a longer authorization is then accepted and
+ *   executed
+ * - Out-of-range configurations are rejected
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, createExecuteDeferredIx, findDeferredExecPda } from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  createSetWalletConfigIx,
+  deferredTransfer,
+  encodeUntilArgs,
+  findWalletConfigPda,
+  DISC_AUTHORIZE_UNTIL,
+} from './instructions';
+
+const INVALID_EXPIRY_WINDOW = 3016;
+const INVALID_WALLET_CONFIG = 3043;
+/** ~1 day at 400ms/slot. */
+const ONE_DAY_SLOTS = 216_000n;
+
+describe('AuthorizeUntil', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let configPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    [configPda] = findWalletConfigPda(walletPda);
+    await sendTx(ctx, result.instructions);
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** AuthorizeUntil of a 0.01 SOL transfer to `recipient`, expiring at `expiresAt`. */
+  function authorizeUntil(recipient: PublicKey, expiresAt: bigint) {
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+      extraAccounts: [{ pubkey: ownerAuthPda, isSigner: false, isWritable: false }],
+    });
+    const [deferredExecPda] = findDeferredExecPda(
+      walletPda,
+      ownerAuthPda,
+      counter + 1,
+      PROGRAM_ID,
+    );
+    const ix = createAuthorizeVariantIx({
+      discriminator: DISC_AUTHORIZE_UNTIL,
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorizerPda: ownerAuthPda,
+      deferredExecPda,
+      args: encodeUntilArgs(transfer.instructionsHash, transfer.accountsHash, expiresAt),
+      authorizerSigner: ownerKp.publicKey,
+      extraAccounts: [{ pubkey: configPda, isSigner: false, isWritable: false }],
+    });
+    return { ix, transfer, deferredExecPda };
+  }
+
+  it('bounds expires_at by the default lifetime without a Wallet Config', async () => {
+    const slot = await getSlot(ctx);
+    const tooLate = authorizeUntil(Keypair.generate().publicKey, slot + ONE_DAY_SLOTS);
+    await sendTxExpectError(ctx, [tooLate.ix], [ownerKp], INVALID_EXPIRY_WINDOW);
+
+    const withinDefault = authorizeUntil(Keypair.generate().publicKey, slot + 5_000n);
+    await sendTx(ctx, [withinDefault.ix], [ownerKp]);
+    counter++;
+    const info = await ctx.connection.getAccountInfo(withinDefault.deferredExecPda);
+    expect(info).not.toBeNull();
+  });
+
+  it('rejects an out-of-range configuration', async () => {
+    await sendTxExpectError(
+      ctx,
+      [
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          // max must stay above min
+          config: { maxDeferredExpirySlots: 10n, minDeferredExpirySlots: 10n },
+        }),
+      ],
+      [ownerKp],
+      INVALID_WALLET_CONFIG,
+    );
+  });
+
+  it('accepts a longer expiry once an Owner raises the bound', async () => {
+    await sendTx(
+      ctx,
+      [
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          config: { maxDeferredExpirySlots: 2n * ONE_DAY_SLOTS },
+        }),
+      ],
+      [ownerKp],
+    );
+    expect(await ctx.connection.getAccountInfo(configPda)).not.toBeNull();
+
+    const recipient = Keypair.generate().publicKey;
+    const { ix, transfer, deferredExecPda } = authorizeUntil(
+      recipient,
+      (await getSlot(ctx)) + ONE_DAY_SLOTS,
+    );
+    await sendTx(ctx, [ix], [ownerKp]);
+    counter++;
+
+    await sendTx(ctx, [
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts: transfer.remainingAccounts,
+        programId: PROGRAM_ID,
+      }),
+    ]);
+    expect(await ctx.connection.getBalance(recipient)).toBe(LAMPORTS_PER_SOL / 100);
+  });
+});
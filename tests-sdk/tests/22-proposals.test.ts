@@ -0,0 +1,183 @@
+/**
+ * Multi-approver proposals (E2E).
+ *
+ * An Owner/Admin creates a proposal naming its approvers; the wallet's
+ * `proposal_threshold` (2 by default) of them must approve before anyone can
+ * run it once with ExecuteDeferred, passing the approvers' Authority PDAs.
+ *
+ * Coverage:
+ * - The proposer's signature counts as its approval
+ * - ExecuteDeferred fails until the threshold is met
+ * - Approving twice, or approving without being an approver, is rejected
+ * - Executes once the second approver approves
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, type AccountMeta } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import {
+  LazorKitClient,
+  ROLE_ADMIN,
+  createExecuteDeferredIx,
+  findAuthorityPda,
+  findDeferredExecPda,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAddEd25519AuthorityIx,
+  createApproveProposalIx,
+  createAuthorizeVariantIx,
+  deferredTransfer,
+  encodeProposalArgs,
+  findWalletConfigPda,
+  DISC_CREATE_PROPOSAL,
+} from './instructions';
+
+const PERMISSION_DENIED = 3002;
+const PROPOSAL_ALREADY_APPROVED = 3045;
+const PROPOSAL_THRESHOLD_NOT_MET = 3046;
+
+describe('Proposals', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let adminKp: Keypair;
+  let adminAuthPda: PublicKey;
+  let outsiderKp: Keypair;
+  let outsiderAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+
+    /** Adds an Ed25519 Admin signed by the Owner. */
+    const addAdmin = async (): Promise<[Keypair, PublicKey]> => {
+      const kp = Keypair.generate();
+      const [authorityPda] = findAuthorityPda(walletPda, kp.publicKey.toBytes(), PROGRAM_ID);
+      await sendTx(
+        ctx,
+        [
+          createAddEd25519AuthorityIx({
+            payer: ctx.payer.publicKey,
+            walletPda,
+            adminAuthorityPda: ownerAuthPda,
+            adminSigner: ownerKp.publicKey,
+            newAuthorityPda: authorityPda,
+            newPubkey: kp.publicKey,
+            role: ROLE_ADMIN,
+          }),
+        ],
+        [ownerKp],
+      );
+      return [kp, authorityPda];
+    };
+    [adminKp, adminAuthPda] = await addAdmin();
+    [outsiderKp, outsiderAuthPda] = await addAdmin();
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  function approve(approverPda: PublicKey, signer: Keypair, proposalPda: PublicKey) {
+    return createApproveProposalIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      approverPda,
+      proposalPda,
+      approverSigner: signer.publicKey,
+    });
+  }
+
+  it('executes once the threshold of approvers has approved', async () => {
+    const recipient = Keypair.generate().publicKey;
+    const approverAccounts: AccountMeta[] = [ownerAuthPda, adminAuthPda].map((pubkey) => ({
+      pubkey,
+      isSigner: false,
+      isWritable: false,
+    }));
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: BigInt(LAMPORTS_PER_SOL / 100),
+      extraAccounts: approverAccounts,
+    });
+    const [proposalPda] = findDeferredExecPda(walletPda, ownerAuthPda, 1, PROGRAM_ID);
+    const [configPda] = findWalletConfigPda(walletPda);
+
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_CREATE_PROPOSAL,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda: ownerAuthPda,
+          deferredExecPda: proposalPda,
+          args: encodeProposalArgs(
+            transfer.instructionsHash,
+            transfer.accountsHash,
+            (await getSlot(ctx)) + 3_000n,
+            [ownerAuthPda, adminAuthPda],
+          ),
+          authorizerSigner: ownerKp.publicKey,
+          extraAccounts: [{ pubkey: configPda, isSigner: false, isWritable: false }],
+        }),
+      ],
+      [ownerKp],
+    );
+
+    const execute = () =>
+      createExecuteDeferredIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        vaultPda,
+        deferredExecPda: proposalPda,
+        refundDestination: ctx.payer.publicKey,
+        packedInstructions: transfer.packedInstructions,
+        remainingAccounts: transfer.remainingAccounts,
+        programId: PROGRAM_ID,
+      });
+
+    // Only the proposer's implicit approval so far
+    await sendTxExpectError(ctx, [execute()], [], PROPOSAL_THRESHOLD_NOT_MET);
+    await sendTxExpectError(
+      ctx,
+      [approve(ownerAuthPda, ownerKp, proposalPda)],
+      [ownerKp],
+      PROPOSAL_ALREADY_APPROVED,
+    );
+    await sendTxExpectError(
+      ctx,
+      [approve(outsiderAuthPda, outsiderKp, proposalPda)],
+      [outsiderKp],
+      PERMISSION_DENIED,
+    );
+
+    await sendTx(ctx, [approve(adminAuthPda, adminKp, proposalPda)], [adminKp]);
+    await sendTx(ctx, [execute()]);
+
+    expect(await ctx.connection.getBalance(recipient)).toBe(LAMPORTS_PER_SOL / 100);
+    expect(await ctx.connection.getAccountInfo(proposalPda)).toBeNull();
+  });
+});
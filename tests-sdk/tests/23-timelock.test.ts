@@ -0,0 +1,200 @@
+/**
+ * Execution timelock (E2E).
+ *
+ * With `timelock_slots` set, a vault outflow above `timelock_max_lamports`
+ * can no longer run directly; it must be queued with QueueExecute and can
+ * run `timelock_slots` later, unless an Owner/Admin vetoes it with
+ * CancelDeferred. Executions of a timelocked wallet pass its Wallet Config.
+ *
+ * Coverage:
+ * - A directly authorized outflow above the cap fails with TimelockRequired
+ * - A queued execution fails before the timelock elapses, then runs
+ * - An Owner vetoes a queued execution
+ * - The timelock cannot be weakened
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, LAMPORTS_PER_SOL, PublicKey, type AccountMeta } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, createExecuteDeferredIx, findDeferredExecPda } from '@lazorkit/sdk-legacy';
+import {
+  createAuthorizeVariantIx,
+  createCancelDeferredIx,
+  createSetWalletConfigIx,
+  deferredTransfer,
+  encodeAuthorizeArgs,
+  encodeUntilArgs,
+  findWalletConfigPda,
+  DISC_AUTHORIZE,
+  DISC_QUEUE_EXECUTE,
+  type WalletConfigArgs,
+} from './instructions';
+
+const TIMELOCK_REQUIRED = 3047;
+const TIMELOCK_NOT_ELAPSED = 3048;
+const TIMELOCK_SLOTS = 15n;
+const TIMELOCK_CONFIG: WalletConfigArgs = {
+  timelockSlots: TIMELOCK_SLOTS,
+  timelockMaxLamports: BigInt(LAMPORTS_PER_SOL / 20),
+};
+
+async function waitForSlot(ctx: TestContext, slot: bigint): Promise<void> {
+  while ((await getSlot(ctx)) <= slot) {
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+}
+
+describe('Timelock', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let configAccount: AccountMeta;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let counter = 0;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    const [configPda] = findWalletConfigPda(walletPda);
+    configAccount = { pubkey: configPda, isSigner: false, isWritable: false };
+    await sendTx(ctx, result.instructions);
+
+    await sendTx(
+      ctx,
+      [
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          config: TIMELOCK_CONFIG,
+        }),
+      ],
+      [ownerKp],
+    );
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** A 0.1 SOL transfer (above the cap) authorized with `discriminator`. */
+  async function authorizeLargeTransfer(discriminator: number) {
+    const recipient = Keypair.generate().publicKey;
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports: BigInt(LAMPORTS_PER_SOL / 10),
+      extraAccounts: [configAccount, { pubkey: ownerAuthPda, isSigner: false, isWritable: false }],
+    });
+    counter++;
+    const [deferredExecPda] = findDeferredExecPda(walletPda, ownerAuthPda, counter, PROGRAM_ID);
+    const queued = discriminator === DISC_QUEUE_EXECUTE;
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda: ownerAuthPda,
+          deferredExecPda,
+          args: queued
+            ? encodeUntilArgs(
+                transfer.instructionsHash,
+                transfer.accountsHash,
+                (await getSlot(ctx)) + TIMELOCK_SLOTS + 3_000n,
+              )
+            : encodeAuthorizeArgs(transfer.instructionsHash, transfer.accountsHash, 300),
+          authorizerSigner: ownerKp.publicKey,
+          extraAccounts: queued ? [configAccount] : [],
+        }),
+      ],
+      [ownerKp],
+    );
+    const execute = createExecuteDeferredIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      deferredExecPda,
+      refundDestination: ctx.payer.publicKey,
+      packedInstructions: transfer.packedInstructions,
+      remainingAccounts: transfer.remainingAccounts,
+      programId: PROGRAM_ID,
+    });
+    return { recipient, deferredExecPda, execute };
+  }
+
+  it('rejects a direct outflow above the cap', async () => {
+    const { execute } = await authorizeLargeTransfer(DISC_AUTHORIZE);
+    await sendTxExpectError(ctx, [execute], [], TIMELOCK_REQUIRED);
+  });
+
+  it('runs a queued execution once the timelock has elapsed', async () => {
+    const queuedAt = await getSlot(ctx);
+    const { recipient, deferredExecPda, execute } =
+      await authorizeLargeTransfer(DISC_QUEUE_EXECUTE);
+
+    await sendTxExpectError(ctx, [execute], [], TIMELOCK_NOT_ELAPSED);
+
+    await waitForSlot(ctx, queuedAt + TIMELOCK_SLOTS + 2n);
+    await sendTx(ctx, [execute]);
+    expect(await ctx.connection.getBalance(recipient)).toBe(LAMPORTS_PER_SOL / 10);
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  });
+
+  it('lets an Owner veto a queued execution', async () => {
+    const { deferredExecPda } = await authorizeLargeTransfer(DISC_QUEUE_EXECUTE);
+    await sendTx(
+      ctx,
+      [
+        createCancelDeferredIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          cancellerPda: ownerAuthPda,
+          deferredExecPda,
+          refundDestination: ctx.payer.publicKey,
+          cancellerSigner: ownerKp.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+    expect(await ctx.connection.getAccountInfo(deferredExecPda)).toBeNull();
+  });
+
+  it('cannot be weakened', async () => {
+    await sendTxExpectError(
+      ctx,
+      [
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          config: { ...TIMELOCK_CONFIG, timelockSlots: 0n },
+        }),
+      ],
+      [ownerKp],
+      TIMELOCK_REQUIRED,
+    );
+  });
+});
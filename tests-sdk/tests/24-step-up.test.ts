@@ -0,0 +1,346 @@
+/**
+ * Step-up authentication (E2E).
+ *
+ * With a `step_up_authority` set, a vault SOL outflow above
+ * `step_up_lamports` needs that authority's approval. For ExecuteDeferred,
+ * that means the step-up authority authorized the DeferredExec. Executions
+ * of a step-up wallet pass its Wallet Config.
+ *
+ * Coverage:
+ * - An outflow above the threshold authorized by the Owner fails with StepUpRequired
+ * - The same outflow below the threshold runs
+ * - The same outflow authorized by the step-up authority runs
+ * - An Ed25519 Owner Execute above the threshold runs with a Secp256r1 step-up co-signer
+ * - It fails without the co-signer, with an empty co-signer payload, or with a
+ *   co-signature over other accounts
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  type TransactionInstruction,
+  type AccountMeta,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { generateMockSecp256r1Key, signSecp256r1 } from './secp256r1Utils';
+import {
+  LazorKitClient,
+  ROLE_ADMIN,
+  DISC_EXECUTE,
+  computeAccountsHash,
+  createExecuteDeferredIx,
+  createExecuteIx,
+  ed25519,
+  findAuthorityPda,
+  findDeferredExecPda,
+  packCompactInstructions,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAddEd25519AuthorityIx,
+  createAuthorizeVariantIx,
+  createSetWalletConfigIx,
+  deferredTransfer,
+  encodeAuthorizeArgs,
+  findWalletConfigPda,
+  DISC_AUTHORIZE,
+} from './instructions';
+
+const STEP_UP_REQUIRED = 3049;
+const STEP_UP_LAMPORTS = BigInt(LAMPORTS_PER_SOL / 20);
+
+describe('Step-up', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let configAccount: AccountMeta;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let stepUpKp: Keypair;
+  let stepUpAuthPda: PublicKey;
+  const counters = new Map<string, number>();
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    const [configPda] = findWalletConfigPda(walletPda);
+    configAccount = { pubkey: configPda, isSigner: false, isWritable: false };
+    await sendTx(ctx, result.instructions);
+
+    // An Ed25519 Admin (e.g. a hardware key) is the step-up authority
+    stepUpKp = Keypair.generate();
+    [stepUpAuthPda] = findAuthorityPda(walletPda, stepUpKp.publicKey.toBytes(), PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAddEd25519AuthorityIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          adminAuthorityPda: ownerAuthPda,
+          adminSigner: ownerKp.publicKey,
+          newAuthorityPda: stepUpAuthPda,
+          newPubkey: stepUpKp.publicKey,
+          role: ROLE_ADMIN,
+        }),
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          config: { stepUpAuthority: stepUpAuthPda, stepUpLamports: STEP_UP_LAMPORTS },
+        }),
+      ],
+      [ownerKp],
+    );
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /** Authorizes a transfer of `lamports` by `authorizerPda` and returns its execution. */
+  async function authorize(authorizerPda: PublicKey, signer: Keypair, lamports: bigint) {
+    const recipient = Keypair.generate().publicKey;
+    const transfer = deferredTransfer({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      recipient,
+      lamports,
+      extraAccounts: [configAccount, { pubkey: authorizerPda, isSigner: false, isWritable: false }],
+    });
+    const counter = (counters.get(authorizerPda.toBase58()) ?? 0) + 1;
+    counters.set(authorizerPda.toBase58(), counter);
+    const [deferredExecPda] = findDeferredExecPda(walletPda, authorizerPda, counter, PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAuthorizeVariantIx({
+          discriminator: DISC_AUTHORIZE,
+          payer: ctx.payer.publicKey,
+          walletPda,
+          authorizerPda,
+          deferredExecPda,
+          args: encodeAuthorizeArgs(transfer.instructionsHash, transfer.accountsHash, 300),
+          authorizerSigner: signer.publicKey,
+        }),
+      ],
+      [signer],
+    );
+    const execute = createExecuteDeferredIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      vaultPda,
+      deferredExecPda,
+      refundDestination: ctx.payer.publicKey,
+      packedInstructions: transfer.packedInstructions,
+      remainingAccounts: transfer.remainingAccounts,
+      programId: PROGRAM_ID,
+    });
+    return { recipient, execute };
+  }
+
+  it('requires the step-up authority above the threshold', async () => {
+    const { execute } = await authorize(ownerAuthPda, ownerKp, 2n * STEP_UP_LAMPORTS);
+    await sendTxExpectError(ctx, [execute], [], STEP_UP_REQUIRED);
+  });
+
+  it('lets the Owner move up to the threshold', async () => {
+    const { recipient, execute } = await authorize(ownerAuthPda, ownerKp, STEP_UP_LAMPORTS);
+    await sendTx(ctx, [execute]);
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(STEP_UP_LAMPORTS);
+  });
+
+  it('runs above the threshold when the step-up authority authorized it', async () => {
+    const { recipient, execute } = await authorize(stepUpAuthPda, stepUpKp, 2n * STEP_UP_LAMPORTS);
+    await sendTx(ctx, [execute]);
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(2n * STEP_UP_LAMPORTS);
+  });
+});
+
+describe('Step-up co-signer on Execute', () => {
+  let ctx: TestContext;
+  let client: LazorKitClient;
+  let walletPda: PublicKey;
+  let vaultPda: PublicKey;
+  let configAccount: AccountMeta;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let stepUpKey: Awaited<ReturnType<typeof generateMockSecp256r1Key>>;
+  let stepUpAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    vaultPda = result.vaultPda;
+    ownerAuthPda = result.authorityPda;
+    const [configPda] = findWalletConfigPda(walletPda);
+    configAccount = { pubkey: configPda, isSigner: false, isWritable: false };
+    await sendTx(ctx, result.instructions);
+
+    // A passkey Admin is the step-up authority of the Ed25519 Owner
+    stepUpKey = await generateMockSecp256r1Key();
+    const added = await client.addAuthority({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminSigner: ed25519(ownerKp.publicKey, ownerAuthPda),
+      newAuthority: {
+        type: 'secp256r1',
+        credentialIdHash: stepUpKey.credentialIdHash,
+        compressedPubkey: stepUpKey.publicKeyBytes,
+        rpId: stepUpKey.rpId,
+      },
+      role: ROLE_ADMIN,
+    });
+    stepUpAuthPda = added.newAuthorityPda;
+    await sendTx(
+      ctx,
+      [
+        ...added.instructions,
+        createSetWalletConfigIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          config: { stepUpAuthority: stepUpAuthPda, stepUpLamports: STEP_UP_LAMPORTS },
+        }),
+      ],
+      [ownerKp],
+    );
+
+    const sig = await ctx.connection.requestAirdrop(vaultPda, LAMPORTS_PER_SOL);
+    await ctx.connection.confirmTransaction(sig, 'confirmed');
+  });
+
+  /**
+   * Builds an Owner Execute transferring `lamports`. The co-signer signs the
+   * compact instructions and the accounts hash for `recipient`; `sentTo`
+   * swaps in another recipient after signing.
+   */
+  async function cosignedExecute(params: {
+    lamports: bigint;
+    cosigner: 'signed' | 'absent' | 'empty';
+    sentTo?: PublicKey;
+  }) {
+    const recipient = Keypair.generate().publicKey;
+    const data = Buffer.alloc(12);
+    data.writeUInt32LE(2, 0);
+    data.writeBigUInt64LE(params.lamports, 4);
+    // System transfer from the vault (3) to the recipient (6)
+    const compactIxs = [{ programIdIndex: 5, accountIndexes: [3, 6], data: new Uint8Array(data) }];
+    const packed = packCompactInstructions(compactIxs);
+
+    const remaining = (to: PublicKey): AccountMeta[] => [
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: to, isSigner: false, isWritable: true },
+      { pubkey: ownerKp.publicKey, isSigner: true, isWritable: false },
+      configAccount,
+      ...(params.cosigner === 'absent'
+        ? []
+        : [{ pubkey: stepUpAuthPda, isSigner: false, isWritable: true }]),
+    ];
+    const accountsHash = computeAccountsHash(
+      [
+        { pubkey: ctx.payer.publicKey, isSigner: true, isWritable: false },
+        { pubkey: walletPda, isSigner: false, isWritable: false },
+        { pubkey: ownerAuthPda, isSigner: false, isWritable: true },
+        { pubkey: vaultPda, isSigner: false, isWritable: true },
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false },
+        ...remaining(recipient),
+      ],
+      compactIxs,
+    );
+
+    // The Ed25519 Owner's auth payload is empty; the co-signer's follows it
+    // with its length (u16 LE).
+    let cosignerPayload = new Uint8Array(0);
+    const precompileIxs: TransactionInstruction[] = [];
+    if (params.cosigner === 'signed') {
+      const { authPayload, precompileIx } = await signSecp256r1({
+        key: stepUpKey,
+        discriminator: new Uint8Array([DISC_EXECUTE]),
+        signedPayload: Buffer.concat([packed, accountsHash]),
+        slot: await getSlot(ctx),
+        counter: (await client.readCounter(stepUpAuthPda)) + 1,
+        payer: ctx.payer.publicKey,
+        sysvarIxIndex: 4,
+      });
+      cosignerPayload = authPayload;
+      precompileIxs.push(precompileIx);
+    }
+    const payloadLen = Buffer.alloc(2);
+    payloadLen.writeUInt16LE(cosignerPayload.length, 0);
+    const authPayload =
+      params.cosigner === 'absent'
+        ? new Uint8Array(0)
+        : new Uint8Array(Buffer.concat([cosignerPayload, payloadLen]));
+
+    const sentTo = params.sentTo ?? recipient;
+    const execute = createExecuteIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorityPda: ownerAuthPda,
+      vaultPda,
+      packedInstructions: packed,
+      authPayload,
+      remainingAccounts: remaining(sentTo),
+      programId: PROGRAM_ID,
+    });
+    return { recipient: sentTo, instructions: [...precompileIxs, execute] };
+  }
+
+  it('runs above the threshold with the Secp256r1 co-signature', async () => {
+    const lamports = 2n * STEP_UP_LAMPORTS;
+    const { recipient, instructions } = await cosignedExecute({ lamports, cosigner: 'signed' });
+    await sendTx(ctx, instructions, [ownerKp]);
+    expect(BigInt(await ctx.connection.getBalance(recipient))).toBe(lamports);
+  });
+
+  it('fails with StepUpRequired without the co-signer', async () => {
+    const lamports = 2n * STEP_UP_LAMPORTS;
+    const { instructions } = await cosignedExecute({ lamports, cosigner: 'absent' });
+    await sendTxExpectError(ctx, instructions, [ownerKp], STEP_UP_REQUIRED);
+  });
+
+  it('rejects an empty co-signer payload', async () => {
+    const lamports = 2n * STEP_UP_LAMPORTS;
+    const { instructions } = await cosignedExecute({ lamports, cosigner: 'empty' });
+    await sendTxExpectError(ctx, instructions, [ownerKp]);
+  });
+
+  it('rejects a co-signature over another recipient', async () => {
+    const { instructions } = await cosignedExecute({
+      lamports: 2n * STEP_UP_LAMPORTS,
+      cosigner: 'signed',
+      sentTo: Keypair.generate().publicKey,
+    });
+    await sendTxExpectError(ctx, instructions, [ownerKp]);
+  });
+});
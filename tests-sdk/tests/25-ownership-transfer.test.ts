@@ -0,0 +1,120 @@
+/**
+ * Two-step ownership transfer (E2E).
+ *
+ * An Owner proposes the next Owner with ProposeOwnership; nothing changes
+ * until that key proves possession with AcceptOwnership, which creates its
+ * Owner Authority and closes the proposer. CancelOwnershipTransfer withdraws
+ * a pending transfer.
+ *
+ * Coverage:
+ * - Only one transfer can be pending at a time
+ * - Accepting without the pending key's signature fails
+ * - An Owner cancels a pending transfer
+ * - The pending Owner accepts; the proposer is closed, rent to the refund destination
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import { setupTest, sendTx, sendTxExpectError, PROGRAM_ID, type TestContext } from './common';
+import { LazorKitClient, findAuthorityPda } from '@lazorkit/sdk-legacy';
+import {
+  createAcceptOwnershipIx,
+  createCancelOwnershipTransferIx,
+  createProposeEd25519OwnershipIx,
+  findPendingOwnerPda,
+} from './instructions';
+
+describe('Ownership transfer', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let pendingPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    [pendingPda] = findPendingOwnerPda(walletPda);
+    await sendTx(ctx, result.instructions);
+  });
+
+  function propose(newOwner: PublicKey) {
+    return createProposeEd25519OwnershipIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      ownerAuthorityPda: ownerAuthPda,
+      ownerSigner: ownerKp.publicKey,
+      newOwner,
+      refundDestination: ctx.payer.publicKey,
+    });
+  }
+
+  function accept(newOwner: PublicKey, signer: PublicKey) {
+    const [newOwnerAuthPda] = findAuthorityPda(walletPda, newOwner.toBytes(), PROGRAM_ID);
+    return createAcceptOwnershipIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      proposerAuthorityPda: ownerAuthPda,
+      newOwnerAuthorityPda: newOwnerAuthPda,
+      refundDestination: ctx.payer.publicKey,
+      newOwnerSigner: signer,
+    });
+  }
+
+  it('allows one pending transfer, cancellable by an Owner', async () => {
+    await sendTx(ctx, [propose(Keypair.generate().publicKey)], [ownerKp]);
+    expect(await ctx.connection.getAccountInfo(pendingPda)).not.toBeNull();
+
+    await sendTxExpectError(ctx, [propose(Keypair.generate().publicKey)], [ownerKp]);
+
+    await sendTx(
+      ctx,
+      [
+        createCancelOwnershipTransferIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          ownerAuthorityPda: ownerAuthPda,
+          ownerSigner: ownerKp.publicKey,
+          refundDestination: ctx.payer.publicKey,
+        }),
+      ],
+      [ownerKp],
+    );
+    expect(await ctx.connection.getAccountInfo(pendingPda)).toBeNull();
+  });
+
+  it('hands ownership over once the new key accepts', async () => {
+    const newOwnerKp = Keypair.generate();
+    await sendTx(ctx, [propose(newOwnerKp.publicKey)], [ownerKp]);
+
+    // Someone else cannot accept in the new owner's place
+    const impostor = Keypair.generate();
+    await sendTxExpectError(ctx, [accept(newOwnerKp.publicKey, impostor.publicKey)], [impostor]);
+
+    await sendTx(ctx, [accept(newOwnerKp.publicKey, newOwnerKp.publicKey)], [newOwnerKp]);
+
+    const [newOwnerAuthPda] = findAuthorityPda(
+      walletPda,
+      newOwnerKp.publicKey.toBytes(),
+      PROGRAM_ID,
+    );
+    const newOwner = await ctx.connection.getAccountInfo(newOwnerAuthPda);
+    expect(newOwner).not.toBeNull();
+    // Header: discriminator, authority_type (0 = Ed25519), role (0 = Owner)
+    expect(newOwner!.data[1]).toBe(0);
+    expect(newOwner!.data[2]).toBe(0);
+
+    const proposer = await ctx.connection.getAccountInfo(ownerAuthPda);
+    expect(proposer === null || proposer.lamports === 0).toBe(true);
+    expect(await ctx.connection.getAccountInfo(pendingPda)).toBeNull();
+  });
+});
@@ -0,0 +1,89 @@
+/**
+ * Proof of possession on AddAuthority (E2E).
+ *
+ * With `REGISTER_FLAG_PROVE_POSSESSION`, the new key must authenticate in the
+ * same instruction (an Ed25519 key signs the transaction), so a mistyped or
+ * foreign key cannot be registered. The authority is then marked
+ * `AUTHORITY_FLAG_VERIFIED`.
+ *
+ * Coverage:
+ * - The new key signs; the authority is marked verified
+ * - Without the new key's signature the registration fails
+ * - Without the flag nothing is proven and the authority is not marked
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, PublicKey } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import { setupTest, sendTx, sendTxExpectError, PROGRAM_ID, type TestContext } from './common';
+import { LazorKitClient, ROLE_ADMIN, findAuthorityPda } from '@lazorkit/sdk-legacy';
+import { createAddEd25519AuthorityIx } from './instructions';
+
+/** `AuthorityAccountHeader::flags` offset and `AUTHORITY_FLAG_VERIFIED`. */
+const AUTHORITY_FLAGS_OFFSET = 5;
+const AUTHORITY_FLAG_VERIFIED = 1;
+
+describe('Proof of possession', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    await sendTx(ctx, result.instructions);
+  });
+
+  function addAdmin(newKey: PublicKey, provePossession: boolean) {
+    const [authorityPda] = findAuthorityPda(walletPda, newKey.toBytes(), PROGRAM_ID);
+    const ix = createAddEd25519AuthorityIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminAuthorityPda: ownerAuthPda,
+      adminSigner: ownerKp.publicKey,
+      newAuthorityPda: authorityPda,
+      newPubkey: newKey,
+      role: ROLE_ADMIN,
+      provePossession,
+    });
+    return { ix, authorityPda };
+  }
+
+  async function authorityFlags(authorityPda: PublicKey): Promise<number> {
+    const info = await ctx.connection.getAccountInfo(authorityPda);
+    return info!.data[AUTHORITY_FLAGS_OFFSET];
+  }
+
+  it('marks the authority verified when the new key signs', async () => {
+    const newKp = Keypair.generate();
+    const { ix, authorityPda } = addAdmin(newKp.publicKey, true);
+    await sendTx(ctx, [ix], [ownerKp, newKp]);
+    expect(await authorityFlags(authorityPda)).toBe(AUTHORITY_FLAG_VERIFIED);
+  });
+
+  it('rejects the registration without the new key signature', async () => {
+    const newKey = Keypair.generate().publicKey;
+    const { ix, authorityPda } = addAdmin(newKey, true);
+    // Same instruction, but the new key is not a signer
+    ix.keys = ix.keys.map((meta) =>
+      meta.pubkey.equals(newKey) ? { ...meta, isSigner: false } : meta,
+    );
+    await sendTxExpectError(ctx, [ix], [ownerKp]);
+    expect(await ctx.connection.getAccountInfo(authorityPda)).toBeNull();
+  });
+
+  it('leaves unproven authorities unmarked', async () => {
+    const { ix, authorityPda } = addAdmin(Keypair.generate().publicKey, false);
+    await sendTx(ctx, [ix], [ownerKp]);
+    expect(await authorityFlags(authorityPda)).toBe(0);
+  });
+});
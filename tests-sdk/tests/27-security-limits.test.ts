@@ -0,0 +1,174 @@
+/**
+ * Per-wallet security limits (E2E).
+ *
+ * SetWalletConfig can tighten the session length, the signature freshness
+ * window and the deferred lifetime bounds, and stop Admins from adding
+ * Spenders. Limits that differ from the program defaults set
+ * `WALLET_FLAG_LIMITS`, after which the affected instructions must pass the
+ * Wallet Config PDA: omitting it cannot skip the limits.
+ *
+ * Coverage:
+ * - CreateSession without the Wallet Config PDA fails
+ * - Sessions longer than `max_session_slots` are rejected
+ * - Admins cannot add Spenders once the flag is cleared; Owners still can
+ * - Out-of-range limits are rejected
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import { Keypair, PublicKey, type AccountMeta } from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import {
+  LazorKitClient,
+  ROLE_ADMIN,
+  ROLE_SPENDER,
+  findAuthorityPda,
+  findSessionPda,
+} from '@lazorkit/sdk-legacy';
+import {
+  createAddEd25519AuthorityIx,
+  createCreateSessionIx,
+  createSetWalletConfigIx,
+  findWalletConfigPda,
+  type WalletConfigArgs,
+} from './instructions';
+
+const PERMISSION_DENIED = 3002;
+const INVALID_SESSION_DURATION = 3008;
+const INVALID_WALLET_CONFIG = 3043;
+const MAX_SESSION_SLOTS = 1_000n;
+
+describe('Security limits', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let configAccount: AccountMeta;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let adminKp: Keypair;
+  let adminAuthPda: PublicKey;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    const [configPda] = findWalletConfigPda(walletPda);
+    configAccount = { pubkey: configPda, isSigner: false, isWritable: false };
+    await sendTx(ctx, result.instructions);
+
+    adminKp = Keypair.generate();
+    [adminAuthPda] = findAuthorityPda(walletPda, adminKp.publicKey.toBytes(), PROGRAM_ID);
+    await sendTx(
+      ctx,
+      [
+        createAddEd25519AuthorityIx({
+          payer: ctx.payer.publicKey,
+          walletPda,
+          adminAuthorityPda: ownerAuthPda,
+          adminSigner: ownerKp.publicKey,
+          newAuthorityPda: adminAuthPda,
+          newPubkey: adminKp.publicKey,
+          role: ROLE_ADMIN,
+        }),
+      ],
+      [ownerKp],
+    );
+  });
+
+  function setConfig(config: WalletConfigArgs) {
+    return createSetWalletConfigIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      ownerAuthorityPda: ownerAuthPda,
+      ownerSigner: ownerKp.publicKey,
+      config,
+    });
+  }
+
+  async function createSession(lifetime: bigint, extraAccounts: AccountMeta[]) {
+    const sessionKey = Keypair.generate().publicKey;
+    const [sessionPda] = findSessionPda(walletPda, sessionKey.toBytes(), PROGRAM_ID);
+    return createCreateSessionIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorityPda: ownerAuthPda,
+      sessionPda,
+      sessionKey,
+      expiresAt: (await getSlot(ctx)) + lifetime,
+      authorizerSigner: ownerKp.publicKey,
+      extraAccounts,
+    });
+  }
+
+  function addSpender(adminPda: PublicKey, admin: Keypair) {
+    const spender = Keypair.generate().publicKey;
+    const [spenderPda] = findAuthorityPda(walletPda, spender.toBytes(), PROGRAM_ID);
+    return createAddEd25519AuthorityIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      adminAuthorityPda: adminPda,
+      adminSigner: admin.publicKey,
+      newAuthorityPda: spenderPda,
+      newPubkey: spender,
+      role: ROLE_SPENDER,
+      extraAccounts: [configAccount],
+    });
+  }
+
+  it('rejects out-of-range limits', async () => {
+    await sendTxExpectError(
+      ctx,
+      [setConfig({ maxSignatureAgeSlots: 1_000n })],
+      [ownerKp],
+      INVALID_WALLET_CONFIG,
+    );
+    await sendTxExpectError(
+      ctx,
+      [setConfig({ maxSessionSlots: 0n })],
+      [ownerKp],
+      INVALID_WALLET_CONFIG,
+    );
+  });
+
+  it('enforces a shorter session limit', async () => {
+    await sendTx(
+      ctx,
+      [setConfig({ maxSessionSlots: MAX_SESSION_SLOTS, flags: 0 })],
+      [ownerKp],
+    );
+
+    // The wallet now has WALLET_FLAG_LIMITS: the config cannot be left out
+    await sendTxExpectError(ctx, [await createSession(500n, [])], [ownerKp]);
+
+    await sendTxExpectError(
+      ctx,
+      [await createSession(2n * MAX_SESSION_SLOTS, [configAccount])],
+      [ownerKp],
+      INVALID_SESSION_DURATION,
+    );
+    await sendTx(ctx, [await createSession(500n, [configAccount])], [ownerKp]);
+  });
+
+  it('stops Admins from adding Spenders', async () => {
+    await sendTxExpectError(
+      ctx,
+      [addSpender(adminAuthPda, adminKp)],
+      [adminKp],
+      PERMISSION_DENIED,
+    );
+    await sendTx(ctx, [addSpender(ownerAuthPda, ownerKp)], [ownerKp]);
+  });
+});
@@ -0,0 +1,180 @@
+/**
+ * Emergency freeze (E2E).
+ *
+ * Any Owner, or the wallet's guardian key, can freeze the wallet: executions
+ * and new sessions then fail with WalletFrozen. Only an Owner unfreezes it,
+ * after `unfreeze_delay_slots` when one is configured. While frozen the
+ * config can only have its guardian replaced or cleared.
+ *
+ * Coverage:
+ * - The guardian freezes; CreateSession fails with WalletFrozen
+ * - The guardian cannot freeze a frozen wallet again
+ * - Only the guardian may change while frozen
+ * - An Owner requests the unfreeze, then completes it after the delay
+ * - A stranger cannot freeze
+ */
+import { describe, it, expect, beforeAll } from 'vitest';
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  type AccountMeta,
+} from '@solana/web3.js';
+import * as crypto from 'crypto';
+import {
+  setupTest,
+  sendTx,
+  sendTxExpectError,
+  getSlot,
+  PROGRAM_ID,
+  type TestContext,
+} from './common';
+import { LazorKitClient, findSessionPda } from '@lazorkit/sdk-legacy';
+import {
+  createCreateSessionIx,
+  createFreezeIx,
+  createSetWalletConfigIx,
+  createUnfreezeIx,
+  findWalletConfigPda,
+  type WalletConfigArgs,
+} from './instructions';
+
+const PERMISSION_DENIED = 3002;
+const TIMELOCK_NOT_ELAPSED = 3048;
+const WALLET_FROZEN = 3051;
+const UNFREEZE_DELAY_SLOTS = 10n;
+/** `WalletAccount::flags` offset and `WALLET_FLAG_FROZEN`. */
+const WALLET_FLAGS_OFFSET = 3;
+const WALLET_FLAG_FROZEN = 1 << 3;
+
+async function waitForSlot(ctx: TestContext, slot: bigint): Promise<void> {
+  while ((await getSlot(ctx)) <= slot) {
+    await new Promise((resolve) => setTimeout(resolve, 400));
+  }
+}
+
+describe('Freeze', () => {
+  let ctx: TestContext;
+  let walletPda: PublicKey;
+  let configAccount: AccountMeta;
+  let ownerKp: Keypair;
+  let ownerAuthPda: PublicKey;
+  let guardianKp: Keypair;
+  let config: WalletConfigArgs;
+
+  beforeAll(async () => {
+    ctx = await setupTest();
+    const client = new LazorKitClient(ctx.connection, PROGRAM_ID);
+
+    ownerKp = Keypair.generate();
+    const result = await client.createWallet({
+      payer: ctx.payer.publicKey,
+      userSeed: crypto.randomBytes(32),
+      owner: { type: 'ed25519', publicKey: ownerKp.publicKey },
+    });
+    walletPda = result.walletPda;
+    ownerAuthPda = result.authorityPda;
+    const [configPda] = findWalletConfigPda(walletPda);
+    configAccount = { pubkey: configPda, isSigner: false, isWritable: false };
+    await sendTx(ctx, result.instructions);
+
+    guardianKp = Keypair.generate();
+    config = { guardian: guardianKp.publicKey, unfreezeDelaySlots: UNFREEZE_DELAY_SLOTS };
+    await sendTx(ctx, [setConfig(config)], [ownerKp]);
+  });
+
+  function setConfig(args: WalletConfigArgs) {
+    return createSetWalletConfigIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      ownerAuthorityPda: ownerAuthPda,
+      ownerSigner: ownerKp.publicKey,
+      config: args,
+    });
+  }
+
+  function guardianFreeze(guardian: Keypair) {
+    return createFreezeIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      guardian: guardian.publicKey,
+      withConfig: true,
+    });
+  }
+
+  /** Unfreeze; a distinct compute limit per attempt keeps the transactions unique. */
+  function unfreeze(attempt: number) {
+    return [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 + attempt }),
+      createUnfreezeIx({
+        payer: ctx.payer.publicKey,
+        walletPda,
+        ownerAuthorityPda: ownerAuthPda,
+        ownerSigner: ownerKp.publicKey,
+        withConfig: true,
+      }),
+    ];
+  }
+
+  async function createSession() {
+    const sessionKey = Keypair.generate().publicKey;
+    const [sessionPda] = findSessionPda(walletPda, sessionKey.toBytes(), PROGRAM_ID);
+    return createCreateSessionIx({
+      payer: ctx.payer.publicKey,
+      walletPda,
+      authorityPda: ownerAuthPda,
+      sessionPda,
+      sessionKey,
+      expiresAt: (await getSlot(ctx)) + 500n,
+      authorizerSigner: ownerKp.publicKey,
+      extraAccounts: [configAccount],
+    });
+  }
+
+  async function isFrozen(): Promise<boolean> {
+    const wallet = await ctx.connection.getAccountInfo(walletPda);
+    return (wallet!.data[WALLET_FLAGS_OFFSET] & WALLET_FLAG_FROZEN) !== 0;
+  }
+
+  it('rejects a stranger', async () => {
+    const stranger = Keypair.generate();
+    await sendTxExpectError(ctx, [guardianFreeze(stranger)], [stranger], PERMISSION_DENIED);
+    expect(await isFrozen()).toBe(false);
+  });
+
+  it('lets the guardian freeze, once', async () => {
+    await sendTx(ctx, [guardianFreeze(guardianKp)], [guardianKp]);
+    expect(await isFrozen()).toBe(true);
+
+    await sendTxExpectError(ctx, [await createSession()], [ownerKp], WALLET_FROZEN);
+    await sendTxExpectError(ctx, [guardianFreeze(guardianKp)], [guardianKp], WALLET_FROZEN);
+  });
+
+  it('only lets the guardian change while frozen', async () => {
+    await sendTxExpectError(
+      ctx,
+      [setConfig({ ...config, unfreezeDelaySlots: 0n })],
+      [ownerKp],
+      WALLET_FROZEN,
+    );
+
+    // Replace the guardian, every other field unchanged
+    guardianKp = Keypair.generate();
+    config = { ...config, guardian: guardianKp.publicKey };
+    await sendTx(ctx, [setConfig(config)], [ownerKp]);
+  });
+
+  it('unfreezes after the delay on an Owner request', async () => {
+    const requestedAt = await getSlot(ctx);
+    await sendTx(ctx, unfreeze(0), [ownerKp]);
+    expect(await isFrozen()).toBe(true);
+
+    await sendTxExpectError(ctx, unfreeze(1), [ownerKp], TIMELOCK_NOT_ELAPSED);
+
+    await waitForSlot(ctx, requestedAt + UNFREEZE_DELAY_SLOTS + 2n);
+    await sendTx(ctx, unfreeze(2), [ownerKp]);
+    expect(await isFrozen()).toBe(false);
+
+    await sendTx(ctx, [await createSession()], [ownerKp]);
+  });
+});
@@ -585,6 +585,7 @@ async function benchDeferredExecution(
     remainingAccounts: [
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: recipient, isSigner: false, isWritable: true },
+      { pubkey: authPda, isSigner: false, isWritable: false },
     ],
       programId: PROGRAM_ID,
   });
@@ -717,6 +718,7 @@ async function benchDeferredMultiInstruction(
       { pubkey: recipient1, isSigner: false, isWritable: true },
       { pubkey: recipient2, isSigner: false, isWritable: true },
       { pubkey: recipient3, isSigner: false, isWritable: true },
+      { pubkey: authPda, isSigner: false, isWritable: false },
     ],
       programId: PROGRAM_ID,
   });
@@ -836,7 +838,7 @@ async function main() {
 
   if (secp256r1Result && deferredSingle) {
     const totalDeferredCU = deferredSingle.authorize.cu + deferredSingle.executeDeferred.cu;
-    const deferredRent = calculateRent(176);
+    const deferredRent = calculateRent(192);
 
     console.log('\n\n## Deferred Execution vs Immediate Execute (Secp256r1)\n');
     console.log('| Metric | Immediate Execute | Deferred TX1 (Authorize) | Deferred TX2 (Execute) | Deferred Total | Notes |');
@@ -864,8 +866,8 @@ async function main() {
     { name: 'Wallet PDA', dataSize: 8 },
     { name: 'Authority (Ed25519)', dataSize: 80 },
     { name: 'Authority (Secp256r1)', dataSize: 125 }, // 48 header + 32 cred_hash + 33 pubkey + 1 rpIdLen + ~11 rpId
-    { name: 'Session', dataSize: 80 },
-    { name: 'DeferredExec (temporary)', dataSize: 176 },
+    { name: 'Session', dataSize: 144 }, // header only, no actions
+    { name: 'DeferredExec (temporary)', dataSize: 192 },
   ];
 
   console.log('\n\n## Rent-Exempt Costs\n');
@@ -453,6 +453,12 @@ async function main() {
       payer: payer.publicKey,
       deferredPayload,
     });
+    // The authorizer's Authority PDA must be passed to the execution
+    execIxs[execIxs.length - 1].keys.push({
+      pubkey: secpOwnerAuthPda,
+      isSigner: false,
+      isWritable: false,
+    });
     const r2 = await sendAndMeasure(connection, payer, execIxs);
     record('ExecuteDeferred (Deferred TX2)', r2);
   }
@@ -0,0 +1,960 @@
+/**
+ * Low-level builders for instructions (and instruction-data forms) that
+ * @lazorkit/sdk-legacy does not cover yet. Layouts mirror the program's
+ * processors byte for byte; see docs/Architecture.md for each instruction.
+ *
+ * Ed25519 authorities authenticate by signing the transaction, so builders
+ * take the signer key as `authorizerSigner` (appended as a readonly signer).
+ * Secp256r1 callers pass the auth payload from `signSecp256r1` and the
+ * sysvar_instructions account in `extraAccounts`.
+ */
+import {
+  PublicKey,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+  TransactionInstruction,
+  type AccountMeta,
+} from '@solana/web3.js';
+import {
+  computeAccountsHash,
+  computeInstructionsHash,
+  packCompactInstructions,
+} from '@lazorkit/sdk-legacy';
+import { PROGRAM_ID } from './common';
+
+// ─── CreateSession (disc=5) ──────────────────────────────────────────
+
+export const DISC_CREATE_SESSION = 5;
+
+export const SESSION_FLAG_REQUIRE_CREATOR = 1 << 0;
+export const SESSION_FLAG_TEMPLATE = 1 << 1;
+export const SESSION_FLAG_STAGED = 1 << 2;
+
+/** Bit 15 of `actions_len`: a flags byte (and template) follows the actions. */
+export const CREATE_SESSION_FLAGS_PRESENT = 0x8000;
+
+/** Session layout versions (`SessionAccount.version`, byte 2). */
+export const LEGACY_SESSION_VERSION = 1;
+export const SESSION_VERSION = 2;
+
+export interface CreateSessionParams {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  authorityPda: PublicKey;
+  sessionPda: PublicKey;
+  sessionKey: PublicKey;
+  expiresAt: bigint;
+  actions?: Uint8Array;
+  /** `SESSION_FLAG_*`; when set, the flagged form is used. */
+  flags?: number;
+  template?: PublicKey;
+  authorizerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}
+
+/**
+ * The signed part of CreateSession instruction data:
+ * `[session_key(32)][expires_at(8)][actions_len(2)][actions][flags(1)?][template(32)?]`.
+ * The flags byte is only written when `flags` is given.
+ */
+export function encodeCreateSessionArgs(params: {
+  sessionKey: PublicKey;
+  expiresAt: bigint;
+  actions?: Uint8Array;
+  flags?: number;
+  template?: PublicKey;
+}): Buffer {
+  const actions = params.actions ?? new Uint8Array(0);
+  const flagged = params.flags !== undefined;
+  const head = Buffer.alloc(42);
+  params.sessionKey.toBuffer().copy(head, 0);
+  head.writeBigUInt64LE(params.expiresAt, 32);
+  head.writeUInt16LE(actions.length | (flagged ? CREATE_SESSION_FLAGS_PRESENT : 0), 40);
+  const parts: Buffer[] = [head, Buffer.from(actions)];
+  if (flagged) {
+    parts.push(Buffer.from([params.flags!]));
+    if (params.template) parts.push(params.template.toBuffer());
+  }
+  return Buffer.concat(parts);
+}
+
+export function createCreateSessionIx(params: CreateSessionParams): TransactionInstruction {
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.authorityPda, isSigner: false, isWritable: true },
+    { pubkey: params.sessionPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+  ];
+  if (params.template) {
+    keys.push({ pubkey: params.template, isSigner: false, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  if (params.authorizerSigner) {
+    keys.push({ pubkey: params.authorizerSigner, isSigner: true, isWritable: false });
+  }
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_CREATE_SESSION]),
+      encodeCreateSessionArgs(params),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+// ─── Action buffers ──────────────────────────────────────────────────
+
+/** First byte of a versioned actions buffer. */
+export const ACTION_BUFFER_MAGIC = 0xff;
+/** Highest actions buffer version the program understands. */
+export const ACTION_BUFFER_VERSION = 1;
+/** Version 1+ type byte bit: reject the action if its type is unknown. */
+export const ACTION_FLAG_CRITICAL = 0x80;
+const ACTION_HEADER_SIZE = 11;
+
+/**
+ * Converts a version 0 actions buffer (as built by the SDK's `Actions`) to
+ * version 1: prepends `[magic][version]` and marks every action critical,
+ * except the types listed in `advisoryTypes`.
+ */
+export function versionActionsBuffer(
+  buf: Uint8Array,
+  advisoryTypes: number[] = [],
+): Buffer {
+  const out = Buffer.concat([Buffer.from([ACTION_BUFFER_MAGIC, ACTION_BUFFER_VERSION]), buf]);
+  let cursor = 2;
+  while (cursor < out.length) {
+    const type = out[cursor];
+    if (!advisoryTypes.includes(type)) out[cursor] = type | ACTION_FLAG_CRITICAL;
+    cursor += ACTION_HEADER_SIZE + out.readUInt16LE(cursor + 1);
+  }
+  return out;
+}
+
+// ─── RotateSession (disc=10) ─────────────────────────────────────────
+
+export const DISC_ROTATE_SESSION = 10;
+
+/** Bytes an admin signs for RotateSession: `old_session ‖ new_session_key ‖ payer`. */
+export function rotateSessionSignedPayload(
+  oldSessionPda: PublicKey,
+  newSessionKey: PublicKey,
+  payer: PublicKey,
+): Buffer {
+  return Buffer.concat([oldSessionPda.toBuffer(), newSessionKey.toBuffer(), payer.toBuffer()]);
+}
+
+export function createRotateSessionIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  authorityPda: PublicKey;
+  oldSessionPda: PublicKey;
+  newSessionPda: PublicKey;
+  newSessionKey: PublicKey;
+  authorizerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.authorityPda, isSigner: false, isWritable: true },
+    { pubkey: params.oldSessionPda, isSigner: false, isWritable: true },
+    { pubkey: params.newSessionPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ...(params.extraAccounts ?? []),
+  ];
+  if (params.authorizerSigner) {
+    keys.push({ pubkey: params.authorizerSigner, isSigner: true, isWritable: false });
+  }
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_ROTATE_SESSION]),
+      params.newSessionKey.toBuffer(),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+// ─── AddAuthority (disc=1) with registration flags ───────────────────
+
+export const DISC_ADD_AUTHORITY = 1;
+
+export const REGISTER_FLAG_PROVE_POSSESSION = 1 << 0;
+export const REGISTER_FLAG_EXPIRES = 1 << 1;
+
+/** Size of the expiry extension (`[expires_at(8)][funder(32)]`) after the authority data. */
+export const AUTHORITY_EXPIRY_EXTENSION_SIZE = 40;
+
+/**
+ * AddAuthority for an Ed25519 key, with `expiresAt` sent after the
+ * authority data (`REGISTER_FLAG_EXPIRES`). The admin is an Ed25519
+ * authority signing the transaction. With `provePossession`
+ * (`REGISTER_FLAG_PROVE_POSSESSION`) the new key must sign too and the
+ * authority is marked verified; both auth payloads are then empty.
+ * `extraAccounts` carries the Wallet Config PDA when the wallet needs it.
+ */
+export function createAddEd25519AuthorityIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  adminAuthorityPda: PublicKey;
+  adminSigner: PublicKey;
+  newAuthorityPda: PublicKey;
+  newPubkey: PublicKey;
+  role: number;
+  expiresAt?: bigint;
+  provePossession?: boolean;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  let flags = 0;
+  if (params.expiresAt !== undefined) flags |= REGISTER_FLAG_EXPIRES;
+  if (params.provePossession) flags |= REGISTER_FLAG_PROVE_POSSESSION;
+  const args = Buffer.alloc(8);
+  args.writeUInt8(0, 0);
+  args.writeUInt8(params.role, 1);
+  args.writeUInt8(flags, 2);
+  const parts = [Buffer.from([DISC_ADD_AUTHORITY]), args, params.newPubkey.toBuffer()];
+  if (params.expiresAt !== undefined) {
+    const expiry = Buffer.alloc(8);
+    expiry.writeBigUInt64LE(params.expiresAt);
+    parts.push(expiry);
+  }
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.adminAuthorityPda, isSigner: false, isWritable: false },
+    { pubkey: params.newAuthorityPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+    { pubkey: params.adminSigner, isSigner: true, isWritable: false },
+  ];
+  if (params.provePossession) {
+    // Empty new-authority payload: `[new_auth_payload_len = 0]`
+    parts.push(Buffer.alloc(2));
+    keys.push({ pubkey: params.newPubkey, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat(parts),
+  });
+}
+
+// ─── CloseExpiredAuthority (disc=27) ─────────────────────────────────
+
+export const DISC_CLOSE_EXPIRED_AUTHORITY = 27;
+
+/** Permissionless: any payer may submit it; rent goes to the recorded funder. */
+export function createCloseExpiredAuthorityIx(params: {
+  walletPda: PublicKey;
+  authorityPda: PublicKey;
+  funder: PublicKey;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys: [
+      { pubkey: params.walletPda, isSigner: false, isWritable: false },
+      { pubkey: params.authorityPda, isSigner: false, isWritable: true },
+      { pubkey: params.funder, isSigner: false, isWritable: true },
+    ],
+    data: Buffer.from([DISC_CLOSE_EXPIRED_AUTHORITY]),
+  });
+}
+
+// ─── Deferred Buffer (disc=15) and buffered ExecuteDeferred ──────────
+
+export const DISC_WRITE_DEFERRED_BUFFER = 15;
+export const DISC_EXECUTE_DEFERRED = 7;
+
+/** `DeferredBufferAccount` header: disc, version, bump, padding, deferred, payer. */
+export const DEFERRED_BUFFER_HEADER_SIZE = 72;
+export const MAX_DEFERRED_BUFFER_SIZE = 10 * 1024;
+
+export function findDeferredBufferPda(
+  deferredExecPda: PublicKey,
+  programId: PublicKey = PROGRAM_ID,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('deferred_buffer'), deferredExecPda.toBuffer()],
+    programId,
+  );
+}
+
+/** Appends `chunk` at `offset`, which must equal the bytes written so far. */
+export function createWriteDeferredBufferIx(params: {
+  payer: PublicKey;
+  deferredExecPda: PublicKey;
+  offset: number;
+  chunk: Uint8Array;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [bufferPda] = findDeferredBufferPda(params.deferredExecPda, programId);
+  const offset = Buffer.alloc(4);
+  offset.writeUInt32LE(params.offset);
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.payer, isSigner: true, isWritable: true },
+      { pubkey: params.deferredExecPda, isSigner: false, isWritable: false },
+      { pubkey: bufferPda, isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ],
+    data: Buffer.concat([Buffer.from([DISC_WRITE_DEFERRED_BUFFER]), offset, params.chunk]),
+  });
+}
+
+/** Splits `payload` into WriteDeferredBuffer instructions of at most `chunkSize` bytes. */
+export function createWriteDeferredBufferIxs(params: {
+  payer: PublicKey;
+  deferredExecPda: PublicKey;
+  payload: Uint8Array;
+  chunkSize?: number;
+  programId?: PublicKey;
+}): TransactionInstruction[] {
+  const chunkSize = params.chunkSize ?? 900;
+  const ixs: TransactionInstruction[] = [];
+  for (let offset = 0; offset < params.payload.length; offset += chunkSize) {
+    ixs.push(
+      createWriteDeferredBufferIx({
+        ...params,
+        offset,
+        chunk: params.payload.subarray(offset, offset + chunkSize),
+      }),
+    );
+  }
+  return ixs;
+}
+
+/**
+ * ExecuteDeferred from a Deferred Buffer: empty instruction data, the buffer
+ * at account 5 and the inner accounts after it. Compact instruction indexes
+ * count the buffer, so inner accounts start at 6.
+ */
+export function createExecuteDeferredFromBufferIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  vaultPda: PublicKey;
+  deferredExecPda: PublicKey;
+  refundDestination: PublicKey;
+  remainingAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [bufferPda] = findDeferredBufferPda(params.deferredExecPda, programId);
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.payer, isSigner: true, isWritable: true },
+      { pubkey: params.walletPda, isSigner: false, isWritable: false },
+      { pubkey: params.vaultPda, isSigner: false, isWritable: true },
+      { pubkey: params.deferredExecPda, isSigner: false, isWritable: true },
+      { pubkey: params.refundDestination, isSigner: false, isWritable: true },
+      { pubkey: bufferPda, isSigner: false, isWritable: true },
+      ...(params.remainingAccounts ?? []),
+    ],
+    data: Buffer.from([DISC_EXECUTE_DEFERRED]),
+  });
+}
+
+// ─── Authorize variants (disc=6, 17, 18, 19) ─────────────────────────
+
+export const DISC_AUTHORIZE = 6;
+
+/** Signed arguments of `Authorize`: `instructions_hash ‖ accounts_hash ‖ expiry_offset`. */
+export function encodeAuthorizeArgs(
+  instructionsHash: Uint8Array,
+  accountsHash: Uint8Array,
+  expiryOffset: number,
+): Buffer {
+  const expiry = Buffer.alloc(2);
+  expiry.writeUInt16LE(expiryOffset);
+  return Buffer.concat([instructionsHash, accountsHash, expiry]);
+}
+
+export const DISC_AUTHORIZE_RECURRING = 17;
+
+/**
+ * Signed arguments of `AuthorizeRecurring`: the `Authorize` arguments plus
+ * `max_executions u16 ‖ min_interval_slots u32`.
+ */
+export function encodeRecurringArgs(
+  instructionsHash: Uint8Array,
+  accountsHash: Uint8Array,
+  expiryOffset: number,
+  maxExecutions: number,
+  minIntervalSlots: number,
+): Buffer {
+  const schedule = Buffer.alloc(6);
+  schedule.writeUInt16LE(maxExecutions, 0);
+  schedule.writeUInt32LE(minIntervalSlots, 2);
+  return Buffer.concat([
+    encodeAuthorizeArgs(instructionsHash, accountsHash, expiryOffset),
+    schedule,
+  ]);
+}
+
+export const DISC_AUTHORIZE_CONDITIONAL = 18;
+
+export const PREDICATE_OP_EQ = 0;
+export const PREDICATE_OP_NE = 1;
+export const PREDICATE_OP_LT = 2;
+export const PREDICATE_OP_LE = 3;
+export const PREDICATE_OP_GT = 4;
+export const PREDICATE_OP_GE = 5;
+/** `op` bit: ordered comparisons are two's-complement signed. */
+export const PREDICATE_FLAG_SIGNED = 0x80;
+
+/**
+ * One predicate: `[account 32][offset u16][len u8][op u8][operand len]`. The
+ * operand is a little-endian integer as wide as the compared value.
+ */
+export function encodePredicate(params: {
+  account: PublicKey;
+  offset: number;
+  op: number;
+  operand: Uint8Array;
+}): Buffer {
+  const header = Buffer.alloc(4);
+  header.writeUInt16LE(params.offset, 0);
+  header.writeUInt8(params.operand.length, 2);
+  header.writeUInt8(params.op, 3);
+  return Buffer.concat([params.account.toBuffer(), header, params.operand]);
+}
+
+/**
+ * Signed arguments of `AuthorizeConditional`: the `AuthorizeRecurring`
+ * arguments plus `predicates_len u16 ‖ predicates`.
+ */
+export function encodeConditionalArgs(
+  instructionsHash: Uint8Array,
+  accountsHash: Uint8Array,
+  expiryOffset: number,
+  maxExecutions: number,
+  minIntervalSlots: number,
+  predicates: Uint8Array[],
+): Buffer {
+  const list = Buffer.concat(predicates);
+  const len = Buffer.alloc(2);
+  len.writeUInt16LE(list.length);
+  return Buffer.concat([
+    encodeRecurringArgs(
+      instructionsHash,
+      accountsHash,
+      expiryOffset,
+      maxExecutions,
+      minIntervalSlots,
+    ),
+    len,
+    list,
+  ]);
+}
+
+export const DISC_AUTHORIZE_UNTIL = 19;
+
+/**
+ * Signed arguments of `AuthorizeUntil`: `instructions_hash ‖ accounts_hash ‖
+ * expires_at u64`. The Wallet Config PDA goes after the auth extra.
+ */
+export function encodeUntilArgs(
+  instructionsHash: Uint8Array,
+  accountsHash: Uint8Array,
+  expiresAt: bigint,
+): Buffer {
+  const expiry = Buffer.alloc(8);
+  expiry.writeBigUInt64LE(expiresAt);
+  return Buffer.concat([instructionsHash, accountsHash, expiry]);
+}
+
+/**
+ * Any `Authorize*` instruction. `args` are its signed arguments (see the
+ * `encode*Args` helpers); the authorizer is an Owner/Admin Authority PDA or a
+ * Session PDA. Ed25519 and session keys sign the transaction as
+ * `authorizerSigner` (the auth extra, account 6); Secp256r1 passes the sysvar
+ * instructions account in `extraAccounts` instead. `extraAccounts` also
+ * carries the Wallet Config PDA when the wallet needs it.
+ */
+export function createAuthorizeVariantIx(params: {
+  discriminator: number;
+  payer: PublicKey;
+  walletPda: PublicKey;
+  authorizerPda: PublicKey;
+  deferredExecPda: PublicKey;
+  args: Uint8Array;
+  authorizerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.authorizerPda, isSigner: false, isWritable: true },
+    { pubkey: params.deferredExecPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+  ];
+  if (params.authorizerSigner) {
+    keys.push({ pubkey: params.authorizerSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([params.discriminator]),
+      params.args,
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+/**
+ * A vault-to-`recipient` SOL transfer for deferred execution, with its
+ * hashes. ExecuteDeferred layout: 0 payer, 1 wallet, 2 vault, 3 deferred,
+ * 4 refund (the payer), 5 System Program, 6 recipient, then `extraAccounts`
+ * (Session, Wallet Config, approver PDAs...), which the instruction does not
+ * reference.
+ */
+export function deferredTransfer(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  vaultPda: PublicKey;
+  recipient: PublicKey;
+  lamports: bigint;
+  extraAccounts?: AccountMeta[];
+}) {
+  const data = Buffer.alloc(12);
+  data.writeUInt32LE(2, 0);
+  data.writeBigUInt64LE(params.lamports, 4);
+  const compactIxs = [{ programIdIndex: 5, accountIndexes: [2, 6], data: new Uint8Array(data) }];
+  const remainingAccounts: AccountMeta[] = [
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: params.recipient, isSigner: false, isWritable: true },
+    ...(params.extraAccounts ?? []),
+  ];
+  const tx2AccountMetas: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.vaultPda, isSigner: false, isWritable: true },
+    { pubkey: PublicKey.default, isSigner: false, isWritable: true },
+    { pubkey: params.payer, isSigner: false, isWritable: true },
+    ...remainingAccounts,
+  ];
+  return {
+    instructionsHash: computeInstructionsHash(compactIxs),
+    accountsHash: computeAccountsHash(tx2AccountMetas, compactIxs),
+    packedInstructions: packCompactInstructions(compactIxs),
+    remainingAccounts,
+  };
+}
+
+// ─── CancelDeferred (disc=16) ────────────────────────────────────────
+
+export const DISC_CANCEL_DEFERRED = 16;
+
+/**
+ * Closes a pending DeferredExec, rent to its stored payer. The canceller is
+ * any Owner/Admin Authority PDA or the Session PDA that authorized it; Ed25519
+ * and session keys sign as `cancellerSigner`, Secp256r1 passes `authPayload`
+ * and the sysvar instructions account in `extraAccounts`.
+ */
+export function createCancelDeferredIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  cancellerPda: PublicKey;
+  deferredExecPda: PublicKey;
+  refundDestination: PublicKey;
+  cancellerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.cancellerPda, isSigner: false, isWritable: true },
+    { pubkey: params.deferredExecPda, isSigner: false, isWritable: true },
+    { pubkey: params.refundDestination, isSigner: false, isWritable: true },
+  ];
+  if (params.cancellerSigner) {
+    keys.push({ pubkey: params.cancellerSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_CANCEL_DEFERRED]),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+// ─── SetWalletConfig (disc=20) ───────────────────────────────────────
+
+export const DISC_SET_WALLET_CONFIG = 20;
+
+export function findWalletConfigPda(
+  walletPda: PublicKey,
+  programId: PublicKey = PROGRAM_ID,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('wallet_config'), walletPda.toBuffer()],
+    programId,
+  );
+}
+
+/** Wallet configuration; omitted fields take the program defaults. */
+export interface WalletConfigArgs {
+  maxDeferredExpirySlots?: bigint;
+  proposalThreshold?: number;
+  timelockSlots?: bigint;
+  timelockMaxLamports?: bigint;
+  sensitivePrograms?: PublicKey[];
+  timelockMints?: { mint: PublicKey; maxAmount: bigint }[];
+  /** Authority PDA of the step-up approver (none by default). */
+  stepUpAuthority?: PublicKey;
+  stepUpLamports?: bigint;
+  stepUpMints?: { mint: PublicKey; maxAmount: bigint }[];
+  maxSignatureAgeSlots?: bigint;
+  maxSessionSlots?: bigint;
+  minDeferredExpirySlots?: bigint;
+  /** `WALLET_CONFIG_FLAG_*` (Admins may add Spenders by default). */
+  flags?: number;
+  guardian?: PublicKey;
+  unfreezeDelaySlots?: bigint;
+}
+
+export const WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS = 1;
+
+function u64(value: bigint): Buffer {
+  const buf = Buffer.alloc(8);
+  buf.writeBigUInt64LE(value);
+  return buf;
+}
+
+/** The signed `SetWalletConfig` arguments, in instruction order. */
+export function encodeWalletConfigArgs(config: WalletConfigArgs): Buffer {
+  const programs = config.sensitivePrograms ?? [];
+  const timelockMints = config.timelockMints ?? [];
+  const mints = config.stepUpMints ?? [];
+  return Buffer.concat([
+    u64(config.maxDeferredExpirySlots ?? 9_000n),
+    Buffer.from([config.proposalThreshold ?? 2]),
+    u64(config.timelockSlots ?? 0n),
+    u64(config.timelockMaxLamports ?? 0n),
+    Buffer.from([programs.length]),
+    ...programs.map((program) => program.toBuffer()),
+    Buffer.from([timelockMints.length]),
+    ...timelockMints.map(({ mint, maxAmount }) => Buffer.concat([mint.toBuffer(), u64(maxAmount)])),
+    (config.stepUpAuthority ?? PublicKey.default).toBuffer(),
+    u64(config.stepUpLamports ?? 0n),
+    Buffer.from([mints.length]),
+    ...mints.map(({ mint, maxAmount }) => Buffer.concat([mint.toBuffer(), u64(maxAmount)])),
+    u64(config.maxSignatureAgeSlots ?? 150n),
+    u64(config.maxSessionSlots ?? 6_480_000n),
+    u64(config.minDeferredExpirySlots ?? 10n),
+    Buffer.from([config.flags ?? WALLET_CONFIG_FLAG_ADMIN_ADDS_SPENDERS]),
+    (config.guardian ?? PublicKey.default).toBuffer(),
+    u64(config.unfreezeDelaySlots ?? 0n),
+  ]);
+}
+
+/**
+ * Writes the Wallet Config PDA, signed by an Ed25519 Owner (`ownerSigner`)
+ * or a Secp256r1 Owner (`authPayload` plus the sysvar instructions account
+ * in `extraAccounts`).
+ */
+export function createSetWalletConfigIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  ownerAuthorityPda: PublicKey;
+  config: WalletConfigArgs;
+  ownerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [configPda] = findWalletConfigPda(params.walletPda, programId);
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: true },
+    { pubkey: params.ownerAuthorityPda, isSigner: false, isWritable: true },
+    { pubkey: configPda, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+  ];
+  if (params.ownerSigner) {
+    keys.push({ pubkey: params.ownerSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_SET_WALLET_CONFIG]),
+      encodeWalletConfigArgs(params.config),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+// ─── Proposals (disc=21, 22) ─────────────────────────────────────────
+
+export const DISC_CREATE_PROPOSAL = 21;
+export const DISC_APPROVE_PROPOSAL = 22;
+
+/**
+ * Signed arguments of `CreateProposal`: `instructions_hash ‖ accounts_hash ‖
+ * expires_at u64 ‖ approver_count u8 ‖ approvers`. Send it with
+ * `createAuthorizeVariantIx`, the proposer as authorizer and the Wallet
+ * Config PDA after the auth extra.
+ */
+export function encodeProposalArgs(
+  instructionsHash: Uint8Array,
+  accountsHash: Uint8Array,
+  expiresAt: bigint,
+  approvers: PublicKey[],
+): Buffer {
+  return Buffer.concat([
+    instructionsHash,
+    accountsHash,
+    u64(expiresAt),
+    Buffer.from([approvers.length]),
+    ...approvers.map((approver) => approver.toBuffer()),
+  ]);
+}
+
+/** Records the approval of `approverPda`, one of the proposal's approvers. */
+export function createApproveProposalIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  approverPda: PublicKey;
+  proposalPda: PublicKey;
+  approverSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: params.approverPda, isSigner: false, isWritable: true },
+    { pubkey: params.proposalPda, isSigner: false, isWritable: true },
+  ];
+  if (params.approverSigner) {
+    keys.push({ pubkey: params.approverSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId: params.programId ?? PROGRAM_ID,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_APPROVE_PROPOSAL]),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+// ─── QueueExecute (disc=23) ──────────────────────────────────────────
+
+/**
+ * Queues an execution of a timelocked wallet. Same arguments as
+ * `AuthorizeUntil` (`encodeUntilArgs`), sent with `createAuthorizeVariantIx`
+ * and the Wallet Config PDA after the auth extra; `expires_at` must lie after
+ * the timelock.
+ */
+export const DISC_QUEUE_EXECUTE = 23;
+
+// ─── Two-step ownership transfer (disc=24, 25, 26) ───────────────────
+
+export const DISC_PROPOSE_OWNERSHIP = 24;
+export const DISC_ACCEPT_OWNERSHIP = 25;
+export const DISC_CANCEL_OWNERSHIP_TRANSFER = 26;
+
+export function findPendingOwnerPda(
+  walletPda: PublicKey,
+  programId: PublicKey = PROGRAM_ID,
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('pending_owner'), walletPda.toBuffer()],
+    programId,
+  );
+}
+
+/**
+ * Records `newOwner` (an Ed25519 key) as the pending Owner. The proposer and
+ * Pending Owner rent later go to `refundDestination`.
+ */
+export function createProposeEd25519OwnershipIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  ownerAuthorityPda: PublicKey;
+  ownerSigner: PublicKey;
+  newOwner: PublicKey;
+  refundDestination: PublicKey;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [pendingPda] = findPendingOwnerPda(params.walletPda, programId);
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.payer, isSigner: true, isWritable: true },
+      { pubkey: params.walletPda, isSigner: false, isWritable: false },
+      { pubkey: params.ownerAuthorityPda, isSigner: false, isWritable: true },
+      { pubkey: pendingPda, isSigner: false, isWritable: true },
+      { pubkey: params.refundDestination, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+      { pubkey: params.ownerSigner, isSigner: true, isWritable: false },
+    ],
+    data: Buffer.concat([
+      Buffer.from([DISC_PROPOSE_OWNERSHIP, 0]),
+      params.newOwner.toBuffer(),
+    ]),
+  });
+}
+
+/**
+ * The pending Owner accepts: an Ed25519 key signs as `newOwnerSigner`, a
+ * Secp256r1 passkey passes `authPayload` and the sysvar instructions account
+ * in `extraAccounts`.
+ */
+export function createAcceptOwnershipIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  proposerAuthorityPda: PublicKey;
+  newOwnerAuthorityPda: PublicKey;
+  refundDestination: PublicKey;
+  newOwnerSigner?: PublicKey;
+  authPayload?: Uint8Array;
+  extraAccounts?: AccountMeta[];
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [pendingPda] = findPendingOwnerPda(params.walletPda, programId);
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: false },
+    { pubkey: pendingPda, isSigner: false, isWritable: true },
+    { pubkey: params.proposerAuthorityPda, isSigner: false, isWritable: true },
+    { pubkey: params.newOwnerAuthorityPda, isSigner: false, isWritable: true },
+    { pubkey: params.refundDestination, isSigner: false, isWritable: true },
+    { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+  ];
+  if (params.newOwnerSigner) {
+    keys.push({ pubkey: params.newOwnerSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([DISC_ACCEPT_OWNERSHIP]),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+/** Any Owner withdraws the pending transfer (Ed25519 signer). */
+export function createCancelOwnershipTransferIx(params: {
+  payer: PublicKey;
+  walletPda: PublicKey;
+  ownerAuthorityPda: PublicKey;
+  ownerSigner: PublicKey;
+  refundDestination: PublicKey;
+  programId?: PublicKey;
+}): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const [pendingPda] = findPendingOwnerPda(params.walletPda, programId);
+  return new TransactionInstruction({
+    programId,
+    keys: [
+      { pubkey: params.payer, isSigner: true, isWritable: true },
+      { pubkey: params.walletPda, isSigner: false, isWritable: false },
+      { pubkey: params.ownerAuthorityPda, isSigner: false, isWritable: true },
+      { pubkey: pendingPda, isSigner: false, isWritable: true },
+      { pubkey: params.refundDestination, isSigner: false, isWritable: true },
+      { pubkey: params.ownerSigner, isSigner: true, isWritable: false },
+    ],
+    data: Buffer.from([DISC_CANCEL_OWNERSHIP_TRANSFER]),
+  });
+}
+
+// ─── Freeze / Unfreeze (disc=28, 29) ─────────────────────────────────
+
+export const DISC_FREEZE = 28;
+export const DISC_UNFREEZE = 29;
+
+/**
+ * Freeze or Unfreeze. The caller is an Owner Authority PDA (Ed25519 keys sign
+ * as `ownerSigner`, Secp256r1 passes `authPayload` and the sysvar
+ * instructions account in `extraAccounts`) or, for Freeze only, the guardian
+ * signing the transaction. Wallets with `WALLET_FLAG_LIMITS` pass their
+ * Wallet Config PDA (`withConfig`); an Owner's signature covers it.
+ */
+function createFreezeInstruction(
+  discriminator: number,
+  params: {
+    payer: PublicKey;
+    walletPda: PublicKey;
+    ownerAuthorityPda?: PublicKey;
+    ownerSigner?: PublicKey;
+    guardian?: PublicKey;
+    withConfig?: boolean;
+    authPayload?: Uint8Array;
+    extraAccounts?: AccountMeta[];
+    programId?: PublicKey;
+  },
+): TransactionInstruction {
+  const programId = params.programId ?? PROGRAM_ID;
+  const keys: AccountMeta[] = [
+    { pubkey: params.payer, isSigner: true, isWritable: true },
+    { pubkey: params.walletPda, isSigner: false, isWritable: true },
+    params.guardian
+      ? { pubkey: params.guardian, isSigner: true, isWritable: false }
+      : { pubkey: params.ownerAuthorityPda!, isSigner: false, isWritable: true },
+  ];
+  if (params.withConfig) {
+    const [configPda] = findWalletConfigPda(params.walletPda, programId);
+    keys.push({ pubkey: configPda, isSigner: false, isWritable: true });
+  }
+  if (params.ownerSigner) {
+    keys.push({ pubkey: params.ownerSigner, isSigner: true, isWritable: false });
+  }
+  keys.push(...(params.extraAccounts ?? []));
+  return new TransactionInstruction({
+    programId,
+    keys,
+    data: Buffer.concat([
+      Buffer.from([discriminator]),
+      Buffer.from(params.authPayload ?? new Uint8Array(0)),
+    ]),
+  });
+}
+
+export function createFreezeIx(
+  params: Parameters<typeof createFreezeInstruction>[1],
+): TransactionInstruction {
+  return createFreezeInstruction(DISC_FREEZE, params);
+}
+
+export function createUnfreezeIx(
+  params: Omit<Parameters<typeof createFreezeInstruction>[1], 'guardian'>,
+): TransactionInstruction {
+  return createFreezeInstruction(DISC_UNFREEZE, params);
+}